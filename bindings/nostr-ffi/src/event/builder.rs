@@ -107,6 +107,8 @@ impl EventBuilder {
                 sender_keys.deref(),
                 XOnlyPublicKey::from_str(&receiver_pubkey)?,
                 content,
+                None,
+                None,
             )?,
         })
     }
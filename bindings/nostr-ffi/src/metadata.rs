@@ -59,6 +59,13 @@ impl Metadata {
         Arc::new(builder)
     }
 
+    pub fn website(self: Arc<Self>, website: String) -> Result<Arc<Self>> {
+        let website = Url::parse(&website)?;
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.metadata = builder.metadata.website(website);
+        Ok(Arc::new(builder))
+    }
+
     pub fn picture(self: Arc<Self>, picture: String) -> Result<Arc<Self>> {
         let picture = Url::parse(&picture)?;
         let mut builder = unwrap_or_clone_arc(self);
@@ -66,9 +73,28 @@ impl Metadata {
         Ok(Arc::new(builder))
     }
 
+    pub fn banner(self: Arc<Self>, banner: String) -> Result<Arc<Self>> {
+        let banner = Url::parse(&banner)?;
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.metadata = builder.metadata.banner(banner);
+        Ok(Arc::new(builder))
+    }
+
     pub fn nip05(self: Arc<Self>, nip05: String) -> Arc<Self> {
         let mut builder = unwrap_or_clone_arc(self);
         builder.metadata = builder.metadata.nip05(nip05);
         Arc::new(builder)
     }
+
+    pub fn lud06(self: Arc<Self>, lud06: String) -> Arc<Self> {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.metadata = builder.metadata.lud06(lud06);
+        Arc::new(builder)
+    }
+
+    pub fn lud16(self: Arc<Self>, lud16: String) -> Arc<Self> {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.metadata = builder.metadata.lud16(lud16);
+        Arc::new(builder)
+    }
 }
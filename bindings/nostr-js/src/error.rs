@@ -0,0 +1,15 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use wasm_bindgen::JsValue;
+
+pub type Result<T, E = JsValue> = std::result::Result<T, E>;
+
+/// Helper to convert the `E` to a JS `Error`
+#[inline]
+pub fn into_err<E>(error: E) -> JsValue
+where
+    E: std::error::Error,
+{
+    js_sys::Error::new(&error.to_string()).into()
+}
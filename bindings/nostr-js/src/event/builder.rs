@@ -0,0 +1,83 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::ops::Deref;
+
+use js_sys::Array;
+use nostr::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use super::JsEvent;
+use crate::error::{into_err, Result};
+use crate::key::JsKeys;
+use crate::types::JsMetadata;
+
+#[wasm_bindgen(js_name = EventBuilder)]
+pub struct JsEventBuilder {
+    builder: EventBuilder,
+}
+
+impl Deref for JsEventBuilder {
+    type Target = EventBuilder;
+    fn deref(&self) -> &Self::Target {
+        &self.builder
+    }
+}
+
+/// Parse an array of string arrays (i.e. `[["p", "<pubkey>"]]`) into [`Tag`]s
+fn parse_tags(tags: Array) -> Result<Vec<Tag>> {
+    let mut new_tags: Vec<Tag> = Vec::with_capacity(tags.length() as usize);
+    for tag in tags.iter() {
+        let tag: Vec<String> = Array::from(&tag)
+            .iter()
+            .filter_map(|value| value.as_string())
+            .collect();
+        new_tags.push(Tag::try_from(tag).map_err(into_err)?);
+    }
+    Ok(new_tags)
+}
+
+#[wasm_bindgen(js_class = EventBuilder)]
+impl JsEventBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(kind: u64, content: String, tags: Array) -> Result<JsEventBuilder> {
+        Ok(Self {
+            builder: EventBuilder::new(kind.into(), content, &parse_tags(tags)?),
+        })
+    }
+
+    /// Set a custom `created_at` timestamp (seconds)
+    #[wasm_bindgen(js_name = customCreatedAt)]
+    pub fn custom_created_at(&self, created_at: u64) -> Self {
+        Self {
+            builder: self
+                .builder
+                .clone()
+                .custom_created_at(Timestamp::from(created_at)),
+        }
+    }
+
+    #[wasm_bindgen(js_name = toEvent)]
+    pub fn to_event(&self, keys: &JsKeys) -> Result<JsEvent> {
+        let event = self
+            .builder
+            .clone()
+            .to_event(keys.deref())
+            .map_err(into_err)?;
+        Ok(event.into())
+    }
+
+    #[wasm_bindgen(js_name = setMetadata)]
+    pub fn set_metadata(metadata: &JsMetadata) -> Self {
+        Self {
+            builder: EventBuilder::set_metadata(metadata.into()),
+        }
+    }
+
+    #[wasm_bindgen(js_name = newTextNote)]
+    pub fn new_text_note(content: String, tags: Array) -> Result<JsEventBuilder> {
+        Ok(Self {
+            builder: EventBuilder::new_text_note(content, &parse_tags(tags)?),
+        })
+    }
+}
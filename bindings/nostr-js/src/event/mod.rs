@@ -0,0 +1,103 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::ops::Deref;
+
+use js_sys::Array;
+use nostr::prelude::*;
+use wasm_bindgen::prelude::*;
+
+mod builder;
+
+pub use self::builder::JsEventBuilder;
+use crate::error::{into_err, Result};
+use crate::key::JsPublicKey;
+
+#[wasm_bindgen(js_name = Event)]
+pub struct JsEvent {
+    inner: Event,
+}
+
+impl From<Event> for JsEvent {
+    fn from(event: Event) -> Self {
+        Self { inner: event }
+    }
+}
+
+impl Deref for JsEvent {
+    type Target = Event;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl From<&JsEvent> for Event {
+    fn from(event: &JsEvent) -> Self {
+        event.inner.clone()
+    }
+}
+
+#[wasm_bindgen(js_class = Event)]
+impl JsEvent {
+    /// Event id (hex)
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.inner.id.to_hex()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pubkey(&self) -> JsPublicKey {
+        self.inner.pubkey.into()
+    }
+
+    #[wasm_bindgen(getter, js_name = createdAt)]
+    pub fn created_at(&self) -> u64 {
+        self.inner.created_at.as_u64()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> u64 {
+        self.inner.kind.into()
+    }
+
+    /// Tags as array of string arrays
+    #[wasm_bindgen(getter)]
+    pub fn tags(&self) -> Array {
+        self.inner
+            .tags
+            .iter()
+            .map(|tag| {
+                tag.as_vec()
+                    .into_iter()
+                    .map(JsValue::from)
+                    .collect::<Array>()
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn content(&self) -> String {
+        self.inner.content.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signature(&self) -> String {
+        self.inner.sig.to_string()
+    }
+
+    pub fn verify(&self) -> bool {
+        self.inner.verify().is_ok()
+    }
+
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: String) -> Result<JsEvent> {
+        Ok(Self {
+            inner: Event::from_json(json).map_err(into_err)?,
+        })
+    }
+
+    #[wasm_bindgen(js_name = asJson)]
+    pub fn as_json(&self) -> String {
+        self.inner.as_json()
+    }
+}
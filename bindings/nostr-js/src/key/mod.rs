@@ -0,0 +1,86 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::ops::Deref;
+
+use nostr::prelude::*;
+use wasm_bindgen::prelude::*;
+
+mod public_key;
+mod secret_key;
+
+pub use self::public_key::JsPublicKey;
+pub use self::secret_key::JsSecretKey;
+use crate::error::{into_err, Result};
+
+#[wasm_bindgen(js_name = Keys)]
+pub struct JsKeys {
+    inner: Keys,
+}
+
+impl Deref for JsKeys {
+    type Target = Keys;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl From<Keys> for JsKeys {
+    fn from(keys: Keys) -> Self {
+        Self { inner: keys }
+    }
+}
+
+#[wasm_bindgen(js_class = Keys)]
+impl JsKeys {
+    /// Initialize from secret key.
+    #[wasm_bindgen(constructor)]
+    pub fn new(secret_key: &JsSecretKey) -> Self {
+        Self {
+            inner: Keys::new(secret_key.into()),
+        }
+    }
+
+    /// Initialize with public key only (no secret key).
+    #[wasm_bindgen(js_name = fromPublicKey)]
+    pub fn from_public_key(public_key: &JsPublicKey) -> Self {
+        Self {
+            inner: Keys::from_public_key(public_key.into()),
+        }
+    }
+
+    /// Init [`Keys`] from `hex` or `bech32` secret key string
+    #[wasm_bindgen(js_name = fromSkStr)]
+    pub fn from_sk_str(secret_key: String) -> Result<JsKeys> {
+        Ok(Self {
+            inner: Keys::from_sk_str(&secret_key).map_err(into_err)?,
+        })
+    }
+
+    /// Init [`Keys`] from `hex` or `bech32` public key string
+    #[wasm_bindgen(js_name = fromPkStr)]
+    pub fn from_pk_str(public_key: String) -> Result<JsKeys> {
+        Ok(Self {
+            inner: Keys::from_pk_str(&public_key).map_err(into_err)?,
+        })
+    }
+
+    /// Generate new random keys
+    pub fn generate() -> Self {
+        Self {
+            inner: Keys::generate(),
+        }
+    }
+
+    /// Get public key
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> JsPublicKey {
+        self.inner.public_key().into()
+    }
+
+    /// Get secret key
+    #[wasm_bindgen(js_name = secretKey)]
+    pub fn secret_key(&self) -> Result<JsSecretKey> {
+        Ok(self.inner.secret_key().map_err(into_err)?.into())
+    }
+}
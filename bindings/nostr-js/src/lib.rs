@@ -0,0 +1,12 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+mod error;
+mod event;
+mod key;
+pub mod nips;
+mod types;
+
+pub use self::event::{JsEvent, JsEventBuilder};
+pub use self::key::{JsKeys, JsPublicKey, JsSecretKey};
+pub use self::types::JsMetadata;
@@ -0,0 +1,25 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use js_sys::Promise;
+use nostr::nips::nip05;
+use nostr::secp256k1::XOnlyPublicKey;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::JsPublicKey;
+
+/// Verify NIP05
+///
+/// The `Promise` resolves to `true` if the `nip05` identifier maps to the public key.
+#[wasm_bindgen(js_name = verifyNip05)]
+pub fn verify_nip05(public_key: &JsPublicKey, nip05: String) -> Promise {
+    let public_key: XOnlyPublicKey = public_key.into();
+    future_to_promise(async move {
+        #[cfg(target_arch = "wasm32")]
+        let res = nip05::verify(public_key, nip05.as_str()).await;
+        #[cfg(not(target_arch = "wasm32"))]
+        let res = nip05::verify(public_key, nip05.as_str(), None).await;
+        Ok(JsValue::from_bool(res.is_ok()))
+    })
+}
@@ -0,0 +1,6 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+mod metadata;
+
+pub use self::metadata::JsMetadata;
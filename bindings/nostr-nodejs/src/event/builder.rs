@@ -40,6 +40,18 @@ impl JsEventBuilder {
         })
     }
 
+    /// Set a custom `created_at` timestamp (seconds)
+    #[napi]
+    pub fn custom_created_at(&self, created_at: BigInt) -> Self {
+        let created_at: u64 = created_at.get_u64().1;
+        Self {
+            builder: self
+                .builder
+                .clone()
+                .custom_created_at(Timestamp::from(created_at)),
+        }
+    }
+
     #[napi]
     pub fn to_event(&self, keys: &JsKeys) -> Result<JsEvent> {
         let event = self
@@ -106,6 +118,8 @@ impl JsEventBuilder {
                 sender_keys.deref(),
                 receiver_pubkey.into(),
                 content,
+                None,
+                None,
             )
             .map_err(into_err)?,
         })
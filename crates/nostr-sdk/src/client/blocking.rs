@@ -3,19 +3,24 @@
 
 #![allow(missing_docs)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::time::Duration;
 
 use nostr::key::XOnlyPublicKey;
+use nostr::nips::nip94::FileMetadata;
 use nostr::url::Url;
-use nostr::{ChannelId, ClientMessage, Contact, Event, EventId, Filter, Keys, Metadata, Tag};
+use nostr::{
+    ChannelId, ClientMessage, Contact, ContactList, Event, EventBuilder, EventId, Filter, Keys,
+    Metadata, SubscriptionId, Tag, Timestamp,
+};
 use tokio::sync::broadcast;
 
-use super::{Error, Options};
+use super::{Error, Options, SyncReport};
 use crate::client::Entity;
-use crate::relay::pool::RelayPoolNotification;
-use crate::relay::{Relay, RelayOptions};
+use crate::relay::pool::{DeliveryReport, RelayPoolNotification};
+use crate::relay::{Relay, RelayOptions, RelayStatus};
+use crate::store::MemoryStore;
 use crate::RUNTIME;
 
 #[derive(Debug, Clone)]
@@ -56,6 +61,10 @@ impl Client {
         RUNTIME.block_on(async { self.client.relays().await })
     }
 
+    pub fn relays_status(&self) -> HashMap<Url, RelayStatus> {
+        RUNTIME.block_on(async { self.client.relays_status().await })
+    }
+
     /// Add multiple relays
     pub fn add_relays<S>(&self, relays: Vec<(S, Option<SocketAddr>)>) -> Result<(), Error>
     where
@@ -120,6 +129,22 @@ impl Client {
         })
     }
 
+    pub fn subscribe_with_id(&self, id: SubscriptionId, filters: Vec<Filter>) {
+        RUNTIME.block_on(async {
+            self.client.subscribe_with_id(id, filters).await;
+        })
+    }
+
+    pub fn subscribe_and_store(&self, filters: Vec<Filter>, store: MemoryStore) -> SubscriptionId {
+        RUNTIME.block_on(async { self.client.subscribe_and_store(filters, store).await })
+    }
+
+    pub fn unsubscribe_from(&self, id: SubscriptionId) {
+        RUNTIME.block_on(async {
+            self.client.unsubscribe_from(id).await;
+        })
+    }
+
     pub fn unsubscribe(&self) {
         RUNTIME.block_on(async {
             self.client.unsubscribe().await;
@@ -134,6 +159,75 @@ impl Client {
         RUNTIME.block_on(async { self.client.get_events_of(filters, timeout).await })
     }
 
+    pub fn get_events_from_author(
+        &self,
+        pubkey: XOnlyPublicKey,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        RUNTIME.block_on(async {
+            self.client
+                .get_events_from_author(pubkey, filters, timeout)
+                .await
+        })
+    }
+
+    pub fn get_events_of_with_opts(
+        &self,
+        filters: Vec<Filter>,
+        relays: Option<Vec<Url>>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        RUNTIME.block_on(async {
+            self.client
+                .get_events_of_with_opts(filters, relays, timeout)
+                .await
+        })
+    }
+
+    pub fn get_events_of_with_sources(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(Event, HashSet<Url>)>, Error> {
+        RUNTIME.block_on(async {
+            self.client
+                .get_events_of_with_sources(filters, timeout)
+                .await
+        })
+    }
+
+    pub fn fetch_since(
+        &self,
+        filters: Vec<Filter>,
+        since: Timestamp,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        RUNTIME.block_on(async { self.client.fetch_since(filters, since, timeout).await })
+    }
+
+    pub fn check_replaceable_conflict(&self, event: &Event) -> Result<bool, Error> {
+        RUNTIME.block_on(async { self.client.check_replaceable_conflict(event).await })
+    }
+
+    pub fn sync(
+        &self,
+        filter: Filter,
+        store: &MemoryStore,
+        upload: bool,
+        timeout: Option<Duration>,
+    ) -> Result<SyncReport, Error> {
+        RUNTIME.block_on(async { self.client.sync(filter, store, upload, timeout).await })
+    }
+
+    pub fn get_event_fastest(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Event>, Error> {
+        RUNTIME.block_on(async { self.client.get_event_fastest(filters, timeout).await })
+    }
+
     pub fn req_events_of(&self, filters: Vec<Filter>, timeout: Option<Duration>) {
         RUNTIME.block_on(async {
             self.client.req_events_of(filters, timeout).await;
@@ -163,6 +257,17 @@ impl Client {
         RUNTIME.block_on(async { self.client.send_event_to(url, event).await })
     }
 
+    pub fn authenticate<S>(&self, url: S) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        RUNTIME.block_on(async { self.client.authenticate(url).await })
+    }
+
+    pub fn delivery_report(&self, event_id: EventId) -> Option<DeliveryReport> {
+        RUNTIME.block_on(async { self.client.delivery_report(event_id).await })
+    }
+
     pub fn set_metadata(&self, metadata: Metadata) -> Result<EventId, Error> {
         RUNTIME.block_on(async { self.client.set_metadata(metadata).await })
     }
@@ -190,6 +295,14 @@ impl Client {
         })
     }
 
+    pub fn send_event_with_pow(
+        &self,
+        builder: EventBuilder,
+        difficulty: u8,
+    ) -> Result<EventId, Error> {
+        RUNTIME.block_on(async { self.client.send_event_with_pow(builder, difficulty).await })
+    }
+
     pub fn add_recommended_relay<S>(&self, url: S) -> Result<EventId, Error>
     where
         S: Into<String>,
@@ -205,6 +318,13 @@ impl Client {
         RUNTIME.block_on(async { self.client.get_contact_list(timeout).await })
     }
 
+    pub fn get_contact_list_with_relays(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<ContactList, Error> {
+        RUNTIME.block_on(async { self.client.get_contact_list_with_relays(timeout).await })
+    }
+
     pub fn get_contact_list_public_keys(
         &self,
         timeout: Option<Duration>,
@@ -219,6 +339,25 @@ impl Client {
         RUNTIME.block_on(async { self.client.get_contact_list_metadata(timeout).await })
     }
 
+    pub fn get_metadata(
+        &self,
+        public_key: XOnlyPublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Metadata, Error> {
+        RUNTIME.block_on(async { self.client.get_metadata(public_key, timeout).await })
+    }
+
+    pub fn set_interests<S>(&self, hashtags: Vec<S>) -> Result<EventId, Error>
+    where
+        S: AsRef<str>,
+    {
+        RUNTIME.block_on(async { self.client.set_interests(hashtags).await })
+    }
+
+    pub fn get_interests(&self, timeout: Option<Duration>) -> Result<Vec<String>, Error> {
+        RUNTIME.block_on(async { self.client.get_interests(timeout).await })
+    }
+
     #[cfg(feature = "nip04")]
     pub fn send_direct_msg<S>(&self, receiver: XOnlyPublicKey, msg: S) -> Result<EventId, Error>
     where
@@ -227,6 +366,23 @@ impl Client {
         RUNTIME.block_on(async { self.client.send_direct_msg(receiver, msg).await })
     }
 
+    #[cfg(feature = "nip59")]
+    pub fn send_private_msg<S>(
+        &self,
+        receiver: XOnlyPublicKey,
+        content: S,
+        reply_to: Option<EventId>,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        RUNTIME.block_on(async {
+            self.client
+                .send_private_msg(receiver, content, reply_to)
+                .await
+        })
+    }
+
     pub fn repost_event(
         &self,
         event_id: EventId,
@@ -262,6 +418,13 @@ impl Client {
         RUNTIME.block_on(async { self.client.reaction(event_id, public_key, content).await })
     }
 
+    pub fn file_metadata<S>(&self, description: S, metadata: FileMetadata) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        RUNTIME.block_on(async { self.client.file_metadata(description, metadata).await })
+    }
+
     pub fn new_channel(&self, metadata: Metadata) -> Result<EventId, Error> {
         RUNTIME.block_on(async { self.client.new_channel(metadata).await })
     }
@@ -295,6 +458,23 @@ impl Client {
         })
     }
 
+    pub fn send_channel_msg_reply<S>(
+        &self,
+        channel_id: ChannelId,
+        relay_url: Url,
+        reply_to: &Event,
+        msg: S,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        RUNTIME.block_on(async {
+            self.client
+                .send_channel_msg_reply(channel_id, relay_url, reply_to, msg)
+                .await
+        })
+    }
+
     pub fn hide_channel_msg<S>(
         &self,
         message_id: EventId,
@@ -3,32 +3,57 @@
 
 //! Client
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "nip05")]
+use std::future::Future;
 use std::net::SocketAddr;
 #[cfg(feature = "sqlite")]
 use std::path::Path;
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use nostr::event::builder::Error as EventBuilderError;
+use nostr::event::newest_replaceable;
 use nostr::key::XOnlyPublicKey;
+#[cfg(feature = "nip59")]
+use nostr::nips::nip59;
+use nostr::nips::nip77::{self, Negentropy};
+use nostr::nips::nip94::FileMetadata;
+use nostr::nips::{nip51, nip65};
 use nostr::types::metadata::Error as MetadataError;
 use nostr::url::Url;
 use nostr::{
-    ChannelId, ClientMessage, Contact, Entity, Event, EventBuilder, EventId, Filter, Keys, Kind,
-    Metadata, Tag,
+    ChannelId, ClientMessage, Contact, ContactList, Entity, Event, EventBuilder, EventId, Filter,
+    Keys, Kind, Metadata, RelayMessage, SubscriptionId, Tag, Timestamp,
 };
 #[cfg(feature = "sqlite")]
 use nostr_sdk_sqlite::Store;
-use tokio::sync::broadcast;
+#[cfg(feature = "nip05")]
+use tokio::sync::Semaphore;
+use tokio::sync::{broadcast, Mutex};
+use tokio_util::sync::CancellationToken;
 
 #[cfg(feature = "blocking")]
 pub mod blocking;
 mod options;
 
 pub use self::options::Options;
-use crate::relay::pool::{Error as RelayPoolError, RelayPool, RelayPoolNotification};
-use crate::{Relay, RelayOptions};
+use crate::relay::pool::{
+    DeliveryReport, Error as RelayPoolError, FlushReport, RelayPool, RelayPoolNotification,
+};
+use crate::{MemoryStore, Relay, RelayOptions, RelayStatus};
+
+/// Default max time to wait for the contact list and metadata fetches
+const DEFAULT_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Max time to wait for a NIP-05 lookup of a received profile
+#[cfg(feature = "nip05")]
+const NIP05_VERIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Max number of NIP-05 lookups running at the same time
+#[cfg(feature = "nip05")]
+const NIP05_MAX_CONCURRENT_LOOKUPS: usize = 8;
 
 /// [`Client`] error
 #[derive(Debug, thiserror::Error)]
@@ -42,6 +67,9 @@ pub enum Error {
     /// Relay not found
     #[error("relay not found")]
     RelayNotFound,
+    /// Auth challenge not found
+    #[error("auth challenge not found")]
+    ChallengeNotFound,
     /// [`EventBuilder`] error
     #[error("event builder error: {0}")]
     EventBuilder(#[from] EventBuilderError),
@@ -54,6 +82,34 @@ pub enum Error {
     /// Metadata error
     #[error(transparent)]
     Metadata(#[from] MetadataError),
+    /// NIP59 error
+    #[cfg(feature = "nip59")]
+    #[error(transparent)]
+    NIP59(#[from] nip59::Error),
+    /// Event `created_at` too far in the future
+    #[error("event created_at too far in the future: {0}")]
+    EventInFuture(Timestamp),
+    /// NIP77 error
+    #[error(transparent)]
+    NIP77(#[from] nip77::Error),
+    /// Negentropy reconciliation rejected by the relay (`NEG-ERR`)
+    #[error("negentropy error: {0}")]
+    NegentropyError(String),
+    /// Timeout
+    #[error("timeout")]
+    Timeout,
+}
+
+/// NIP-65 relay lists (url and marker) by author, with their fetch time
+type RelayListCache = HashMap<XOnlyPublicKey, (Instant, Vec<(Url, Option<String>)>)>;
+
+/// Result of [`Client::sync`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Events fetched from the relays and saved into the store
+    pub received: Vec<EventId>,
+    /// Local events sent to the relays
+    pub sent: Vec<EventId>,
 }
 
 /// Nostr client
@@ -62,6 +118,8 @@ pub struct Client {
     pool: RelayPool,
     keys: Keys,
     opts: Options,
+    /// Cached NIP-65 relay lists (url and marker) of the authors
+    relay_lists: Arc<Mutex<RelayListCache>>,
 }
 
 impl Client {
@@ -89,11 +147,14 @@ impl Client {
     /// let client = Client::new_with_opts(&my_keys, opts);
     /// ```
     pub fn new_with_opts(keys: &Keys, opts: Options) -> Self {
-        Self {
+        let client = Self {
             pool: RelayPool::new(),
             keys: keys.clone(),
             opts,
-        }
+            relay_lists: Arc::new(Mutex::new(HashMap::new())),
+        };
+        client.init();
+        client
     }
 
     /// New [`Client`] with [`Store`]
@@ -111,11 +172,92 @@ impl Client {
     where
         P: AsRef<Path>,
     {
-        Ok(Self {
+        let client = Self {
             pool: RelayPool::new_with_store(path)?,
             keys: keys.clone(),
             opts,
-        })
+            relay_lists: Arc::new(Mutex::new(HashMap::new())),
+        };
+        client.init();
+        Ok(client)
+    }
+
+    /// Spawn the background tasks enabled in [`Options`]
+    fn init(&self) {
+        self.pool
+            .set_store_ephemeral(self.opts.get_store_ephemeral());
+        self.pool.set_event_filter(self.opts.get_event_filter());
+
+        #[cfg(feature = "nip05")]
+        if self.opts.get_auto_verify_nip05() {
+            self.spawn_nip05_verifier(|public_key, nip05| async move {
+                nostr::nips::nip05::verify(public_key, &nip05, None)
+                    .await
+                    .is_ok()
+            });
+        }
+    }
+
+    /// Verify the `nip05` of the received profiles with `lookup` and notify the result
+    ///
+    /// Each lookup runs in its own task (at most [`NIP05_MAX_CONCURRENT_LOOKUPS`] at once)
+    /// and an identifier already looked up for the same public key is skipped.
+    #[cfg(feature = "nip05")]
+    fn spawn_nip05_verifier<F, Fut>(&self, lookup: F)
+    where
+        F: Fn(XOnlyPublicKey, String) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+        let tasks = self.pool.tasks().clone();
+        let mut notifications = pool.notifications();
+        self.pool.tasks().spawn(async move {
+            log::debug!("NIP-05 verifier thread started");
+            let semaphore = Arc::new(Semaphore::new(NIP05_MAX_CONCURRENT_LOOKUPS));
+            let mut looked_up: HashMap<XOnlyPublicKey, String> = HashMap::new();
+            loop {
+                let notification = match notifications.recv().await {
+                    Ok(notification) => notification,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("NIP-05 verifier lagged: {skipped} notifications skipped");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                match notification {
+                    RelayPoolNotification::Event(_, event) if event.kind == Kind::Metadata => {
+                        let nip05: Option<String> = Metadata::from_json(&event.content)
+                            .ok()
+                            .and_then(|metadata| metadata.nip05);
+                        if let Some(nip05) = nip05 {
+                            if looked_up.get(&event.pubkey) == Some(&nip05) {
+                                continue;
+                            }
+                            looked_up.insert(event.pubkey, nip05.clone());
+
+                            let pubkey: XOnlyPublicKey = event.pubkey;
+                            let lookup = lookup(pubkey, nip05);
+                            let pool = pool.clone();
+                            let semaphore = semaphore.clone();
+                            tasks.spawn(async move {
+                                let _permit = semaphore.acquire_owned().await;
+                                let verified: bool =
+                                    tokio::time::timeout(NIP05_VERIFY_TIMEOUT, lookup)
+                                        .await
+                                        .unwrap_or(false);
+                                pool.notify(RelayPoolNotification::Nip05Verified {
+                                    pubkey,
+                                    verified,
+                                });
+                            });
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+            log::debug!("Exited from NIP-05 verifier thread");
+        });
     }
 
     /// Update default difficulty for new [`Event`]
@@ -139,6 +281,27 @@ impl Client {
         Ok(self.pool.shutdown().await?)
     }
 
+    /// Number of running background tasks
+    ///
+    /// Drops to zero after [`Client::shutdown`].
+    pub fn task_count(&self) -> usize {
+        self.pool.task_count()
+    }
+
+    /// Completly shutdown [`Client`], waiting (up to `timeout`) for the `OK` of the sent events
+    ///
+    /// If `force` is `true`, the pending events are not awaited.
+    pub async fn shutdown_with_flush(
+        self,
+        force: bool,
+        timeout: Duration,
+    ) -> Result<FlushReport, Error> {
+        let timeout: Duration = if force { Duration::ZERO } else { timeout };
+        let report: FlushReport = self.pool.flush(timeout).await;
+        self.pool.shutdown().await?;
+        Ok(report)
+    }
+
     /// Get new notification listener
     pub fn notifications(&self) -> broadcast::Receiver<RelayPoolNotification> {
         self.pool.notifications()
@@ -149,6 +312,13 @@ impl Client {
         self.pool.relays().await
     }
 
+    /// Get the current [`RelayStatus`] of every relay
+    ///
+    /// Cheaper than [`Client::relays`] when only the statuses are needed.
+    pub async fn relays_status(&self) -> HashMap<Url, RelayStatus> {
+        self.pool.relays_status().await
+    }
+
     /// Add new relay
     ///
     /// # Example
@@ -364,7 +534,38 @@ impl Client {
             .await;
     }
 
-    /// Unsubscribe
+    /// Subscribe to filters with custom [`SubscriptionId`]
+    ///
+    /// Multiple subscriptions with different ids can be active at the same time.
+    pub async fn subscribe_with_id(&self, id: SubscriptionId, filters: Vec<Filter>) {
+        self.pool
+            .subscribe_with_id(id, filters, self.opts.get_wait_for_send())
+            .await;
+    }
+
+    /// Subscribe to filters and store every verified event matching them into the [`MemoryStore`]
+    ///
+    /// Duplicated events are skipped and replaceable events supersede the older ones.
+    /// Ephemeral events are delivered but not stored, unless [`Options::store_ephemeral`] is enabled.
+    /// Use [`Client::unsubscribe_from`] with the returned [`SubscriptionId`] to stop it.
+    pub async fn subscribe_and_store(
+        &self,
+        filters: Vec<Filter>,
+        store: MemoryStore,
+    ) -> SubscriptionId {
+        self.pool
+            .subscribe_and_store(filters, store, self.opts.get_wait_for_send())
+            .await
+    }
+
+    /// Unsubscribe from subscription with custom [`SubscriptionId`], sending the `CLOSE` message
+    pub async fn unsubscribe_from(&self, id: SubscriptionId) {
+        self.pool
+            .unsubscribe_from(id, self.opts.get_wait_for_send())
+            .await;
+    }
+
+    /// Unsubscribe from all subscriptions
     pub async fn unsubscribe(&self) {
         self.pool.unsubscribe(self.opts.get_wait_for_send()).await;
     }
@@ -400,6 +601,383 @@ impl Client {
         Ok(self.pool.get_events_of(filters, timeout).await?)
     }
 
+    /// Get events of filters from a subset of relays
+    ///
+    /// If `relays` is `None`, the request is sent to all the relays of the pool.
+    /// Return an error if one of the `relays` isn't in the pool.
+    ///
+    /// Useful for the outbox model (i.e. query only the write relays of an author).
+    pub async fn get_events_of_with_opts(
+        &self,
+        filters: Vec<Filter>,
+        relays: Option<Vec<Url>>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        Ok(self
+            .pool
+            .get_events_of_with_opts(filters, relays, timeout)
+            .await?)
+    }
+
+    /// Get events of filters, with the relays that served each of them
+    ///
+    /// Events are deduplicated by id: the sources of the duplicates are merged.
+    pub async fn get_events_of_with_sources(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(Event, HashSet<Url>)>, Error> {
+        Ok(self
+            .pool
+            .get_events_of_with_sources(filters, timeout)
+            .await?)
+    }
+
+    /// Get events of filters, stopping early when `cancel` is canceled (ex. the user navigated away)
+    ///
+    /// On cancellation the `CLOSE` is sent to the relays and the events received so far are returned.
+    pub async fn get_events_of_cancelable(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+        cancel: CancellationToken,
+    ) -> Result<Vec<Event>, Error> {
+        Ok(self
+            .pool
+            .get_events_of_cancelable(filters, timeout, cancel)
+            .await?)
+    }
+
+    /// Get the events of filters created since `since` (included)
+    ///
+    /// A cheap incremental sync: keep the newest `created_at` received and use it as the next `since`.
+    /// If `timeout` is `None`, the default fetch timeout (10 secs) is used.
+    pub async fn fetch_since(
+        &self,
+        filters: Vec<Filter>,
+        since: Timestamp,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        let filters: Vec<Filter> = filters.into_iter().map(|f| f.since(since)).collect();
+        self.get_events_of(filters, timeout.or(Some(DEFAULT_FETCH_TIMEOUT)))
+            .await
+    }
+
+    /// Check if publishing the replaceable `event` would actually replace the version on relays
+    ///
+    /// Fetch the current version (waiting at most 10 secs) and return `false` if it's newer than `event`.
+    /// Return `true` if the event is not replaceable or if no version is found.
+    pub async fn check_replaceable_conflict(&self, event: &Event) -> Result<bool, Error> {
+        let filter = Filter::new().author(event.pubkey).kind(event.kind);
+        let filter = if event.kind.is_replaceable() {
+            filter
+        } else if event.kind.is_parameterized_replaceable() {
+            filter.identifier(event.identifier().unwrap_or_default())
+        } else {
+            return Ok(true);
+        };
+
+        let events: Vec<Event> = self
+            .get_events_of(vec![filter], Some(DEFAULT_FETCH_TIMEOUT))
+            .await?;
+        match newest_replaceable(events.into_iter().filter(|e| e.id != event.id)) {
+            Some(current) => Ok(event.cmp_by_date(&current).is_lt()),
+            None => Ok(true),
+        }
+    }
+
+    /// Get events of an author using the outbox model
+    ///
+    /// Fetch the author's relay list (kind `10002`), connect to their write relays
+    /// and query the filters only from them. The relay list is cached for [`Options::relay_list_ttl`].
+    /// The write relays that weren't in the pool are removed once the query is completed.
+    /// If the author has no relay list, the filters are sent to the read relays of the pool.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/65.md>
+    pub async fn get_events_from_author(
+        &self,
+        pubkey: XOnlyPublicKey,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        let write_relays: Vec<Url> = self.get_write_relays(pubkey, timeout).await?;
+
+        if write_relays.is_empty() {
+            let relays: Vec<Url> = self
+                .pool
+                .relays()
+                .await
+                .into_iter()
+                .filter(|(_, relay)| relay.opts().read())
+                .map(|(url, _)| url)
+                .collect();
+            return self
+                .get_events_of_with_opts(filters, Some(relays), timeout)
+                .await;
+        }
+
+        let added: Vec<Url> = self.add_missing_relays(&write_relays).await?;
+        let res = self
+            .get_events_of_with_opts(filters, Some(write_relays), timeout)
+            .await;
+        self.remove_relays(added).await;
+        res
+    }
+
+    /// Remove from the pool the relays added by [`Client::add_missing_relays`]
+    async fn remove_relays(&self, relays: Vec<Url>) {
+        for url in relays.into_iter() {
+            if let Err(e) = self.pool.remove_relay(url.clone()).await {
+                log::error!("Impossible to remove relay {url}: {e}");
+            }
+        }
+    }
+
+    /// Add to the pool, and connect, the relays that are not already in it
+    ///
+    /// Return the added relays.
+    async fn add_missing_relays(&self, relays: &[Url]) -> Result<Vec<Url>, Error> {
+        let pool_relays = self.pool.relays().await;
+        let mut added: Vec<Url> = Vec::new();
+        for url in relays.iter() {
+            if !pool_relays.contains_key(url) {
+                self.pool
+                    .add_relay(url.clone(), None, RelayOptions::default())
+                    .await?;
+                added.push(url.clone());
+                self.connect_relay(url.as_str()).await?;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Get the NIP-65 relay list of an author (cached)
+    ///
+    /// Only the found relay lists are cached: a miss (i.e. timeout) is fetched again at the next call.
+    async fn get_relay_list(
+        &self,
+        pubkey: XOnlyPublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(Url, Option<String>)>, Error> {
+        let ttl: Duration = self.opts.get_relay_list_ttl();
+        if let Some((fetched_at, relays)) = self.relay_lists.lock().await.get(&pubkey) {
+            if fetched_at.elapsed() < ttl {
+                return Ok(relays.clone());
+            }
+        }
+
+        let filter = Filter::new().author(pubkey).kind(Kind::RelayList).limit(1);
+        let events: Vec<Event> = self.get_events_of(vec![filter], timeout).await?;
+        let relays: Vec<(Url, Option<String>)> = match events
+            .into_iter()
+            .filter(|e| e.pubkey == pubkey && e.kind == Kind::RelayList)
+            .max_by_key(|e| e.created_at)
+        {
+            Some(event) => nip65::get_relay_list(event)
+                .into_iter()
+                .filter_map(|(url, marker)| Some((Url::parse(&url).ok()?, marker)))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if !relays.is_empty() {
+            self.relay_lists
+                .lock()
+                .await
+                .insert(pubkey, (Instant::now(), relays.clone()));
+        }
+        Ok(relays)
+    }
+
+    /// Get the NIP-65 write relays of an author (cached)
+    async fn get_write_relays(
+        &self,
+        pubkey: XOnlyPublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Url>, Error> {
+        Ok(self
+            .get_relay_list(pubkey, timeout)
+            .await?
+            .into_iter()
+            .filter(|(_, marker)| marker.as_deref() != Some("read"))
+            .map(|(url, _)| url)
+            .collect())
+    }
+
+    /// Get the NIP-65 read (inbox) relays of an author (cached)
+    #[cfg(feature = "nip59")]
+    async fn get_inbox_relays(
+        &self,
+        pubkey: XOnlyPublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Url>, Error> {
+        Ok(self
+            .get_relay_list(pubkey, timeout)
+            .await?
+            .into_iter()
+            .filter(|(_, marker)| marker.as_deref() != Some("write"))
+            .map(|(url, _)| url)
+            .collect())
+    }
+
+    /// Reconcile the [`MemoryStore`] with the relays
+    ///
+    /// Fetch the events matching the [`Filter`] that are missing in the store and, if `upload` is `true`,
+    /// send to each relay the stored events that it doesn't have.
+    ///
+    /// The differences are computed with NIP-77 (negentropy). If a relay doesn't support it
+    /// (`NEG-ERR`, timeout or invalid message), the events are reconciled with a normal `REQ`.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/77.md>
+    pub async fn sync(
+        &self,
+        filter: Filter,
+        store: &MemoryStore,
+        upload: bool,
+        timeout: Option<Duration>,
+    ) -> Result<SyncReport, Error> {
+        let timeout: Duration = timeout.unwrap_or(DEFAULT_FETCH_TIMEOUT);
+        let mut report = SyncReport::default();
+
+        for (url, relay) in self.relays().await.into_iter() {
+            let local: Vec<Event> = store.query(vec![filter.clone()]).await;
+            let items = local.iter().map(|e| (e.created_at, e.id));
+
+            let (remote, have_ids): (Vec<Event>, HashSet<EventId>) = match self
+                .negentropy_reconcile(&relay, filter.clone(), Negentropy::new(items), timeout)
+                .await
+            {
+                Ok((have_ids, need_ids)) => {
+                    let remote: Vec<Event> = if need_ids.is_empty() {
+                        Vec::new()
+                    } else {
+                        let ids = need_ids.iter().map(|id| id.to_hex());
+                        relay
+                            .get_events_of(vec![Filter::new().ids(ids)], Some(timeout))
+                            .await
+                            .map_err(RelayPoolError::from)?
+                    };
+                    (remote, have_ids.into_iter().collect())
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Negentropy reconciliation with {url} failed ({e}): fallback to REQ"
+                    );
+                    let remote: Vec<Event> = relay
+                        .get_events_of(vec![filter.clone()], Some(timeout))
+                        .await
+                        .map_err(RelayPoolError::from)?;
+                    let remote_ids: HashSet<EventId> = remote.iter().map(|e| e.id).collect();
+                    let have_ids: HashSet<EventId> = local
+                        .iter()
+                        .map(|e| e.id)
+                        .filter(|id| !remote_ids.contains(id))
+                        .collect();
+                    (remote, have_ids)
+                }
+            };
+
+            for event in remote.into_iter() {
+                let event_id: EventId = event.id;
+                if event.verify().is_ok()
+                    && self.pool.is_storable(&event)
+                    && store.insert(event).await
+                {
+                    report.received.push(event_id);
+                }
+            }
+
+            if upload {
+                for event in local.into_iter().filter(|e| have_ids.contains(&e.id)) {
+                    let event_id: EventId = self.send_event_to(url.to_string(), event).await?;
+                    if !report.sent.contains(&event_id) {
+                        report.sent.push(event_id);
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Run the NIP-77 reconciliation with a relay
+    ///
+    /// Return the ids that only the local set has and the ids that only the relay has.
+    async fn negentropy_reconcile(
+        &self,
+        relay: &Relay,
+        filter: Filter,
+        mut negentropy: Negentropy,
+        timeout: Duration,
+    ) -> Result<(Vec<EventId>, Vec<EventId>), Error> {
+        let url: Url = relay.url();
+        let id = SubscriptionId::generate();
+        let mut have_ids: Vec<EventId> = Vec::new();
+        let mut need_ids: Vec<EventId> = Vec::new();
+
+        // Subscribe to the notifications before sending NEG-OPEN to not miss the first reply
+        let mut notifications = self.notifications();
+        let open = ClientMessage::neg_open(id.clone(), filter, negentropy.initiate());
+        self.send_msg_to(url.to_string(), open).await?;
+
+        let res: Result<(), Error> = tokio::time::timeout(timeout, async {
+            while let Ok(notification) = notifications.recv().await {
+                let msg: RelayMessage = match notification {
+                    RelayPoolNotification::Message(relay_url, msg) if relay_url == url => msg,
+                    _ => continue,
+                };
+                match msg {
+                    RelayMessage::NegMsg {
+                        subscription_id,
+                        message,
+                    } if subscription_id == id => {
+                        match negentropy.reconcile_with_ids(
+                            &message,
+                            &mut have_ids,
+                            &mut need_ids,
+                        )? {
+                            Some(next) => {
+                                let msg = ClientMessage::neg_msg(id.clone(), next);
+                                self.send_msg_to(url.to_string(), msg).await?;
+                            }
+                            None => return Ok(()),
+                        }
+                    }
+                    RelayMessage::NegErr {
+                        subscription_id,
+                        code,
+                    } if subscription_id == id => return Err(Error::NegentropyError(code)),
+                    _ => (),
+                }
+            }
+            Err(Error::Timeout)
+        })
+        .await
+        .unwrap_or(Err(Error::Timeout));
+
+        // The relay already closed the session on NEG-ERR
+        if !matches!(res, Err(Error::NegentropyError(_))) {
+            let close = ClientMessage::neg_close(id);
+            if let Err(e) = self.send_msg_to(url.to_string(), close).await {
+                log::error!("Impossible to close negentropy session with {url}: {e}");
+            }
+        }
+
+        res?;
+        Ok((have_ids, need_ids))
+    }
+
+    /// Race the query across relays and return the first verified [`Event`] matching the filters
+    ///
+    /// The other requests are cancelled as soon as an event is received.
+    pub async fn get_event_fastest(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Event>, Error> {
+        Ok(self.pool.get_event_fastest(filters, timeout).await?)
+    }
+
     /// Request events of filters
     /// All events will be received on notification listener (`client.notifications()`)
     pub async fn req_events_of(&self, filters: Vec<Filter>, timeout: Option<Duration>) {
@@ -426,8 +1004,19 @@ impl Client {
             .await?)
     }
 
+    /// Return [`Error::EventInFuture`] if [`Options::reject_future_events`] is set and the event is too far in the future
+    fn check_created_at(&self, event: &Event) -> Result<(), Error> {
+        match self.opts.get_future_tolerance() {
+            Some(tolerance) if event.is_too_far_in_future(tolerance) => {
+                Err(Error::EventInFuture(event.created_at))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Send event
     pub async fn send_event(&self, event: Event) -> Result<EventId, Error> {
+        self.check_created_at(&event)?;
         let event_id = event.id;
         self.send_msg(ClientMessage::new_event(event)).await?;
         Ok(event_id)
@@ -438,12 +1027,47 @@ impl Client {
     where
         S: Into<String>,
     {
+        self.check_created_at(&event)?;
         let event_id = event.id;
         self.send_msg_to(url, ClientMessage::new_event(event))
             .await?;
         Ok(event_id)
     }
 
+    /// Authenticate to relay, replying to the last challenge received from it
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/42.md>
+    pub async fn authenticate<S>(&self, url: S) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let url = Url::parse(&url.into())?;
+        let relay: Relay = self
+            .relays()
+            .await
+            .get(&url)
+            .cloned()
+            .ok_or(Error::RelayNotFound)?;
+        let challenge: String = relay.challenge().await.ok_or(Error::ChallengeNotFound)?;
+        let event: Event = EventBuilder::auth(challenge, url.clone()).to_event(&self.keys)?;
+        let event_id = event.id;
+        self.pool
+            .send_msg_to(
+                url,
+                ClientMessage::auth(event),
+                self.opts.get_wait_for_send(),
+            )
+            .await?;
+        Ok(event_id)
+    }
+
+    /// Get the [`DeliveryReport`] of a sent [`Event`]
+    ///
+    /// Collect the `OK` responses of the relays as they arrive.
+    pub async fn delivery_report(&self, event_id: EventId) -> Option<DeliveryReport> {
+        self.pool.delivery_report(event_id).await
+    }
+
     async fn send_event_builder(&self, builder: EventBuilder) -> Result<EventId, Error> {
         let event: Event = {
             let difficulty: u8 = self.opts.get_difficulty();
@@ -536,8 +1160,34 @@ impl Client {
     where
         S: Into<String>,
     {
-        let event: Event =
-            EventBuilder::new_text_note(content, tags).to_pow_event(&self.keys, difficulty)?;
+        let builder = EventBuilder::new_text_note(content, tags);
+        self.send_event_with_pow(builder, difficulty).await
+    }
+
+    /// Mine and publish a POW [`Event`] of any [`Kind`]
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/13.md>
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr_sdk::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #   let my_keys = Keys::generate();
+    /// #   let client = Client::new(&my_keys);
+    /// # let event_id = EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45").unwrap();
+    /// # let public_key = my_keys.public_key();
+    /// let builder = EventBuilder::new_reaction(event_id, public_key, "+");
+    /// client.send_event_with_pow(builder, 16).await.unwrap();
+    /// # }
+    /// ```
+    pub async fn send_event_with_pow(
+        &self,
+        builder: EventBuilder,
+        difficulty: u8,
+    ) -> Result<EventId, Error> {
+        let event: Event = builder.to_pow_event(&self.keys, difficulty)?;
         self.send_event(event).await
     }
 
@@ -578,6 +1228,8 @@ impl Client {
 
     /// Get contact list
     ///
+    /// If `timeout` is `None`, wait at most 10 secs.
+    ///
     /// <https://github.com/nostr-protocol/nips/blob/master/02.md>
     ///
     /// # Example
@@ -595,28 +1247,32 @@ impl Client {
     /// # }
     /// ```
     pub async fn get_contact_list(&self, timeout: Option<Duration>) -> Result<Vec<Contact>, Error> {
-        let mut contact_list: Vec<Contact> = Vec::new();
+        Ok(self.get_contact_list_with_relays(timeout).await?.contacts)
+    }
+
+    /// Get [`ContactList`], with the relay map of the content
+    ///
+    /// If `timeout` is `None`, wait at most 10 secs.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/02.md>
+    pub async fn get_contact_list_with_relays(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<ContactList, Error> {
+        let mut contact_list = ContactList::default();
 
         let filter = Filter::new()
             .authors(vec![self.keys.public_key()])
             .kind(Kind::ContactList)
             .limit(1);
-        let events: Vec<Event> = self.get_events_of(vec![filter], timeout).await?;
+        let events: Vec<Event> = self
+            .get_events_of(vec![filter], timeout.or(Some(DEFAULT_FETCH_TIMEOUT)))
+            .await?;
 
-        for event in events.into_iter() {
-            for tag in event.tags.into_iter() {
-                match tag {
-                    Tag::PubKey(pk, relay_url) => {
-                        contact_list.push(Contact::new(pk, relay_url, None))
-                    }
-                    Tag::ContactList {
-                        pk,
-                        relay_url,
-                        alias,
-                    } => contact_list.push(Contact::new(pk, relay_url, alias)),
-                    _ => (),
-                }
-            }
+        for event in events.iter() {
+            let list = ContactList::from_event(event);
+            contact_list.contacts.extend(list.contacts);
+            contact_list.relays.extend(list.relays);
         }
 
         Ok(contact_list)
@@ -624,8 +1280,9 @@ impl Client {
 
     /// Get contact list public keys
     ///
+    /// If `timeout` is `None`, wait at most 10 secs.
+    ///
     /// <https://github.com/nostr-protocol/nips/blob/master/02.md>
-    /// ```
     pub async fn get_contact_list_public_keys(
         &self,
         timeout: Option<Duration>,
@@ -636,7 +1293,9 @@ impl Client {
             .authors(vec![self.keys.public_key()])
             .kind(Kind::ContactList)
             .limit(1);
-        let events: Vec<Event> = self.get_events_of(vec![filter], timeout).await?;
+        let events: Vec<Event> = self
+            .get_events_of(vec![filter], timeout.or(Some(DEFAULT_FETCH_TIMEOUT)))
+            .await?;
 
         for event in events.into_iter() {
             for tag in event.tags.into_iter() {
@@ -652,11 +1311,13 @@ impl Client {
     }
 
     /// Get contact list [`Metadata`]
-    /// ```
+    ///
+    /// If `timeout` is `None`, wait at most 10 secs for every request.
     pub async fn get_contact_list_metadata(
         &self,
         timeout: Option<Duration>,
     ) -> Result<HashMap<XOnlyPublicKey, Metadata>, Error> {
+        let timeout: Option<Duration> = timeout.or(Some(DEFAULT_FETCH_TIMEOUT));
         let public_keys = self.get_contact_list_public_keys(timeout).await?;
         let mut contacts: HashMap<XOnlyPublicKey, Metadata> =
             public_keys.iter().map(|p| (*p, Metadata::new())).collect();
@@ -684,6 +1345,59 @@ impl Client {
         Ok(contacts)
     }
 
+    /// Get the [`Metadata`] of `public_key` (the newest kind `0` event)
+    ///
+    /// Return an empty [`Metadata`] if not found.
+    /// If `timeout` is `None`, wait at most 10 secs.
+    pub async fn get_metadata(
+        &self,
+        public_key: XOnlyPublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Metadata, Error> {
+        let filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::Metadata)
+            .limit(1);
+        let events: Vec<Event> = self
+            .get_events_of(vec![filter], timeout.or(Some(DEFAULT_FETCH_TIMEOUT)))
+            .await?;
+        match newest_replaceable(events) {
+            Some(event) => Ok(Metadata::from_json(&event.content)?),
+            None => Ok(Metadata::new()),
+        }
+    }
+
+    /// Set interests list (kind `10015`)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn set_interests<S>(&self, hashtags: Vec<S>) -> Result<EventId, Error>
+    where
+        S: AsRef<str>,
+    {
+        let builder = EventBuilder::interests(hashtags);
+        self.send_event_builder(builder).await
+    }
+
+    /// Get interests list (normalized hashtags)
+    ///
+    /// If `timeout` is `None`, the default fetch timeout (10 secs) is used.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_interests(&self, timeout: Option<Duration>) -> Result<Vec<String>, Error> {
+        let filter = Filter::new()
+            .author(self.keys.public_key())
+            .kind(Kind::Interests)
+            .limit(1);
+        let events: Vec<Event> = self
+            .get_events_of(vec![filter], timeout.or(Some(DEFAULT_FETCH_TIMEOUT)))
+            .await?;
+        Ok(events
+            .into_iter()
+            .max_by_key(|e| e.created_at)
+            .map(|event| nip51::get_interests(&event))
+            .unwrap_or_default())
+    }
+
     /// Send encrypted direct message
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
@@ -716,10 +1430,99 @@ impl Client {
     where
         S: Into<String>,
     {
-        let builder = EventBuilder::new_encrypted_direct_msg(&self.keys, receiver, msg)?;
+        let builder =
+            EventBuilder::new_encrypted_direct_msg(&self.keys, receiver, msg, None, None)?;
         self.send_event_builder(builder).await
     }
 
+    /// Send private direct message
+    ///
+    /// The kind `14` rumor is gift-wrapped for the receiver and, to keep it available on the other devices,
+    /// for the sender. Each gift wrap is sent to the inbox (read) relays of its recipient, according to their
+    /// NIP-65 relay list, or to the relays of the pool if they have none.
+    ///
+    /// Return the [`EventId`] of the gift wrap sent to the receiver.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    #[cfg(feature = "nip59")]
+    pub async fn send_private_msg<S>(
+        &self,
+        receiver: XOnlyPublicKey,
+        content: S,
+        reply_to: Option<EventId>,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let self_pubkey: XOnlyPublicKey = self.keys.public_key();
+        let rumor = EventBuilder::private_msg_rumor(receiver, content, reply_to)
+            .to_unsigned_event(self_pubkey);
+
+        let pool_relays: Vec<Url> = self
+            .pool
+            .relays()
+            .await
+            .into_iter()
+            .filter(|(_, relay)| relay.opts().write())
+            .map(|(url, _)| url)
+            .collect();
+
+        let gift_wrap: Event = nip59::make_gift_wrap(&self.keys, &receiver, &rumor)?;
+        let event_id: EventId = gift_wrap.id;
+        let gift_wraps = [
+            (gift_wrap, self.get_inbox_relays(receiver, None).await?),
+            (
+                nip59::make_gift_wrap(&self.keys, &self_pubkey, &rumor)?,
+                self.get_inbox_relays(self_pubkey, None).await?,
+            ),
+        ];
+
+        for (gift_wrap, relays) in gift_wraps.into_iter() {
+            let (relays, added): (Vec<Url>, Vec<Url>) = if relays.is_empty() {
+                (pool_relays.clone(), Vec::new())
+            } else {
+                let added: Vec<Url> = self.add_missing_relays(&relays).await?;
+                (relays, added)
+            };
+
+            let mut sent: usize = 0;
+            let mut last_error: Option<RelayPoolError> = None;
+            for url in relays.into_iter() {
+                // Wait for the send on the added relays, since they are removed right after
+                let wait: bool = self.opts.get_wait_for_send() || added.contains(&url);
+                match self
+                    .pool
+                    .send_msg_to(
+                        url.clone(),
+                        ClientMessage::new_event(gift_wrap.clone()),
+                        wait,
+                    )
+                    .await
+                {
+                    Ok(_) => sent += 1,
+                    Err(e) => {
+                        log::error!(
+                            "Impossible to send gift wrap {} to {url}: {e}",
+                            gift_wrap.id
+                        );
+                        last_error = Some(e);
+                    }
+                }
+            }
+
+            self.remove_relays(added).await;
+
+            // The message is lost only if no relay got the receiver's gift wrap
+            if sent == 0 && gift_wrap.id == event_id {
+                return Err(last_error
+                    .unwrap_or(RelayPoolError::NoRelayConnected)
+                    .into());
+            }
+        }
+
+        Ok(event_id)
+    }
+
     /// Repost event
     pub async fn repost_event(
         &self,
@@ -851,6 +1654,21 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Announce a file with a file metadata event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/94.md>
+    pub async fn file_metadata<S>(
+        &self,
+        description: S,
+        metadata: FileMetadata,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let builder = EventBuilder::file_metadata(description, metadata);
+        self.send_event_builder(builder).await
+    }
+
     /// Create new channel
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -888,6 +1706,23 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Reply to a message of a channel
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub async fn send_channel_msg_reply<S>(
+        &self,
+        channel_id: ChannelId,
+        relay_url: Url,
+        reply_to: &Event,
+        msg: S,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let builder = EventBuilder::new_channel_msg_reply(channel_id, relay_url, reply_to, msg);
+        self.send_event_builder(builder).await
+    }
+
     /// Hide channel message
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -972,3 +1807,1014 @@ impl Client {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use futures_util::{SinkExt, StreamExt};
+    use nostr::RelayMessage;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::*;
+
+    /// Mock relay: reply to every `REQ` with `event` and `EOSE`, and forward the received events to `uploaded`
+    ///
+    /// NIP-77 isn't supported: `NEG-OPEN` is rejected with `NEG-ERR`.
+    async fn mock_relay(event: Event, uploaded: mpsc::Sender<Event>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let event = event.clone();
+                let uploaded = uploaded.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(msg)) = ws.next().await {
+                        let msg = match msg {
+                            Message::Text(text) => ClientMessage::from_json(text),
+                            _ => continue,
+                        };
+                        let replies: Vec<RelayMessage> = match msg {
+                            Ok(ClientMessage::Req {
+                                subscription_id, ..
+                            }) => vec![
+                                RelayMessage::new_event(subscription_id.clone(), event.clone()),
+                                RelayMessage::new_eose(subscription_id),
+                            ],
+                            Ok(ClientMessage::Event(event)) => {
+                                let _ = uploaded.send(*event.clone()).await;
+                                vec![RelayMessage::new_ok(event.id, true, "")]
+                            }
+                            Ok(ClientMessage::NegOpen {
+                                subscription_id, ..
+                            }) => vec![RelayMessage::neg_err(
+                                subscription_id,
+                                "blocked: negentropy disabled",
+                            )],
+                            _ => Vec::new(),
+                        };
+                        for reply in replies.into_iter() {
+                            if ws.send(Message::Text(reply.as_json())).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    /// Mock NIP-77 relay: reconcile `events` with negentropy, reply to `REQ` with the matching events
+    /// and forward the received events to `uploaded`
+    async fn mock_negentropy_relay(
+        events: Vec<Event>,
+        uploaded: mpsc::Sender<Event>,
+    ) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let events = events.clone();
+                let uploaded = uploaded.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    let mut negentropy =
+                        Negentropy::new(events.iter().map(|e| (e.created_at, e.id)));
+                    while let Some(Ok(msg)) = ws.next().await {
+                        let msg = match msg {
+                            Message::Text(text) => ClientMessage::from_json(text),
+                            _ => continue,
+                        };
+                        let replies: Vec<RelayMessage> = match msg {
+                            Ok(ClientMessage::NegOpen {
+                                subscription_id,
+                                initial_message: message,
+                                ..
+                            })
+                            | Ok(ClientMessage::NegMsg {
+                                subscription_id,
+                                message,
+                            }) => vec![RelayMessage::neg_msg(
+                                subscription_id,
+                                negentropy.reconcile(&message).unwrap(),
+                            )],
+                            Ok(ClientMessage::Req {
+                                subscription_id,
+                                filters,
+                            }) => {
+                                let mut replies: Vec<RelayMessage> = events
+                                    .iter()
+                                    .filter(|e| filters.iter().any(|f| f.matches_event(e)))
+                                    .map(|e| {
+                                        RelayMessage::new_event(subscription_id.clone(), e.clone())
+                                    })
+                                    .collect();
+                                replies.push(RelayMessage::new_eose(subscription_id));
+                                replies
+                            }
+                            Ok(ClientMessage::Event(event)) => {
+                                let _ = uploaded.send(*event.clone()).await;
+                                vec![RelayMessage::new_ok(event.id, true, "")]
+                            }
+                            _ => Vec::new(),
+                        };
+                        for reply in replies.into_iter() {
+                            if ws.send(Message::Text(reply.as_json())).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_sync() {
+        let keys = Keys::generate();
+        let remote = EventBuilder::new_text_note("Remote", &[])
+            .to_event(&Keys::generate())
+            .unwrap();
+        let local = EventBuilder::new_text_note("Local", &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let (uploaded_sender, mut uploaded) = mpsc::channel(16);
+        let addr = mock_relay(remote.clone(), uploaded_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let store = MemoryStore::new();
+        store.insert(local.clone()).await;
+
+        let report = client
+            .sync(
+                Filter::new().kind(Kind::TextNote),
+                &store,
+                true,
+                Some(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.received, vec![remote.id]);
+        assert_eq!(report.sent, vec![local.id]);
+        assert_eq!(store.get(&remote.id).await, Some(remote));
+        assert_eq!(
+            tokio::time::timeout(Duration::from_secs(5), uploaded.recv())
+                .await
+                .unwrap(),
+            Some(local)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_negentropy() {
+        let keys = Keys::generate();
+        let remote_keys = Keys::generate();
+        let notes = |keys: &Keys, prefix: &str| -> Vec<Event> {
+            (0..50)
+                .map(|i| {
+                    EventBuilder::new_text_note(format!("{prefix} {i}"), &[])
+                        .custom_created_at(Timestamp::from(1_680_000_000 + i))
+                        .to_event(keys)
+                        .unwrap()
+                })
+                .collect()
+        };
+        let shared: Vec<Event> = notes(&keys, "Shared");
+        let remote_only: Vec<Event> = notes(&remote_keys, "Remote");
+        let local_only: Vec<Event> = notes(&keys, "Local");
+
+        let (uploaded_sender, mut uploaded) = mpsc::channel(128);
+        let relay_events: Vec<Event> = shared.iter().chain(remote_only.iter()).cloned().collect();
+        let addr = mock_negentropy_relay(relay_events, uploaded_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let store = MemoryStore::new();
+        for event in shared.iter().chain(local_only.iter()) {
+            store.insert(event.clone()).await;
+        }
+
+        let report = client
+            .sync(
+                Filter::new().kind(Kind::TextNote),
+                &store,
+                true,
+                Some(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+
+        let ids = |events: &[Event]| -> HashSet<EventId> { events.iter().map(|e| e.id).collect() };
+        assert_eq!(
+            report
+                .received
+                .iter()
+                .copied()
+                .collect::<HashSet<EventId>>(),
+            ids(&remote_only)
+        );
+        assert_eq!(
+            report.sent.iter().copied().collect::<HashSet<EventId>>(),
+            ids(&local_only)
+        );
+        for event in remote_only.iter() {
+            assert!(store.get(&event.id).await.is_some());
+        }
+
+        let mut received: HashSet<EventId> = HashSet::new();
+        while received.len() < local_only.len() {
+            let event = tokio::time::timeout(Duration::from_secs(5), uploaded.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            received.insert(event.id);
+        }
+        assert_eq!(received, ids(&local_only));
+    }
+
+    #[tokio::test]
+    async fn test_store_ephemeral() {
+        let keys = Keys::generate();
+        let ephemeral = EventBuilder::new(Kind::Ephemeral(20001), "Ephemeral", &[])
+            .to_event(&keys)
+            .unwrap();
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr = mock_relay(ephemeral.clone(), uploaded_sender).await;
+
+        for store_ephemeral in [false, true] {
+            let opts = Options::new()
+                .wait_for_connection(true)
+                .store_ephemeral(store_ephemeral);
+            let client = Client::new_with_opts(&keys, opts);
+            client
+                .add_relay(format!("ws://{addr}"), None)
+                .await
+                .unwrap();
+            client.connect().await;
+
+            let mut notifications = client.notifications();
+            let store = MemoryStore::new();
+            client
+                .subscribe_and_store(vec![Filter::new()], store.clone())
+                .await;
+
+            // Delivered
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while let Ok(notification) = notifications.recv().await {
+                    if let RelayPoolNotification::Event(_, event) = notification {
+                        if event.id == ephemeral.id {
+                            break;
+                        }
+                    }
+                }
+            })
+            .await
+            .unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            // Stored only if enabled
+            assert_eq!(store.get(&ephemeral.id).await.is_some(), store_ephemeral);
+
+            client.shutdown().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_with_flush() {
+        let keys = Keys::generate();
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr = mock_relay(
+            EventBuilder::new_text_note("Remote", &[])
+                .to_event(&keys)
+                .unwrap(),
+            uploaded_sender,
+        )
+        .await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let event_id = client.publish_text_note("Bye", &[]).await.unwrap();
+        let report = client
+            .shutdown_with_flush(false, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(report.confirmed, vec![event_id]);
+        assert!(report.unconfirmed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reject_future_events() {
+        let keys = Keys::generate();
+        let client = Client::new_with_opts(
+            &keys,
+            Options::new().reject_future_events(Duration::from_secs(60)),
+        );
+
+        let event = EventBuilder::new_text_note("Too early", &[])
+            .custom_created_at(Timestamp::now() + Duration::from_secs(3600))
+            .to_event(&keys)
+            .unwrap();
+        assert!(matches!(
+            client.send_event(event).await,
+            Err(Error::EventInFuture(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_task_count_after_shutdown() {
+        let keys = Keys::generate();
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr = mock_relay(
+            EventBuilder::new_text_note("Remote", &[])
+                .to_event(&keys)
+                .unwrap(),
+            uploaded_sender,
+        )
+        .await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+        client.subscribe(vec![Filter::new().limit(1)]).await;
+        assert!(client.task_count() > 0);
+
+        client.clone().shutdown().await.unwrap();
+        assert_eq!(client.task_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_events_of_exclude_authors() {
+        let keys = Keys::generate();
+        let spammer = Keys::generate();
+        let note = EventBuilder::new_text_note("Hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        let spam = EventBuilder::new_text_note("Spam", &[])
+            .to_event(&spammer)
+            .unwrap();
+
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr_a = mock_relay(note.clone(), uploaded_sender.clone()).await;
+        let addr_b = mock_relay(spam, uploaded_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relays(vec![
+                (format!("ws://{addr_a}"), None),
+                (format!("ws://{addr_b}"), None),
+            ])
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let filter = Filter::new()
+            .kind(Kind::TextNote)
+            .exclude_authors(vec![spammer.public_key()]);
+        let events = client
+            .get_events_of(vec![filter], Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(events, vec![note]);
+    }
+
+    #[tokio::test]
+    async fn test_event_filter() {
+        let keys = Keys::generate();
+        let blocked = Keys::generate();
+        let note = EventBuilder::new_text_note("Hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        let spam = EventBuilder::new_text_note("Spam", &[])
+            .to_event(&blocked)
+            .unwrap();
+
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr_a = mock_relay(note.clone(), uploaded_sender.clone()).await;
+        let addr_b = mock_relay(spam, uploaded_sender).await;
+
+        let blocked_pubkey = blocked.public_key();
+        let opts = Options::new()
+            .wait_for_connection(true)
+            .event_filter(Arc::new(move |event: &Event| {
+                event.pubkey != blocked_pubkey
+            }));
+        let client = Client::new_with_opts(&keys, opts);
+        client
+            .add_relays(vec![
+                (format!("ws://{addr_a}"), None),
+                (format!("ws://{addr_b}"), None),
+            ])
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let mut notifications = client.notifications();
+        let events = client
+            .get_events_of(vec![Filter::new()], Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(events, vec![note.clone()]);
+
+        while let Ok(notification) = notifications.try_recv() {
+            match notification {
+                RelayPoolNotification::Event(_, event) => assert_eq!(event, note),
+                RelayPoolNotification::Message(_, RelayMessage::Event { event, .. }) => {
+                    assert_eq!(*event, note)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_timeout() {
+        // Silent relay: never reply
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                        while let Some(Ok(_)) = ws.next().await {}
+                    }
+                });
+            }
+        });
+
+        let keys = Keys::generate();
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let timeout = Some(Duration::from_millis(500));
+        let start = Instant::now();
+        assert!(client.get_contact_list(timeout).await.unwrap().is_empty());
+        assert_eq!(
+            client
+                .get_metadata(keys.public_key(), timeout)
+                .await
+                .unwrap(),
+            Metadata::new()
+        );
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    /// Mock relay: reply to every `REQ` with `event` and `EOSE` after `delay`, and flag `answered`
+    async fn delayed_relay(event: Event, delay: Duration, answered: Arc<AtomicBool>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let event = event.clone();
+                let answered = answered.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    while let Some(Ok(msg)) = ws.next().await {
+                        if let Message::Text(text) = msg {
+                            if let Ok(ClientMessage::Req {
+                                subscription_id, ..
+                            }) = ClientMessage::from_json(text)
+                            {
+                                tokio::time::sleep(delay).await;
+                                answered.store(true, Ordering::SeqCst);
+                                for reply in [
+                                    RelayMessage::new_event(subscription_id.clone(), event.clone()),
+                                    RelayMessage::new_eose(subscription_id),
+                                ] {
+                                    if ws.send(Message::Text(reply.as_json())).await.is_err() {
+                                        return;
+                                    }
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_get_event_fastest() {
+        let keys = Keys::generate();
+        let fast_event = EventBuilder::new_text_note("fast", &[])
+            .to_event(&keys)
+            .unwrap();
+        let slow_event = EventBuilder::new_text_note("slow", &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let fast_answered = Arc::new(AtomicBool::new(false));
+        let slow_answered = Arc::new(AtomicBool::new(false));
+        let fast = delayed_relay(
+            fast_event.clone(),
+            Duration::from_millis(50),
+            fast_answered.clone(),
+        )
+        .await;
+        let slow = delayed_relay(slow_event, Duration::from_secs(3), slow_answered.clone()).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{slow}"), None)
+            .await
+            .unwrap();
+        client
+            .add_relay(format!("ws://{fast}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let start = Instant::now();
+        let event = client
+            .get_event_fastest(
+                vec![Filter::new().author(keys.public_key())],
+                Some(Duration::from_secs(10)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(event, Some(fast_event));
+        assert!(fast_answered.load(Ordering::SeqCst));
+        assert!(!slow_answered.load(Ordering::SeqCst));
+        assert!(start.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn test_get_events_of_cancelable() {
+        // Never reply, forward the received messages
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (received_sender, mut received) = mpsc::channel(16);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let received_sender = received_sender.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                        while let Some(Ok(Message::Text(text))) = ws.next().await {
+                            if let Ok(msg) = ClientMessage::from_json(text) {
+                                let _ = received_sender.send(msg).await;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let keys = Keys::generate();
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let cancel = CancellationToken::new();
+        let canceler = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            canceler.cancel();
+        });
+
+        let start = Instant::now();
+        let events = client
+            .get_events_of_cancelable(vec![Filter::new()], Some(Duration::from_secs(30)), cancel)
+            .await
+            .unwrap();
+        assert!(events.is_empty());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let mut messages = Vec::new();
+        while messages.len() < 2 {
+            let msg = tokio::time::timeout(Duration::from_secs(5), received.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            messages.push(msg);
+        }
+        let id = match &messages[0] {
+            ClientMessage::Req {
+                subscription_id, ..
+            } => subscription_id.clone(),
+            msg => panic!("Unexpected message: {msg:?}"),
+        };
+        assert_eq!(messages[1], ClientMessage::close(id));
+    }
+
+    #[tokio::test]
+    async fn test_check_replaceable_conflict() {
+        let keys = Keys::generate();
+        let metadata = |created_at: u64| {
+            EventBuilder::set_metadata(Metadata::new().name("alice"))
+                .custom_created_at(Timestamp::from(created_at))
+                .to_event(&keys)
+                .unwrap()
+        };
+        let current = metadata(2_000);
+
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr = mock_relay(current.clone(), uploaded_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        // Older: would not replace the version on relays
+        assert!(!client
+            .check_replaceable_conflict(&metadata(1_000))
+            .await
+            .unwrap());
+        assert!(client
+            .check_replaceable_conflict(&metadata(3_000))
+            .await
+            .unwrap());
+
+        // Not replaceable
+        let note = EventBuilder::new_text_note("Hi", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert!(client.check_replaceable_conflict(&note).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_events_of_with_opts() {
+        let keys = Keys::generate();
+        let event_a = EventBuilder::new_text_note("A", &[])
+            .to_event(&keys)
+            .unwrap();
+        let event_b = EventBuilder::new_text_note("B", &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let addr_a = mock_relay(event_a.clone(), uploaded_sender.clone()).await;
+        let addr_b = mock_relay(event_b, uploaded_sender).await;
+        let url_a = Url::parse(&format!("ws://{addr_a}")).unwrap();
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client.add_relay(url_a.to_string(), None).await.unwrap();
+        client
+            .add_relay(format!("ws://{addr_b}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let filters = vec![Filter::new().kind(Kind::TextNote)];
+        let timeout = Some(Duration::from_secs(5));
+        let events = client
+            .get_events_of_with_opts(filters.clone(), Some(vec![url_a]), timeout)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event_a]);
+
+        let unknown = Url::parse("ws://127.0.0.1:1").unwrap();
+        assert!(client
+            .get_events_of_with_opts(filters, Some(vec![unknown]), timeout)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_events_of_with_sources() {
+        let keys = Keys::generate();
+        let shared = EventBuilder::new_text_note("Shared", &[])
+            .to_event(&keys)
+            .unwrap();
+        let unique = EventBuilder::new_text_note("Unique", &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let mut urls: Vec<Url> = Vec::new();
+        for event in [shared.clone(), shared.clone(), unique.clone()] {
+            let addr = mock_relay(event, uploaded_sender.clone()).await;
+            urls.push(Url::parse(&format!("ws://{addr}")).unwrap());
+        }
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        for url in urls.iter() {
+            client.add_relay(url.as_str(), None).await.unwrap();
+        }
+        client.connect().await;
+
+        let events = client
+            .get_events_of_with_sources(vec![Filter::new()], Some(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        for (event, sources) in events.into_iter() {
+            if event.id == shared.id {
+                assert_eq!(sources, HashSet::from([urls[0].clone(), urls[1].clone()]));
+            } else {
+                assert_eq!(event.id, unique.id);
+                assert_eq!(sources, HashSet::from([urls[2].clone()]));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_events_from_author() {
+        let keys = Keys::generate();
+        let author = Keys::generate();
+        let note = EventBuilder::new_text_note("Outbox", &[])
+            .to_event(&author)
+            .unwrap();
+
+        let (uploaded_sender, _uploaded) = mpsc::channel(16);
+        let write_addr = mock_relay(note.clone(), uploaded_sender.clone()).await;
+        let write_url = Url::parse(&format!("ws://{write_addr}")).unwrap();
+        let relay_list = EventBuilder::new(
+            Kind::RelayList,
+            "",
+            &[Tag::RelayMetadata(
+                write_url.to_string(),
+                Some(String::from("write")),
+            )],
+        )
+        .to_event(&author)
+        .unwrap();
+        let index_addr = mock_relay(relay_list, uploaded_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{index_addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let events = client
+            .get_events_from_author(
+                author.public_key(),
+                vec![Filter::new().author(author.public_key())],
+                Some(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(events, vec![note]);
+        // The author's write relay is used only for this query
+        assert!(!client.relays().await.contains_key(&write_url));
+        assert!(client
+            .relay_lists
+            .lock()
+            .await
+            .contains_key(&author.public_key()));
+
+        // Misses aren't cached
+        let unknown = Keys::generate().public_key();
+        let relays = client
+            .get_relay_list(unknown, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert!(relays.is_empty());
+        assert!(!client.relay_lists.lock().await.contains_key(&unknown));
+    }
+
+    #[test]
+    fn test_relay_list_ttl() {
+        let opts = Options::new().relay_list_ttl(Duration::from_millis(1500));
+        assert_eq!(opts.get_relay_list_ttl(), Duration::from_millis(1500));
+    }
+
+    #[cfg(feature = "nip59")]
+    #[tokio::test]
+    async fn test_send_private_msg() {
+        use nostr::nips::nip17;
+        use tokio::time;
+
+        let keys = Keys::generate();
+        let receiver = Keys::generate();
+
+        let note = EventBuilder::new_text_note("Inbox", &[])
+            .to_event(&receiver)
+            .unwrap();
+        let (inbox_sender, mut inbox) = mpsc::channel(16);
+        let inbox_addr = mock_relay(note, inbox_sender).await;
+        let inbox_url = Url::parse(&format!("ws://{inbox_addr}")).unwrap();
+        let relay_list = EventBuilder::new(
+            Kind::RelayList,
+            "",
+            &[Tag::RelayMetadata(
+                inbox_url.to_string(),
+                Some(String::from("read")),
+            )],
+        )
+        .to_event(&receiver)
+        .unwrap();
+        let (pool_sender, mut pool) = mpsc::channel(16);
+        let pool_addr = mock_relay(relay_list, pool_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{pool_addr}"), None)
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let event_id = client
+            .send_private_msg(receiver.public_key(), "Saturn, bringer of old age", None)
+            .await
+            .unwrap();
+
+        // Receiver copy is sent to the receiver inbox relay
+        let to_receiver = time::timeout(Duration::from_secs(5), inbox.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(to_receiver.id, event_id);
+        assert_eq!(to_receiver.kind, Kind::GiftWrap);
+        let msg = nip17::unwrap_private_msg(&receiver, &to_receiver).unwrap();
+        assert_eq!(msg.content, "Saturn, bringer of old age");
+
+        // Sender copy is sent to the pool, since the sender has no relay list
+        let to_self = time::timeout(Duration::from_secs(5), pool.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(to_self.kind, Kind::GiftWrap);
+        let msg = nip17::unwrap_private_msg(&keys, &to_self).unwrap();
+        assert_eq!(msg.sender, keys.public_key());
+        assert_eq!(msg.receivers, vec![receiver.public_key()]);
+
+        // The receiver inbox relay is removed from the pool after sending
+        let relays = client.relays().await;
+        assert_eq!(relays.len(), 1);
+        assert!(!relays.contains_key(&inbox_url));
+    }
+
+    #[cfg(feature = "nip59")]
+    #[tokio::test]
+    async fn test_send_private_msg_partial_delivery() {
+        let keys = Keys::generate();
+        let receiver = Keys::generate();
+
+        // Two inbox relays: the first one is read-only in the pool, so the send to it fails
+        let note = EventBuilder::new_text_note("Inbox", &[])
+            .to_event(&receiver)
+            .unwrap();
+        let (inbox_sender, mut inbox) = mpsc::channel(16);
+        let inbox_addr = mock_relay(note.clone(), inbox_sender).await;
+        let (read_only_sender, _read_only) = mpsc::channel(16);
+        let read_only_addr = mock_relay(note, read_only_sender).await;
+        let relay_list = EventBuilder::new(
+            Kind::RelayList,
+            "",
+            &[
+                Tag::RelayMetadata(format!("ws://{read_only_addr}"), Some(String::from("read"))),
+                Tag::RelayMetadata(format!("ws://{inbox_addr}"), Some(String::from("read"))),
+            ],
+        )
+        .to_event(&receiver)
+        .unwrap();
+        let (pool_sender, _pool) = mpsc::channel(16);
+        let pool_addr = mock_relay(relay_list, pool_sender).await;
+
+        let client = Client::new_with_opts(&keys, Options::new().wait_for_connection(true));
+        client
+            .add_relay(format!("ws://{pool_addr}"), None)
+            .await
+            .unwrap();
+        client
+            .add_relay_with_opts(
+                format!("ws://{read_only_addr}"),
+                None,
+                RelayOptions::new(true, false),
+            )
+            .await
+            .unwrap();
+        client.connect().await;
+
+        let event_id = client
+            .send_private_msg(receiver.public_key(), "Partial delivery", None)
+            .await
+            .unwrap();
+        let to_receiver = tokio::time::timeout(Duration::from_secs(5), inbox.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(to_receiver.id, event_id);
+        assert_eq!(client.relays().await.len(), 2);
+    }
+
+    #[cfg(feature = "nip05")]
+    #[tokio::test]
+    async fn test_auto_verify_nip05() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        /// Fixture server: serve a `/.well-known/nostr.json` mapping `_` to `public_key`
+        async fn nip05_server(public_key: XOnlyPublicKey) -> SocketAddr {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf).await;
+                    let body = format!(r#"{{"names":{{"_":"{public_key}"}}}}"#);
+                    let res = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(res.as_bytes()).await;
+                }
+            });
+            addr
+        }
+
+        /// Look up `nostr.json` on the fixture server instead of `https://<domain>`
+        async fn fixture_lookup(public_key: XOnlyPublicKey, nip05: String) -> bool {
+            let address = nostr::nips::nip05::Nip05Address::parse(&nip05).unwrap();
+            let mut stream = tokio::net::TcpStream::connect(address.domain())
+                .await
+                .unwrap();
+            let req = format!(
+                "GET /.well-known/nostr.json?name={} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+                address.local_part(),
+                address.domain()
+            );
+            stream.write_all(req.as_bytes()).await.unwrap();
+            let mut res = String::new();
+            stream.read_to_string(&mut res).await.unwrap();
+            let (_, body) = res.split_once("\r\n\r\n").unwrap();
+            let json: serde_json::Value = serde_json::from_str(body).unwrap();
+            json["names"][address.local_part()].as_str() == Some(&public_key.to_string())
+        }
+
+        let keys = Keys::generate();
+        let impostor = Keys::generate();
+        let addr = nip05_server(keys.public_key()).await;
+
+        let client = Client::new(&keys);
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let counter = lookups.clone();
+        client.spawn_nip05_verifier(move |public_key, nip05| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            fixture_lookup(public_key, nip05)
+        });
+        let mut notifications = client.notifications();
+
+        // Both profiles claim the same identifier: only the one listed in nostr.json is verified.
+        // The repeated profile is looked up only once.
+        let url = Url::parse("ws://127.0.0.1:7777").unwrap();
+        for keys in [&keys, &keys, &impostor] {
+            let event = EventBuilder::set_metadata(Metadata::new().nip05(format!("_@{addr}")))
+                .to_event(keys)
+                .unwrap();
+            client
+                .pool
+                .notify(RelayPoolNotification::Event(url.clone(), event));
+        }
+
+        let results = tokio::time::timeout(Duration::from_secs(15), async {
+            let mut results: HashMap<XOnlyPublicKey, bool> = HashMap::new();
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::Nip05Verified { pubkey, verified } = notification {
+                    results.insert(pubkey, verified);
+                    if results.len() == 2 {
+                        return results;
+                    }
+                }
+            }
+            panic!("notification channel closed");
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(results.get(&keys.public_key()), Some(&true));
+        assert_eq!(results.get(&impostor.public_key()), Some(&false));
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,9 +1,13 @@
 // Copyright (c) 2022-2023 Yuki Kishimoto
 // Distributed under the MIT software license
 
-use std::sync::atomic::AtomicU8;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::Event;
+
+use crate::relay::pool::EventFilter;
 
 /// Options
 #[derive(Debug, Clone)]
@@ -16,6 +20,17 @@ pub struct Options {
     difficulty: Arc<AtomicU8>,
     /// REQ filters chunk size
     req_filters_chunk_size: Arc<AtomicU8>,
+    /// Verify NIP-05 of received profiles
+    #[cfg(feature = "nip05")]
+    auto_verify_nip05: Arc<AtomicBool>,
+    /// NIP-65 relay list cache TTL (millis)
+    relay_list_ttl: Arc<AtomicU64>,
+    /// Max `created_at` drift in the future of the sent events
+    future_tolerance: Option<Duration>,
+    /// Save ephemeral events into the stores
+    store_ephemeral: Arc<AtomicBool>,
+    /// Custom filter of the received events
+    event_filter: Option<EventFilter>,
 }
 
 impl Default for Options {
@@ -25,6 +40,12 @@ impl Default for Options {
             wait_for_send: Arc::new(AtomicBool::new(true)),
             difficulty: Arc::new(AtomicU8::new(0)),
             req_filters_chunk_size: Arc::new(AtomicU8::new(10)),
+            #[cfg(feature = "nip05")]
+            auto_verify_nip05: Arc::new(AtomicBool::new(false)),
+            relay_list_ttl: Arc::new(AtomicU64::new(3_600_000)),
+            future_tolerance: None,
+            store_ephemeral: Arc::new(AtomicBool::new(false)),
+            event_filter: None,
         }
     }
 }
@@ -88,4 +109,69 @@ impl Options {
     pub(crate) fn get_req_filters_chunk_size(&self) -> usize {
         self.req_filters_chunk_size.load(Ordering::SeqCst) as usize
     }
+
+    /// If set to `true`, `Client` verify in background the `nip05` of the received profiles (kind 0)
+    /// and emit a `RelayPoolNotification::Nip05Verified` notification with the result.
+    #[cfg(feature = "nip05")]
+    pub fn auto_verify_nip05(self, enable: bool) -> Self {
+        Self {
+            auto_verify_nip05: Arc::new(AtomicBool::new(enable)),
+            ..self
+        }
+    }
+
+    #[cfg(feature = "nip05")]
+    pub(crate) fn get_auto_verify_nip05(&self) -> bool {
+        self.auto_verify_nip05.load(Ordering::SeqCst)
+    }
+
+    /// Set how long the NIP-65 relay lists fetched by `Client::get_events_from_author` are cached (default: 1 hour)
+    pub fn relay_list_ttl(self, ttl: Duration) -> Self {
+        Self {
+            relay_list_ttl: Arc::new(AtomicU64::new(ttl.as_millis() as u64)),
+            ..self
+        }
+    }
+
+    pub(crate) fn get_relay_list_ttl(&self) -> Duration {
+        Duration::from_millis(self.relay_list_ttl.load(Ordering::SeqCst))
+    }
+
+    /// Reject, before sending them, the events with a `created_at` more than `tolerance` in the future (default: disabled)
+    pub fn reject_future_events(self, tolerance: Duration) -> Self {
+        Self {
+            future_tolerance: Some(tolerance),
+            ..self
+        }
+    }
+
+    pub(crate) fn get_future_tolerance(&self) -> Option<Duration> {
+        self.future_tolerance
+    }
+
+    /// If set to `true`, the ephemeral events (kinds `20000-29999`) are saved into the stores (default: `false`)
+    pub fn store_ephemeral(self, enable: bool) -> Self {
+        Self {
+            store_ephemeral: Arc::new(AtomicBool::new(enable)),
+            ..self
+        }
+    }
+
+    pub(crate) fn get_store_ephemeral(&self) -> bool {
+        self.store_ephemeral.load(Ordering::SeqCst)
+    }
+
+    /// Set a custom filter for the received events, applied after the signature verification
+    ///
+    /// The events for which `filter` returns `false` are dropped before any notification.
+    pub fn event_filter(self, filter: Arc<dyn Fn(&Event) -> bool + Send + Sync>) -> Self {
+        Self {
+            event_filter: Some(EventFilter::new(filter)),
+            ..self
+        }
+    }
+
+    pub(crate) fn get_event_filter(&self) -> Option<EventFilter> {
+        self.event_filter.clone()
+    }
 }
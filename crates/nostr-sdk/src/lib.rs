@@ -17,17 +17,20 @@ use once_cell::sync::Lazy;
 use tokio::runtime::Runtime;
 
 pub use nostr::{self, *};
+pub use tokio_util::sync::CancellationToken;
 
 pub mod client;
 pub mod prelude;
 pub mod relay;
+pub mod store;
 mod thread;
 
 #[cfg(feature = "blocking")]
 pub use self::client::blocking;
-pub use self::client::{Client, Options};
+pub use self::client::{Client, Options, SyncReport};
 pub use self::relay::pool::{RelayPool, RelayPoolNotification};
-pub use self::relay::{Relay, RelayOptions, RelayStatus};
+pub use self::relay::{QueueOverflow, RateLimiter, Relay, RelayOptions, RelayStatus};
+pub use self::store::MemoryStore;
 
 #[cfg(feature = "blocking")]
 static RUNTIME: Lazy<Runtime> = Lazy::new(|| Runtime::new().expect("Can't start Tokio runtime"));
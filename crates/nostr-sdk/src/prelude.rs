@@ -10,4 +10,5 @@ pub use nostr::prelude::*;
 pub use crate::client::*;
 pub use crate::relay::pool::*;
 pub use crate::relay::*;
+pub use crate::store::*;
 pub use crate::*;
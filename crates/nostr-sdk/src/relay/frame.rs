@@ -0,0 +1,50 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Websocket frames
+//!
+//! Parse the inbound frames of a websocket connection, for relays and bridges built with this crate.
+
+use futures_util::future;
+use futures_util::{Stream, StreamExt};
+use nostr::message::MessageHandleError;
+use nostr::{ClientMessage, RelayMessage};
+
+use super::net::Message as WsMessage;
+
+fn text_frames<S, E>(stream: S) -> impl Stream<Item = String>
+where
+    S: Stream<Item = Result<WsMessage, E>>,
+{
+    stream.filter_map(|frame| {
+        future::ready(match frame {
+            Ok(WsMessage::Text(text)) => Some(text),
+            Ok(WsMessage::Binary(data)) => String::from_utf8(data).ok(),
+            _ => None,
+        })
+    })
+}
+
+/// Parse the inbound frames of a connection as [`ClientMessage`] (relay side)
+///
+/// Control frames, non UTF-8 frames and transport errors are skipped.
+pub fn client_messages<S, E>(
+    stream: S,
+) -> impl Stream<Item = Result<ClientMessage, MessageHandleError>>
+where
+    S: Stream<Item = Result<WsMessage, E>>,
+{
+    text_frames(stream).map(ClientMessage::from_json)
+}
+
+/// Parse the inbound frames of a connection as [`RelayMessage`] (client side)
+///
+/// Control frames, non UTF-8 frames and transport errors are skipped.
+pub fn relay_messages<S, E>(
+    stream: S,
+) -> impl Stream<Item = Result<RelayMessage, MessageHandleError>>
+where
+    S: Stream<Item = Result<WsMessage, E>>,
+{
+    text_frames(stream).map(RelayMessage::from_json)
+}
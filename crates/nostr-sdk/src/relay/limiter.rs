@@ -0,0 +1,93 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Rate limiter
+
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token bucket rate limiter
+///
+/// Allow bursts of up to `per_second` messages and then `per_second` messages per second.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    per_second: NonZeroU32,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// New [`RateLimiter`] with a full bucket
+    pub fn new(per_second: NonZeroU32) -> Self {
+        Self {
+            per_second,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: per_second.get() as f64,
+                last_refill: Instant::now(),
+            })),
+        }
+    }
+
+    /// Get the max number of messages per second
+    pub fn per_second(&self) -> NonZeroU32 {
+        self.per_second
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed: f64 = now.duration_since(bucket.last_refill).as_secs_f64();
+        let capacity: f64 = self.per_second.get() as f64;
+        bucket.tokens = (bucket.tokens + elapsed * capacity).min(capacity);
+        bucket.last_refill = now;
+    }
+
+    /// Get the number of messages that can be sent right now without waiting
+    pub fn remaining(&self) -> u32 {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        self.refill(&mut bucket);
+        bucket.tokens as u32
+    }
+
+    /// Wait until a message can be sent and consume a token
+    pub async fn acquire(&self) {
+        loop {
+            let wait: Duration = {
+                let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+                self.refill(&mut bucket);
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second.get() as f64)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(NonZeroU32::new(20).unwrap());
+        assert_eq!(limiter.remaining(), 20);
+
+        let start = Instant::now();
+        for _ in 0..40 {
+            limiter.acquire().await;
+        }
+        // The first 20 are a burst, the other 20 must take at least 1 sec
+        assert!(start.elapsed() >= Duration::from_millis(950));
+        assert_eq!(limiter.remaining(), 0);
+    }
+}
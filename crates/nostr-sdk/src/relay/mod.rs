@@ -3,27 +3,33 @@
 
 //! Relay
 
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use futures_util::{Future, SinkExt, StreamExt};
 #[cfg(feature = "nip11")]
 use nostr::nips::nip11::RelayInformationDocument;
-use nostr::{ClientMessage, Event, Filter, RelayMessage, SubscriptionId, Url};
+use nostr::{ClientMessage, Event, Filter, RelayMessage, SubscriptionId, Timestamp, Url};
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::oneshot;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+use tokio_util::sync::CancellationToken;
 
+pub mod frame;
+pub mod limiter;
 mod net;
 pub mod pool;
 
+pub use self::limiter::RateLimiter;
 use self::net::Message as WsMessage;
 use self::pool::RelayPoolMessage;
-use crate::thread;
+use crate::thread::TaskTracker;
 use crate::RelayPoolNotification;
 #[cfg(feature = "blocking")]
 use crate::RUNTIME;
@@ -91,6 +97,8 @@ impl fmt::Display for RelayStatus {
 pub enum RelayEvent {
     /// Send [`ClientMessage`]
     SendMsg(Box<ClientMessage>),
+    /// Send raw text frame
+    SendRaw(String),
     // Ping,
     /// Close
     Close,
@@ -98,6 +106,15 @@ pub enum RelayEvent {
     Terminate,
 }
 
+/// Message to drop when the offline queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueOverflow {
+    /// Drop the oldest queued message
+    DropOldest,
+    /// Drop the new message
+    DropNewest,
+}
+
 /// [`Relay`] options
 #[derive(Debug, Clone)]
 pub struct RelayOptions {
@@ -105,6 +122,12 @@ pub struct RelayOptions {
     read: Arc<AtomicBool>,
     /// Allow/disallow write actions
     write: Arc<AtomicBool>,
+    /// Automatically re-send the subscriptions after (re)connection
+    auto_resubscribe: Arc<AtomicBool>,
+    /// Outbound messages rate limiter
+    rate_limiter: Option<RateLimiter>,
+    /// Offline queue size and overflow policy
+    offline_queue: Option<(usize, QueueOverflow)>,
 }
 
 impl Default for RelayOptions {
@@ -119,9 +142,44 @@ impl RelayOptions {
         Self {
             read: Arc::new(AtomicBool::new(read)),
             write: Arc::new(AtomicBool::new(write)),
+            auto_resubscribe: Arc::new(AtomicBool::new(true)),
+            rate_limiter: None,
+            offline_queue: None,
+        }
+    }
+
+    /// Limit the outbound messages to `per_second` messages per second (default: unlimited)
+    ///
+    /// When the limit is reached, the messages are delayed (not dropped).
+    pub fn rate_limit(self, per_second: NonZeroU32) -> Self {
+        Self {
+            rate_limiter: Some(RateLimiter::new(per_second)),
+            ..self
+        }
+    }
+
+    /// Get the outbound messages [`RateLimiter`], if any
+    pub fn rate_limiter(&self) -> Option<RateLimiter> {
+        self.rate_limiter.clone()
+    }
+
+    /// Buffer up to `size` outbound messages while the relay is disconnected or connecting (default: disabled)
+    ///
+    /// The buffered messages are sent as soon as the relay is connected.
+    /// When the queue is full, a message is dropped according to `overflow`
+    /// and a `RelayPoolNotification::MessageDropped` notification is emitted.
+    pub fn offline_queue(self, size: usize, overflow: QueueOverflow) -> Self {
+        Self {
+            offline_queue: Some((size, overflow)),
+            ..self
         }
     }
 
+    /// Get the offline queue size and overflow policy, if enabled
+    pub fn get_offline_queue(&self) -> Option<(usize, QueueOverflow)> {
+        self.offline_queue
+    }
+
     /// Get read option
     pub fn read(&self) -> bool {
         self.read.load(Ordering::SeqCst)
@@ -145,6 +203,22 @@ impl RelayOptions {
             .write
             .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| Some(write));
     }
+
+    /// Get auto resubscribe option
+    ///
+    /// If enabled (default), the active subscriptions are re-sent after every (re)connection
+    pub fn auto_resubscribe(&self) -> bool {
+        self.auto_resubscribe.load(Ordering::SeqCst)
+    }
+
+    /// Set auto resubscribe option
+    pub fn set_auto_resubscribe(&self, auto_resubscribe: bool) {
+        let _ = self
+            .auto_resubscribe
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| {
+                Some(auto_resubscribe)
+            });
+    }
 }
 
 /// Relay instance's actual subscription with its unique id
@@ -187,6 +261,19 @@ pub struct Relay {
     relay_receiver: Arc<Mutex<Receiver<Message>>>,
     notification_sender: broadcast::Sender<RelayPoolNotification>,
     subscription: Arc<Mutex<ActiveSubscription>>,
+    subscriptions: Arc<Mutex<HashMap<SubscriptionId, Vec<Filter>>>>,
+    challenge: Arc<Mutex<Option<String>>>,
+    /// Newest `created_at` received for every subscription
+    last_seen: Arc<Mutex<HashMap<SubscriptionId, Timestamp>>>,
+    /// Messages buffered while the relay is offline
+    offline_queue: Arc<Mutex<VecDeque<ClientMessage>>>,
+    /// Connection attempts since the last successful connection
+    attempts: Arc<AtomicUsize>,
+    /// `true` after the first successful connection
+    connected_once: Arc<AtomicBool>,
+    /// Wake up the auto connect loop when the relay is terminated
+    termination: Arc<Notify>,
+    tasks: TaskTracker,
 }
 
 impl PartialEq for Relay {
@@ -219,9 +306,22 @@ impl Relay {
             relay_receiver: Arc::new(Mutex::new(relay_receiver)),
             notification_sender,
             subscription: Arc::new(Mutex::new(ActiveSubscription::new())),
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            challenge: Arc::new(Mutex::new(None)),
+            last_seen: Arc::new(Mutex::new(HashMap::new())),
+            offline_queue: Arc::new(Mutex::new(VecDeque::new())),
+            attempts: Arc::new(AtomicUsize::new(0)),
+            connected_once: Arc::new(AtomicBool::new(false)),
+            termination: Arc::new(Notify::new()),
+            tasks: TaskTracker::default(),
         }
     }
 
+    /// Track the tasks spawned by the relay with `tasks`
+    pub(crate) fn task_tracker(self, tasks: TaskTracker) -> Self {
+        Self { tasks, ..self }
+    }
+
     /// Get relay url
     pub fn url(&self) -> Url {
         self.url.clone()
@@ -246,7 +346,18 @@ impl Relay {
 
     async fn set_status(&self, status: RelayStatus) {
         let mut s = self.status.lock().await;
-        *s = status;
+        if *s != status {
+            *s = status.clone();
+            if let Err(e) = self
+                .notification_sender
+                .send(RelayPoolNotification::RelayStatus {
+                    url: self.url(),
+                    status,
+                })
+            {
+                log::debug!("RelayPoolNotifications::RelayStatus error: {:?}", e);
+            }
+        }
     }
 
     /// Get [`RelayInformationDocument`]
@@ -280,6 +391,75 @@ impl Relay {
         s.filters = filters;
     }
 
+    /// Get subscriptions opened with [`Relay::subscribe_with_id`]
+    pub async fn subscriptions(&self) -> HashMap<SubscriptionId, Vec<Filter>> {
+        let subscriptions = self.subscriptions.lock().await;
+        subscriptions.clone()
+    }
+
+    /// Update filters of a subscription with custom [`SubscriptionId`], without sending the `REQ`
+    pub async fn update_subscription_filters_with_id(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+    ) {
+        let mut subscriptions = self.subscriptions.lock().await;
+        subscriptions.insert(id, filters);
+    }
+
+    /// Get the newest `created_at` of the events received for the subscription
+    pub async fn last_seen(&self, id: &SubscriptionId) -> Option<Timestamp> {
+        let last_seen = self.last_seen.lock().await;
+        last_seen.get(id).copied()
+    }
+
+    /// Track the newest `created_at` of the active subscriptions
+    async fn update_last_seen(&self, id: &SubscriptionId, created_at: Timestamp) {
+        let tracked: bool = self.subscriptions.lock().await.contains_key(id)
+            || self.subscription.lock().await.id == *id;
+        if tracked {
+            let mut last_seen = self.last_seen.lock().await;
+            let timestamp = last_seen.entry(id.clone()).or_insert(created_at);
+            if created_at > *timestamp {
+                *timestamp = created_at;
+            }
+        }
+    }
+
+    async fn remove_last_seen(&self, id: &SubscriptionId) {
+        let mut last_seen = self.last_seen.lock().await;
+        last_seen.remove(id);
+    }
+
+    /// Advance the `since` of the filters to the newest received event, to not refetch everything
+    ///
+    /// `since` is inclusive, so the events with the same `created_at` are received again.
+    async fn resume_filters(&self, id: &SubscriptionId, filters: Vec<Filter>) -> Vec<Filter> {
+        match self.last_seen(id).await {
+            Some(last_seen) => filters
+                .into_iter()
+                .map(|filter| match filter.since {
+                    Some(since) if since >= last_seen => filter,
+                    _ => filter.since(last_seen),
+                })
+                .collect(),
+            None => filters,
+        }
+    }
+
+    /// Get last `AUTH` challenge received from relay
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/42.md>
+    pub async fn challenge(&self) -> Option<String> {
+        let challenge = self.challenge.lock().await;
+        challenge.clone()
+    }
+
+    async fn set_challenge(&self, challenge: String) {
+        let mut c = self.challenge.lock().await;
+        *c = Some(challenge);
+    }
+
     /// Get [`RelayOptions`]
     pub fn opts(&self) -> RelayOptions {
         self.opts.clone()
@@ -306,7 +486,7 @@ impl Relay {
             }
 
             let relay = self.clone();
-            thread::spawn(async move {
+            self.tasks.spawn(async move {
                 loop {
                     log::debug!(
                         "{} channel capacity: {}",
@@ -333,7 +513,10 @@ impl Relay {
                         _ => (),
                     };
 
-                    tokio::time::sleep(Duration::from_secs(20)).await;
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs(20)) => (),
+                        _ = relay.termination.notified() => (),
+                    }
                 }
             });
         }
@@ -344,13 +527,14 @@ impl Relay {
 
         // Set RelayStatus to `Connecting`
         self.set_status(RelayStatus::Connecting).await;
+        self.attempts.fetch_add(1, Ordering::SeqCst);
         log::debug!("Connecting to {}", url);
 
         // Request `RelayInformationDocument`
         #[cfg(feature = "nip11")]
         {
             let relay = self.clone();
-            thread::spawn(async move {
+            self.tasks.spawn(async move {
                 match RelayInformationDocument::get(relay.url(), relay.proxy()).await {
                     Ok(document) => relay.set_document(document).await,
                     Err(e) => log::error!(
@@ -365,36 +549,33 @@ impl Relay {
         // Connect
         match net::get_connection(&self.url, self.proxy, None).await {
             Ok((mut ws_tx, mut ws_rx)) => {
+                // Hold the offline queue until it's flushed: the messages sent after the status
+                // change wait for the lock (see `Relay::enqueue_offline`) and can't overtake the buffered ones
+                let mut offline_queue = self.offline_queue.lock().await;
                 self.set_status(RelayStatus::Connected).await;
                 log::info!("Connected to {}", url);
 
+                let attempts: usize = self.attempts.swap(0, Ordering::SeqCst);
+                if self.connected_once.swap(true, Ordering::SeqCst) {
+                    if let Err(e) =
+                        self.notification_sender
+                            .send(RelayPoolNotification::Reconnected {
+                                url: self.url(),
+                                attempts,
+                            })
+                    {
+                        log::debug!("RelayPoolNotifications::Reconnected error: {:?}", e);
+                    }
+                }
+
                 let relay = self.clone();
-                thread::spawn(async move {
+                self.tasks.spawn(async move {
                     log::debug!("Relay Event Thread Started");
                     let mut rx = relay.relay_receiver.lock().await;
                     while let Some((relay_event, oneshot_sender)) = rx.recv().await {
-                        match relay_event {
-                            RelayEvent::SendMsg(msg) => {
-                                log::debug!("Sending message {}", msg.as_json());
-                                if let Err(e) = ws_tx.send(WsMessage::Text(msg.as_json())).await {
-                                    log::error!(
-                                        "Impossible to send msg to {}: {}",
-                                        relay.url(),
-                                        e.to_string()
-                                    );
-                                    if let Some(sender) = oneshot_sender {
-                                        if let Err(e) = sender.send(false) {
-                                            log::error!("Impossible to send oneshot msg: {}", e);
-                                        }
-                                    }
-                                    break;
-                                };
-                                if let Some(sender) = oneshot_sender {
-                                    if let Err(e) = sender.send(true) {
-                                        log::error!("Impossible to send oneshot msg: {}", e);
-                                    }
-                                }
-                            }
+                        let frame: String = match relay_event {
+                            RelayEvent::SendMsg(msg) => msg.as_json(),
+                            RelayEvent::SendRaw(frame) => frame,
                             RelayEvent::Close => {
                                 let _ = ws_tx.close().await;
                                 relay.set_status(RelayStatus::Disconnected).await;
@@ -417,12 +598,35 @@ impl Relay {
                                 log::info!("Completely disconnected from {}", url);
                                 break;
                             }
+                        };
+
+                        if let Some(limiter) = relay.opts.rate_limiter.as_ref() {
+                            limiter.acquire().await;
+                        }
+                        log::debug!("Sending message {}", frame);
+                        if let Err(e) = ws_tx.send(WsMessage::Text(frame)).await {
+                            log::error!(
+                                "Impossible to send msg to {}: {}",
+                                relay.url(),
+                                e.to_string()
+                            );
+                            if let Some(sender) = oneshot_sender {
+                                if let Err(e) = sender.send(false) {
+                                    log::error!("Impossible to send oneshot msg: {}", e);
+                                }
+                            }
+                            break;
+                        };
+                        if let Some(sender) = oneshot_sender {
+                            if let Err(e) = sender.send(true) {
+                                log::error!("Impossible to send oneshot msg: {}", e);
+                            }
                         }
                     }
                 });
 
                 let relay = self.clone();
-                thread::spawn(async move {
+                self.tasks.spawn(async move {
                     log::debug!("Relay Message Thread Started");
                     while let Some(msg_res) = ws_rx.next().await {
                         if let Ok(msg) = msg_res {
@@ -432,6 +636,18 @@ impl Relay {
                                 Ok(data) => match RelayMessage::from_json(&data) {
                                     Ok(msg) => {
                                         log::trace!("Received message to {}: {:?}", relay.url, msg);
+                                        if let RelayMessage::Auth { challenge } = &msg {
+                                            relay.set_challenge(challenge.clone()).await;
+                                        }
+                                        if let RelayMessage::Event {
+                                            subscription_id,
+                                            event,
+                                        } = &msg
+                                        {
+                                            relay
+                                                .update_last_seen(subscription_id, event.created_at)
+                                                .await;
+                                        }
                                         if let Err(err) = relay
                                             .pool_sender
                                             .send(RelayPoolMessage::ReceivedMsg {
@@ -464,18 +680,13 @@ impl Relay {
                     }
                 });
 
+                // Send the messages buffered while offline
+                self.flush_offline_queue(&mut offline_queue).await;
+                drop(offline_queue);
+
                 // Subscribe to relay
-                if self.opts.read() {
-                    if let Err(e) = self.resubscribe(false).await {
-                        match e {
-                            Error::FiltersEmpty => log::debug!("Filters empty for {}", self.url()),
-                            _ => log::error!(
-                                "Impossible to subscribe to {}: {}",
-                                self.url(),
-                                e.to_string()
-                            ),
-                        }
-                    }
+                if self.opts.read() && self.opts.auto_resubscribe() {
+                    self.restore_subscriptions().await;
                 }
             }
             Err(err) => {
@@ -508,6 +719,7 @@ impl Relay {
     /// Disconnect from relay and set status to 'Terminated'
     pub async fn terminate(&self) -> Result<(), Error> {
         self.schedule_for_termination(true).await;
+        self.termination.notify_one();
         let status = self.status().await;
         if status.ne(&RelayStatus::Disconnected) && status.ne(&RelayStatus::Terminated) {
             self.send_relay_event(RelayEvent::Terminate, None).await?;
@@ -515,9 +727,65 @@ impl Relay {
         Ok(())
     }
 
+    /// Buffer `msg` if the offline queue is enabled and the relay is disconnected or connecting
+    ///
+    /// Return the message back if not buffered.
+    async fn enqueue_offline(&self, msg: ClientMessage) -> Option<ClientMessage> {
+        let (size, overflow) = match self.opts.get_offline_queue() {
+            Some(offline_queue) => offline_queue,
+            None => return Some(msg),
+        };
+
+        // Keep the queue locked while checking the status, to not miss the flush on connection
+        let mut queue = self.offline_queue.lock().await;
+        if let RelayStatus::Disconnected | RelayStatus::Connecting = self.status().await {
+            let dropped: Option<ClientMessage> = if queue.len() < size {
+                queue.push_back(msg);
+                None
+            } else {
+                match overflow {
+                    QueueOverflow::DropOldest => {
+                        queue.push_back(msg);
+                        queue.pop_front()
+                    }
+                    QueueOverflow::DropNewest => Some(msg),
+                }
+            };
+
+            if let Some(msg) = dropped {
+                log::warn!("Offline queue of {} full: message dropped", self.url);
+                if let Err(e) =
+                    self.notification_sender
+                        .send(RelayPoolNotification::MessageDropped {
+                            url: self.url(),
+                            msg,
+                        })
+                {
+                    log::debug!("RelayPoolNotifications::MessageDropped error: {:?}", e);
+                }
+            }
+
+            None
+        } else {
+            Some(msg)
+        }
+    }
+
+    async fn flush_offline_queue(&self, queue: &mut VecDeque<ClientMessage>) {
+        while let Some(msg) = queue.pop_front() {
+            if let Err(e) = self
+                .send_relay_event(RelayEvent::SendMsg(Box::new(msg)), None)
+                .await
+            {
+                log::error!("Impossible to send buffered msg to {}: {}", self.url, e);
+            }
+        }
+    }
+
     /// Send msg to relay
     ///
-    /// if `wait` arg is true, this method will wait for the msg to be sent
+    /// if `wait` arg is true, this method will wait for the msg to be sent.
+    /// If the message is buffered in the offline queue (see [`RelayOptions::offline_queue`]), return immediately.
     pub async fn send_msg(&self, msg: ClientMessage, wait: bool) -> Result<(), Error> {
         if !self.opts.write() {
             if let ClientMessage::Event(_) = msg {
@@ -531,10 +799,34 @@ impl Relay {
             }
         }
 
+        let msg: ClientMessage = match self.enqueue_offline(msg).await {
+            Some(msg) => msg,
+            None => return Ok(()),
+        };
+
+        self.send_and_wait(RelayEvent::SendMsg(Box::new(msg)), wait)
+            .await
+    }
+
+    /// Send a raw text frame to relay
+    ///
+    /// The frame is sent as is: the read/write options and the offline queue are not applied.
+    /// If `wait` arg is true, this method will wait for the frame to be sent.
+    pub async fn send_raw(&self, frame: String, wait: bool) -> Result<(), Error> {
+        self.send_and_wait(RelayEvent::SendRaw(frame), wait).await
+    }
+
+    /// Send a [`RelayMessage`] frame through the connection (relay mode)
+    ///
+    /// Useful for bridges that forward the messages of a relay to another peer. See [`Relay::send_raw`].
+    pub async fn send_relay_msg(&self, msg: RelayMessage, wait: bool) -> Result<(), Error> {
+        self.send_raw(msg.as_json(), wait).await
+    }
+
+    async fn send_and_wait(&self, relay_event: RelayEvent, wait: bool) -> Result<(), Error> {
         if wait {
             let (tx, rx) = oneshot::channel::<bool>();
-            self.send_relay_event(RelayEvent::SendMsg(Box::new(msg)), Some(tx))
-                .await?;
+            self.send_relay_event(relay_event, Some(tx)).await?;
             match tokio::time::timeout(Duration::from_secs(60), rx).await {
                 Ok(result) => match result {
                     Ok(val) => {
@@ -549,30 +841,75 @@ impl Relay {
                 Err(_) => Err(Error::RecvTimeout),
             }
         } else {
-            self.send_relay_event(RelayEvent::SendMsg(Box::new(msg)), None)
-                .await
+            self.send_relay_event(relay_event, None).await
         }
     }
 
-    /// Subscribes relay with existing filter
+    /// Subscribes relay with existing filter (default subscription only)
     async fn resubscribe(&self, wait: bool) -> Result<SubscriptionId, Error> {
         if !self.opts.read() {
             return Err(Error::ReadDisabled);
         }
+
         let subscription = self.subscription().await;
 
         if subscription.filters.is_empty() {
             return Err(Error::FiltersEmpty);
         }
 
+        let filters: Vec<Filter> = self
+            .resume_filters(&subscription.id, subscription.filters)
+            .await;
         self.send_msg(
-            ClientMessage::new_req(subscription.id.clone(), subscription.filters),
+            ClientMessage::new_req(subscription.id.clone(), filters),
             wait,
         )
         .await?;
         Ok(subscription.id)
     }
 
+    /// Re-send the active subscriptions and notify the re-established ones
+    ///
+    /// A failed subscription is logged and doesn't prevent restoring the others.
+    async fn restore_subscriptions(&self) {
+        let mut ids: Vec<SubscriptionId> = Vec::new();
+
+        for (id, filters) in self.subscriptions().await.into_iter() {
+            let filters: Vec<Filter> = self.resume_filters(&id, filters).await;
+            match self
+                .send_msg(ClientMessage::new_req(id.clone(), filters), false)
+                .await
+            {
+                Ok(()) => ids.push(id),
+                Err(e) => log::error!(
+                    "Impossible to restore subscription {} to {}: {}",
+                    id.to_string(),
+                    self.url(),
+                    e.to_string()
+                ),
+            }
+        }
+
+        match self.resubscribe(false).await {
+            Ok(id) => ids.push(id),
+            Err(Error::FiltersEmpty) => log::debug!("Filters empty for {}", self.url()),
+            Err(e) => log::error!(
+                "Impossible to subscribe to {}: {}",
+                self.url(),
+                e.to_string()
+            ),
+        }
+
+        for id in ids.into_iter() {
+            if let Err(e) = self
+                .notification_sender
+                .send(RelayPoolNotification::Resubscribed(self.url(), id))
+            {
+                log::error!("RelayPoolNotifications::Resubscribed error: {:?}", e);
+            }
+        }
+    }
+
     /// Subscribe
     pub async fn subscribe(
         &self,
@@ -584,15 +921,78 @@ impl Relay {
         }
 
         self.update_subscription_filters(filters).await;
+        self.remove_last_seen(&self.subscription().await.id).await;
         self.resubscribe(wait).await
     }
 
-    /// Unsubscribe
+    /// Subscribe with custom [`SubscriptionId`]
+    ///
+    /// Multiple subscriptions with different ids can be active at the same time.
+    pub async fn subscribe_with_id(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+        wait: bool,
+    ) -> Result<(), Error> {
+        if !self.opts.read() {
+            return Err(Error::ReadDisabled);
+        }
+
+        if filters.is_empty() {
+            return Err(Error::FiltersEmpty);
+        }
+
+        self.update_subscription_filters_with_id(id.clone(), filters.clone())
+            .await;
+        self.remove_last_seen(&id).await;
+        self.send_msg(ClientMessage::new_req(id, filters), wait)
+            .await
+    }
+
+    /// Unsubscribe from subscription with custom [`SubscriptionId`]
+    pub async fn unsubscribe_from(&self, id: SubscriptionId, wait: bool) -> Result<(), Error> {
+        if !self.opts.read() {
+            return Err(Error::ReadDisabled);
+        }
+
+        let removed: bool = {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.remove(&id).is_some()
+        };
+        self.remove_last_seen(&id).await;
+        if removed {
+            self.send_msg(ClientMessage::close(id), wait).await?;
+        }
+        Ok(())
+    }
+
+    /// Send `CLOSE` for the [`SubscriptionId`] to this relay only
+    ///
+    /// Unlike [`Relay::unsubscribe_from`], the `CLOSE` is sent even if the subscription isn't tracked by this relay.
+    pub async fn close_subscription(&self, id: SubscriptionId) -> Result<(), Error> {
+        {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.remove(&id);
+        }
+        self.remove_last_seen(&id).await;
+        self.send_msg(ClientMessage::close(id), false).await
+    }
+
+    /// Unsubscribe from all subscriptions
     pub async fn unsubscribe(&self, wait: bool) -> Result<(), Error> {
         if !self.opts.read() {
             return Err(Error::ReadDisabled);
         }
 
+        let ids: Vec<SubscriptionId> = {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.drain().map(|(id, _)| id).collect()
+        };
+        self.last_seen.lock().await.clear();
+        for id in ids.into_iter() {
+            self.send_msg(ClientMessage::close(id), wait).await?;
+        }
+
         let subscription = self.subscription().await;
         self.send_msg(ClientMessage::close(subscription.id), wait)
             .await?;
@@ -606,6 +1006,23 @@ impl Relay {
         timeout: Option<Duration>,
         callback: impl Fn(Event) -> F,
     ) -> Result<(), Error>
+    where
+        F: Future<Output = ()>,
+    {
+        self.get_events_of_with_cancel(filters, timeout, None, callback)
+            .await
+    }
+
+    /// Get events of filters with custom callback, until EOSE, timeout or cancellation
+    ///
+    /// The `CLOSE` is sent also if canceled.
+    pub(crate) async fn get_events_of_with_cancel<F>(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+        cancel: Option<CancellationToken>,
+        callback: impl Fn(Event) -> F,
+    ) -> Result<(), Error>
     where
         F: Future<Output = ()>,
     {
@@ -642,6 +1059,17 @@ impl Relay {
             }
         };
 
+        // Stop early if canceled
+        let recv = async {
+            match cancel {
+                Some(cancel) => tokio::select! {
+                    _ = recv => (),
+                    _ = cancel.cancelled() => log::debug!("Get events of {} canceled", self.url),
+                },
+                None => recv.await,
+            }
+        };
+
         if let Some(timeout) = timeout {
             if tokio::time::timeout(timeout, recv).await.is_err() {
                 return Err(Error::Timeout);
@@ -678,7 +1106,7 @@ impl Relay {
         }
 
         let relay = self.clone();
-        thread::spawn(async move {
+        self.tasks.spawn(async move {
             let id = SubscriptionId::generate();
 
             // Subscribe
@@ -727,3 +1155,565 @@ impl Relay {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent_messages(relay: &Relay) -> Vec<ClientMessage> {
+        let mut rx = relay.relay_receiver.try_lock().unwrap();
+        let mut messages = Vec::new();
+        while let Ok((event, _)) = rx.try_recv() {
+            if let RelayEvent::SendMsg(msg) = event {
+                messages.push(*msg);
+            }
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_from() {
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, _) = broadcast::channel(1024);
+        let relay = Relay::new(
+            Url::parse("wss://relay.example.com").unwrap(),
+            pool_sender,
+            notification_sender,
+            None,
+            RelayOptions::default(),
+        );
+
+        let first = SubscriptionId::new("first");
+        let second = SubscriptionId::new("second");
+        relay
+            .subscribe_with_id(first.clone(), vec![Filter::new().limit(1)], false)
+            .await
+            .unwrap();
+        relay
+            .subscribe_with_id(second.clone(), vec![Filter::new().limit(2)], false)
+            .await
+            .unwrap();
+        relay.unsubscribe_from(first.clone(), false).await.unwrap();
+
+        let messages = sent_messages(&relay);
+        assert!(messages.contains(&ClientMessage::close(first)));
+        assert!(!messages.contains(&ClientMessage::close(second.clone())));
+        assert_eq!(
+            relay.subscriptions().await.into_keys().collect::<Vec<_>>(),
+            vec![second]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_close_subscription() {
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, _) = broadcast::channel(1024);
+        let relays: Vec<Relay> = ["wss://relay1.example.com", "wss://relay2.example.com"]
+            .into_iter()
+            .map(|url| {
+                Relay::new(
+                    Url::parse(url).unwrap(),
+                    pool_sender.clone(),
+                    notification_sender.clone(),
+                    None,
+                    RelayOptions::default(),
+                )
+            })
+            .collect();
+
+        let id = SubscriptionId::new("spam");
+        for relay in relays.iter() {
+            relay
+                .subscribe_with_id(id.clone(), vec![Filter::new().limit(1)], false)
+                .await
+                .unwrap();
+        }
+        relays[0].close_subscription(id.clone()).await.unwrap();
+
+        assert!(sent_messages(&relays[0]).contains(&ClientMessage::close(id.clone())));
+        assert!(!sent_messages(&relays[1]).contains(&ClientMessage::close(id.clone())));
+        assert!(relays[0].subscriptions().await.is_empty());
+        assert!(relays[1].subscriptions().await.contains_key(&id));
+    }
+
+    #[tokio::test]
+    async fn test_restore_subscriptions() {
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, mut notifications) = broadcast::channel(1024);
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        let relay = Relay::new(
+            url.clone(),
+            pool_sender,
+            notification_sender,
+            None,
+            RelayOptions::default(),
+        );
+
+        let id = SubscriptionId::new("named");
+        let filters = vec![Filter::new().limit(10)];
+        relay
+            .subscribe_with_id(id.clone(), filters.clone(), false)
+            .await
+            .unwrap();
+        sent_messages(&relay);
+
+        // Plain subscribe must not re-send the named subscriptions
+        let default_filters = vec![Filter::new().limit(1)];
+        let default_id = relay
+            .subscribe(default_filters.clone(), false)
+            .await
+            .unwrap();
+        assert_eq!(
+            sent_messages(&relay),
+            vec![ClientMessage::new_req(default_id.clone(), default_filters)]
+        );
+
+        // Simulate a reconnection
+        relay.restore_subscriptions().await;
+
+        let messages = sent_messages(&relay);
+        assert!(messages.contains(&ClientMessage::new_req(id.clone(), filters)));
+        assert_eq!(messages.len(), 2);
+
+        let mut resubscribed = Vec::new();
+        while let Ok(notification) = notifications.try_recv() {
+            if let RelayPoolNotification::Resubscribed(relay_url, id) = notification {
+                assert_eq!(relay_url, url);
+                resubscribed.push(id);
+            }
+        }
+        assert_eq!(resubscribed, vec![id, default_id]);
+    }
+
+    #[tokio::test]
+    async fn test_resubscribe_since_last_seen() {
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, _) = broadcast::channel(1024);
+        let relay = Relay::new(
+            Url::parse("wss://relay.example.com").unwrap(),
+            pool_sender,
+            notification_sender,
+            None,
+            RelayOptions::default(),
+        );
+
+        let id = SubscriptionId::new("notes");
+        let filter = Filter::new().since(Timestamp::from(100));
+        relay
+            .subscribe_with_id(id.clone(), vec![filter.clone()], false)
+            .await
+            .unwrap();
+        sent_messages(&relay);
+
+        // Received events
+        relay.update_last_seen(&id, Timestamp::from(500)).await;
+        relay.update_last_seen(&id, Timestamp::from(300)).await;
+        let untracked = SubscriptionId::new("untracked");
+        relay
+            .update_last_seen(&untracked, Timestamp::from(600))
+            .await;
+        assert_eq!(relay.last_seen(&id).await, Some(Timestamp::from(500)));
+        assert_eq!(relay.last_seen(&untracked).await, None);
+
+        // Simulate a reconnection: resume from the newest received event
+        relay.restore_subscriptions().await;
+        assert_eq!(
+            sent_messages(&relay),
+            vec![ClientMessage::new_req(
+                id.clone(),
+                vec![filter.since(Timestamp::from(500))]
+            )]
+        );
+
+        relay.unsubscribe_from(id.clone(), false).await.unwrap();
+        assert_eq!(relay.last_seen(&id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_status_notifications() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                        while let Some(Ok(_)) = ws.next().await {}
+                    }
+                });
+            }
+        });
+
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, mut notifications) = broadcast::channel(1024);
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        let relay = Relay::new(
+            url.clone(),
+            pool_sender,
+            notification_sender,
+            None,
+            RelayOptions::default(),
+        );
+        assert_eq!(relay.status().await, RelayStatus::Initialized);
+
+        relay.connect(true).await;
+        assert_eq!(relay.status().await, RelayStatus::Connected);
+        relay.terminate().await.unwrap();
+
+        let statuses = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut statuses = Vec::new();
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::RelayStatus {
+                    url: relay_url,
+                    status,
+                } = notification
+                {
+                    assert_eq!(relay_url, url);
+                    statuses.push(status.clone());
+                    if status == RelayStatus::Terminated {
+                        break;
+                    }
+                }
+            }
+            statuses
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            statuses,
+            vec![
+                RelayStatus::Connecting,
+                RelayStatus::Connected,
+                RelayStatus::Terminated
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconnected_notification() {
+        use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+        use tokio_tungstenite::tungstenite::http::StatusCode;
+
+        // Reject the second websocket handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handshakes = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handshakes = handshakes.clone();
+                tokio::spawn(async move {
+                    let callback = |_: &Request, response: Response| {
+                        if handshakes.fetch_add(1, Ordering::SeqCst) == 1 {
+                            let mut error = ErrorResponse::new(None);
+                            *error.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                            return Err(error);
+                        }
+                        Ok(response)
+                    };
+                    if let Ok(mut ws) = tokio_tungstenite::accept_hdr_async(stream, callback).await
+                    {
+                        while let Some(Ok(_)) = ws.next().await {}
+                    }
+                });
+            }
+        });
+
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, mut notifications) = broadcast::channel(1024);
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        let relay = Relay::new(
+            url.clone(),
+            pool_sender,
+            notification_sender,
+            None,
+            RelayOptions::default(),
+        );
+
+        relay.try_connect().await;
+        assert_eq!(relay.status().await, RelayStatus::Connected);
+
+        // Forced disconnection
+        relay.disconnect().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while relay.status().await != RelayStatus::Disconnected {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // First retry is rejected, the second succeed
+        relay.try_connect().await;
+        assert_eq!(relay.status().await, RelayStatus::Disconnected);
+        relay.try_connect().await;
+
+        let mut reconnections = Vec::new();
+        while let Ok(notification) = notifications.try_recv() {
+            if let RelayPoolNotification::Reconnected { url, attempts } = notification {
+                reconnections.push((url, attempts));
+            }
+        }
+        assert_eq!(reconnections, vec![(url, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        // Record the arrival time of every message
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (arrival_sender, mut arrivals) = mpsc::channel(1024);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let arrival_sender = arrival_sender.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                        while let Some(Ok(_)) = ws.next().await {
+                            let _ = arrival_sender.send(tokio::time::Instant::now()).await;
+                        }
+                    }
+                });
+            }
+        });
+
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, _) = broadcast::channel(1024);
+        let opts = RelayOptions::default().rate_limit(NonZeroU32::new(10).unwrap());
+        let relay = Relay::new(
+            Url::parse(&format!("ws://{addr}")).unwrap(),
+            pool_sender,
+            notification_sender,
+            None,
+            opts,
+        );
+        relay.connect(true).await;
+        assert_eq!(relay.opts().rate_limiter().unwrap().remaining(), 10);
+
+        let start = tokio::time::Instant::now();
+        for i in 0..25 {
+            relay
+                .send_msg(
+                    ClientMessage::close(SubscriptionId::new(i.to_string())),
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut last = start;
+        for _ in 0..25 {
+            last = tokio::time::timeout(Duration::from_secs(10), arrivals.recv())
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        // 10 in burst, the other 15 at 10 msg/sec
+        assert!(last.duration_since(start) >= Duration::from_millis(1400));
+
+        relay.terminate().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_raw_frames() {
+        use nostr::{EventBuilder, Keys};
+
+        // Parse the client frames and echo the events back as relay frames
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (parsed_sender, mut parsed) = mpsc::channel(1024);
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let (mut ws_tx, ws_rx) = ws.split();
+            let mut messages = Box::pin(frame::client_messages(ws_rx));
+            while let Some(msg) = messages.next().await {
+                if let Ok(ClientMessage::Event(event)) = &msg {
+                    let echo = RelayMessage::new_event(SubscriptionId::new("echo"), *event.clone());
+                    ws_tx.send(WsMessage::Text(echo.as_json())).await.unwrap();
+                }
+                let _ = parsed_sender.send(msg.ok()).await;
+            }
+        });
+
+        let (pool_sender, mut pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, _) = broadcast::channel(1024);
+        let relay = Relay::new(
+            Url::parse(&format!("ws://{addr}")).unwrap(),
+            pool_sender,
+            notification_sender,
+            None,
+            RelayOptions::default(),
+        );
+        relay.connect(true).await;
+
+        let event = EventBuilder::new_text_note("Echo", &[])
+            .to_event(&Keys::generate())
+            .unwrap();
+        let msg = ClientMessage::new_event(event.clone());
+        relay.send_raw(msg.as_json(), true).await.unwrap();
+        relay.send_raw(String::from("invalid"), true).await.unwrap();
+
+        for expected in [Some(msg), None] {
+            let received = tokio::time::timeout(Duration::from_secs(5), parsed.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(received, expected);
+        }
+
+        match tokio::time::timeout(Duration::from_secs(5), pool_receiver.recv())
+            .await
+            .unwrap()
+            .unwrap()
+        {
+            RelayPoolMessage::ReceivedMsg { msg, .. } => {
+                assert_eq!(
+                    msg,
+                    RelayMessage::new_event(SubscriptionId::new("echo"), event)
+                );
+            }
+            msg => panic!("unexpected message: {msg:?}"),
+        }
+
+        relay.terminate().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_offline_queue() {
+        // Forward the received messages
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (received_sender, mut received) = mpsc::channel(1024);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let received_sender = received_sender.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                        while let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                            let _ = received_sender.send(ClientMessage::from_json(text)).await;
+                        }
+                    }
+                });
+            }
+        });
+
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, mut notifications) = broadcast::channel(1024);
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        let opts = RelayOptions::default().offline_queue(1, QueueOverflow::DropOldest);
+        let relay = Relay::new(url.clone(), pool_sender, notification_sender, None, opts);
+
+        // Send while disconnected
+        relay.set_status(RelayStatus::Disconnected).await;
+        let first = ClientMessage::close(SubscriptionId::new("first"));
+        let second = ClientMessage::close(SubscriptionId::new("second"));
+        relay.send_msg(first.clone(), true).await.unwrap();
+        relay.send_msg(second.clone(), true).await.unwrap();
+
+        // The queue is full: the oldest is dropped
+        let mut dropped = Vec::new();
+        while let Ok(notification) = notifications.try_recv() {
+            if let RelayPoolNotification::MessageDropped {
+                url: relay_url,
+                msg,
+            } = notification
+            {
+                assert_eq!(relay_url, url);
+                dropped.push(msg);
+            }
+        }
+        assert_eq!(dropped, vec![first]);
+
+        // Flushed on connection
+        relay.try_connect().await;
+        assert_eq!(relay.status().await, RelayStatus::Connected);
+        let msg = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(msg, second);
+
+        relay.terminate().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_offline_queue_order() {
+        // Forward the received messages
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (received_sender, mut received) = mpsc::channel(1024);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let received_sender = received_sender.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await {
+                        while let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                            let _ = received_sender.send(ClientMessage::from_json(text)).await;
+                        }
+                    }
+                });
+            }
+        });
+
+        let (pool_sender, _pool_receiver) = mpsc::channel(1024);
+        let (notification_sender, mut notifications) = broadcast::channel(1024);
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        let opts = RelayOptions::default().offline_queue(10, QueueOverflow::DropOldest);
+        let relay = Relay::new(url, pool_sender, notification_sender, None, opts);
+
+        relay.set_status(RelayStatus::Disconnected).await;
+        let buffered: Vec<ClientMessage> = (0..5)
+            .map(|i| ClientMessage::close(SubscriptionId::new(format!("buffered-{i}"))))
+            .collect();
+        for msg in buffered.iter() {
+            relay.send_msg(msg.clone(), false).await.unwrap();
+        }
+
+        // Send a new message as soon as the relay is notified as connected
+        let live = ClientMessage::close(SubscriptionId::new("live"));
+        let sender = {
+            let relay = relay.clone();
+            let live = live.clone();
+            tokio::spawn(async move {
+                while let Ok(notification) = notifications.recv().await {
+                    if let RelayPoolNotification::RelayStatus {
+                        status: RelayStatus::Connected,
+                        ..
+                    } = notification
+                    {
+                        relay.send_msg(live, false).await.unwrap();
+                        break;
+                    }
+                }
+            })
+        };
+
+        relay.try_connect().await;
+        sender.await.unwrap();
+
+        // The buffered messages are delivered before the live one
+        let mut messages: Vec<ClientMessage> = Vec::new();
+        for _ in 0..buffered.len() + 1 {
+            let msg = tokio::time::timeout(Duration::from_secs(5), received.recv())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            messages.push(msg);
+        }
+        let mut expected = buffered;
+        expected.push(live);
+        assert_eq!(messages, expected);
+
+        relay.terminate().await.unwrap();
+    }
+
+    #[test]
+    fn test_auto_resubscribe_option() {
+        let opts = RelayOptions::default();
+        assert!(opts.auto_resubscribe());
+        opts.set_auto_resubscribe(false);
+        assert!(!opts.auto_resubscribe());
+    }
+}
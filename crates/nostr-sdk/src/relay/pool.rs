@@ -3,23 +3,28 @@
 
 //! Relay Pool
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::net::SocketAddr;
 #[cfg(feature = "sqlite")]
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+use nostr::key::XOnlyPublicKey;
 use nostr::url::Url;
-use nostr::{ClientMessage, Event, EventId, Filter, RelayMessage};
+use nostr::{ClientMessage, Event, EventId, Filter, RelayMessage, SubscriptionId};
 #[cfg(feature = "sqlite")]
 use nostr_sdk_sqlite::Store;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::{broadcast, Mutex};
 use tokio::time;
+use tokio_util::sync::CancellationToken;
 
-use super::{Error as RelayError, Relay, RelayOptions};
-use crate::thread;
+use super::{Error as RelayError, Relay, RelayOptions, RelayStatus};
+use crate::store::MemoryStore;
+use crate::thread::{self, TaskTracker};
 
 /// [`RelayPool`] error
 #[derive(Debug, thiserror::Error)]
@@ -69,31 +74,188 @@ pub enum RelayPoolNotification {
     Event(Url, Event),
     /// Received a [`RelayMessage`]
     Message(Url, RelayMessage),
+    /// Subscription re-established after (re)connection
+    Resubscribed(Url, SubscriptionId),
+    /// Relay connected again after a disconnection
+    Reconnected {
+        /// Relay url
+        url: Url,
+        /// Connection attempts needed to reconnect
+        attempts: usize,
+    },
+    /// [`RelayStatus`] changed
+    RelayStatus {
+        /// Relay url
+        url: Url,
+        /// New status
+        status: RelayStatus,
+    },
+    /// Outbound message dropped because the offline queue of the relay is full
+    ///
+    /// See `RelayOptions::offline_queue`.
+    MessageDropped {
+        /// Relay url
+        url: Url,
+        /// Dropped message
+        msg: ClientMessage,
+    },
+    /// Result of the NIP-05 verification of a received profile
+    ///
+    /// Emitted only if `Options::auto_verify_nip05` is enabled.
+    Nip05Verified {
+        /// Public key of the profile
+        pubkey: XOnlyPublicKey,
+        /// `true` if the `nip05` identifier resolves to the public key
+        verified: bool,
+    },
     /// Shutdown
     Shutdown,
 }
 
+/// Delivery report of an [`Event`] sent to relays
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeliveryReport {
+    /// Relays that accepted the event
+    pub acked: Vec<Url>,
+    /// Relays that rejected the event, with the reason
+    pub rejected: Vec<(Url, String)>,
+    /// Relays that not replied yet
+    pub pending: Vec<Url>,
+}
+
+impl DeliveryReport {
+    fn update(&mut self, relay_url: Url, status: bool, message: String) {
+        self.pending.retain(|url| url != &relay_url);
+        self.acked.retain(|url| url != &relay_url);
+        self.rejected.retain(|(url, _)| url != &relay_url);
+        if status {
+            self.acked.push(relay_url);
+        } else {
+            self.rejected.push((relay_url, message));
+        }
+    }
+}
+
+/// Result of [`RelayPool::flush`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlushReport {
+    /// Events accepted by at least one relay
+    pub confirmed: Vec<EventId>,
+    /// Events not accepted by any relay (rejected or not replied yet)
+    pub unconfirmed: Vec<EventId>,
+}
+
+/// Collect the `OK` responses of the relays for the sent events
+#[derive(Debug, Default)]
+struct DeliveryTracker {
+    reports: HashMap<EventId, DeliveryReport>,
+    order: VecDeque<EventId>,
+}
+
+impl DeliveryTracker {
+    fn sent(&mut self, event_id: EventId, relay_url: Url) {
+        if !self.reports.contains_key(&event_id) {
+            while self.order.len() >= MAX_EVENTS {
+                if let Some(id) = self.order.pop_front() {
+                    self.reports.remove(&id);
+                }
+            }
+            self.order.push_back(event_id);
+        }
+
+        let report = self.reports.entry(event_id).or_default();
+        if !report.pending.contains(&relay_url) {
+            report.pending.push(relay_url);
+        }
+    }
+
+    fn received(&mut self, event_id: EventId, relay_url: Url, status: bool, message: String) {
+        if let Some(report) = self.reports.get_mut(&event_id) {
+            report.update(relay_url, status, message);
+        }
+    }
+
+    fn get(&self, event_id: &EventId) -> Option<DeliveryReport> {
+        self.reports.get(event_id).cloned()
+    }
+
+    fn has_pending(&self) -> bool {
+        self.reports
+            .values()
+            .any(|report| !report.pending.is_empty())
+    }
+
+    fn flush_report(&self) -> FlushReport {
+        let mut flush = FlushReport::default();
+        for event_id in self.order.iter() {
+            if let Some(report) = self.reports.get(event_id) {
+                if report.acked.is_empty() {
+                    flush.unconfirmed.push(*event_id);
+                } else {
+                    flush.confirmed.push(*event_id);
+                }
+            }
+        }
+        flush
+    }
+}
+
+/// Custom filter applied to the received events (see `Options::event_filter`)
+#[derive(Clone)]
+pub(crate) struct EventFilter(Arc<dyn Fn(&Event) -> bool + Send + Sync>);
+
+impl fmt::Debug for EventFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventFilter")
+    }
+}
+
+impl EventFilter {
+    pub fn new(filter: Arc<dyn Fn(&Event) -> bool + Send + Sync>) -> Self {
+        Self(filter)
+    }
+
+    /// Check if the event is accepted
+    pub fn check(&self, event: &Event) -> bool {
+        (self.0)(event)
+    }
+}
+
+type SharedEventFilter = Arc<RwLock<Option<EventFilter>>>;
+
 struct RelayPoolTask {
     receiver: Receiver<RelayPoolMessage>,
     notification_sender: broadcast::Sender<RelayPoolNotification>,
     events: VecDeque<EventId>,
+    delivery: Arc<Mutex<DeliveryTracker>>,
+    event_filter: SharedEventFilter,
     #[cfg(feature = "sqlite")]
     store: Option<Store>,
+    #[cfg(feature = "sqlite")]
+    store_ephemeral: Arc<AtomicBool>,
 }
 
 const MAX_EVENTS: usize = 100000;
+/// Max time to wait for the background tasks on shutdown
+const SHUTDOWN_TASKS_TIMEOUT: Duration = Duration::from_secs(10);
 
 impl RelayPoolTask {
     pub fn new(
         pool_task_receiver: Receiver<RelayPoolMessage>,
         notification_sender: broadcast::Sender<RelayPoolNotification>,
+        delivery: Arc<Mutex<DeliveryTracker>>,
+        event_filter: SharedEventFilter,
     ) -> Self {
         Self {
             receiver: pool_task_receiver,
             events: VecDeque::new(),
             notification_sender,
+            delivery,
+            event_filter,
             #[cfg(feature = "sqlite")]
             store: None,
+            #[cfg(feature = "sqlite")]
+            store_ephemeral: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -101,13 +263,19 @@ impl RelayPoolTask {
     pub fn new_with_store(
         pool_task_receiver: Receiver<RelayPoolMessage>,
         notification_sender: broadcast::Sender<RelayPoolNotification>,
+        delivery: Arc<Mutex<DeliveryTracker>>,
+        event_filter: SharedEventFilter,
         store: Option<Store>,
+        store_ephemeral: Arc<AtomicBool>,
     ) -> Self {
         Self {
             receiver: pool_task_receiver,
             events: VecDeque::new(),
             notification_sender,
+            delivery,
+            event_filter,
             store,
+            store_ephemeral,
         }
     }
 
@@ -116,6 +284,32 @@ impl RelayPoolTask {
         while let Some(msg) = self.receiver.recv().await {
             match msg {
                 RelayPoolMessage::ReceivedMsg { relay_url, msg } => {
+                    // Verify the event and drop it, before any notification, if rejected by the custom filter
+                    let verified: bool = match &msg {
+                        RelayMessage::Event { event, .. } => {
+                            if event.verify().is_err() {
+                                false
+                            } else if !self.is_accepted(event) {
+                                log::debug!("Event {} rejected by the event filter", event.id);
+                                continue;
+                            } else {
+                                true
+                            }
+                        }
+                        _ => false,
+                    };
+
+                    // Update the delivery reports before notifying (see `RelayPool::flush`)
+                    if let RelayMessage::Ok {
+                        event_id,
+                        status,
+                        message,
+                    } = &msg
+                    {
+                        let mut delivery = self.delivery.lock().await;
+                        delivery.received(*event_id, relay_url.clone(), *status, message.clone());
+                    }
+
                     let _ = self
                         .notification_sender
                         .send(RelayPoolNotification::Message(
@@ -124,8 +318,7 @@ impl RelayPoolTask {
                         ));
 
                     if let RelayMessage::Event { event, .. } = msg {
-                        // Verifies if the event is valid
-                        if event.verify().is_ok() {
+                        if verified {
                             // Adds only new events
                             if !self.events.contains(&event.id) {
                                 self.add_event(event.id);
@@ -136,7 +329,10 @@ impl RelayPoolTask {
 
                             // Save event into store
                             #[cfg(feature = "sqlite")]
-                            if let Some(store) = &self.store {
+                            if let Some(store) = self.store.as_ref().filter(|_| {
+                                !event.kind.is_ephemeral()
+                                    || self.store_ephemeral.load(Ordering::SeqCst)
+                            }) {
                                 match store.insert_event(*event) {
                                     Ok(_) => log::trace!("Event saved into store"),
                                     Err(e) => {
@@ -165,6 +361,11 @@ impl RelayPoolTask {
         }
     }
 
+    fn is_accepted(&self, event: &Event) -> bool {
+        let filter = self.event_filter.read().unwrap_or_else(|e| e.into_inner());
+        filter.as_ref().map_or(true, |f| f.check(event))
+    }
+
     fn add_event(&mut self, event_id: EventId) {
         while self.events.len() >= MAX_EVENTS {
             self.events.pop_front();
@@ -180,6 +381,13 @@ pub struct RelayPool {
     pool_task_sender: Sender<RelayPoolMessage>,
     notification_sender: broadcast::Sender<RelayPoolNotification>,
     filters: Arc<Mutex<Vec<Filter>>>,
+    subscriptions: Arc<Mutex<HashMap<SubscriptionId, Vec<Filter>>>>,
+    /// Cancel the storing tasks of [`RelayPool::subscribe_and_store`]
+    store_tasks: Arc<Mutex<HashMap<SubscriptionId, CancellationToken>>>,
+    delivery: Arc<Mutex<DeliveryTracker>>,
+    tasks: TaskTracker,
+    store_ephemeral: Arc<AtomicBool>,
+    event_filter: SharedEventFilter,
     #[cfg(feature = "sqlite")]
     store: Option<Store>,
 }
@@ -196,16 +404,30 @@ impl RelayPool {
         let (notification_sender, _) = broadcast::channel(1024);
         let (pool_task_sender, pool_task_receiver) = mpsc::channel(1024);
 
-        let mut relay_pool_task =
-            RelayPoolTask::new(pool_task_receiver, notification_sender.clone());
+        let delivery = Arc::new(Mutex::new(DeliveryTracker::default()));
+        let event_filter: SharedEventFilter = Arc::new(RwLock::new(None));
+
+        let mut relay_pool_task = RelayPoolTask::new(
+            pool_task_receiver,
+            notification_sender.clone(),
+            delivery.clone(),
+            event_filter.clone(),
+        );
 
-        thread::spawn(async move { relay_pool_task.run().await });
+        let tasks = TaskTracker::default();
+        tasks.spawn(async move { relay_pool_task.run().await });
 
         Self {
             relays: Arc::new(Mutex::new(HashMap::new())),
             pool_task_sender,
             notification_sender,
             filters: Arc::new(Mutex::new(Vec::new())),
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            store_tasks: Arc::new(Mutex::new(HashMap::new())),
+            delivery,
+            tasks,
+            store_ephemeral: Arc::new(AtomicBool::new(false)),
+            event_filter,
             #[cfg(feature = "sqlite")]
             store: None,
         }
@@ -221,20 +443,33 @@ impl RelayPool {
         let (pool_task_sender, pool_task_receiver) = mpsc::channel(1024);
 
         let store = Some(Store::open(path)?);
+        let delivery = Arc::new(Mutex::new(DeliveryTracker::default()));
+        let store_ephemeral = Arc::new(AtomicBool::new(false));
+        let event_filter: SharedEventFilter = Arc::new(RwLock::new(None));
 
         let mut relay_pool_task = RelayPoolTask::new_with_store(
             pool_task_receiver,
             notification_sender.clone(),
+            delivery.clone(),
+            event_filter.clone(),
             store.clone(),
+            store_ephemeral.clone(),
         );
 
-        thread::spawn(async move { relay_pool_task.run().await });
+        let tasks = TaskTracker::default();
+        tasks.spawn(async move { relay_pool_task.run().await });
 
         Ok(Self {
             relays: Arc::new(Mutex::new(HashMap::new())),
             pool_task_sender,
             notification_sender,
             filters: Arc::new(Mutex::new(Vec::new())),
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            store_tasks: Arc::new(Mutex::new(HashMap::new())),
+            delivery,
+            tasks,
+            store_ephemeral,
+            event_filter,
             #[cfg(feature = "sqlite")]
             store,
         })
@@ -245,12 +480,54 @@ impl RelayPool {
         self.notification_sender.subscribe()
     }
 
+    /// Number of running background tasks (relay connections, subscriptions, etc.)
+    pub fn task_count(&self) -> usize {
+        self.tasks.count()
+    }
+
+    /// Set if the ephemeral events (kinds `20000-29999`) are saved into the stores (default: `false`)
+    pub fn set_store_ephemeral(&self, enable: bool) {
+        self.store_ephemeral.store(enable, Ordering::SeqCst);
+    }
+
+    /// Check if an event can be saved into the stores (see [`RelayPool::set_store_ephemeral`])
+    pub(crate) fn is_storable(&self, event: &Event) -> bool {
+        !event.kind.is_ephemeral() || self.store_ephemeral.load(Ordering::SeqCst)
+    }
+
+    /// Set the custom filter applied to the received events (see `Options::event_filter`)
+    pub(crate) fn set_event_filter(&self, filter: Option<EventFilter>) {
+        let mut f = self.event_filter.write().unwrap_or_else(|e| e.into_inner());
+        *f = filter;
+    }
+
+    /// Get the [`TaskTracker`] of the pool
+    pub(crate) fn tasks(&self) -> &TaskTracker {
+        &self.tasks
+    }
+
+    /// Send a [`RelayPoolNotification`] to the listeners
+    pub(crate) fn notify(&self, notification: RelayPoolNotification) {
+        let _ = self.notification_sender.send(notification);
+    }
+
     /// Get relays
     pub async fn relays(&self) -> HashMap<Url, Relay> {
         let relays = self.relays.lock().await;
         relays.clone()
     }
 
+    /// Get a snapshot of the [`RelayStatus`] of every relay
+    pub async fn relays_status(&self) -> HashMap<Url, RelayStatus> {
+        // Release the pool lock before awaiting each relay status
+        let relays: HashMap<Url, Relay> = self.relays().await;
+        let mut statuses: HashMap<Url, RelayStatus> = HashMap::with_capacity(relays.len());
+        for (url, relay) in relays.into_iter() {
+            statuses.insert(url, relay.status().await);
+        }
+        statuses
+    }
+
     /// Get [`Store`]
     #[cfg(feature = "sqlite")]
     pub fn store(&self) -> Option<Store> {
@@ -262,6 +539,26 @@ impl RelayPool {
         self.filters.lock().await.clone()
     }
 
+    /// Get [`DeliveryReport`] of a sent [`Event`]
+    pub async fn delivery_report(&self, event_id: EventId) -> Option<DeliveryReport> {
+        let delivery = self.delivery.lock().await;
+        delivery.get(&event_id)
+    }
+
+    /// Wait (up to `timeout`) for the `OK` of the sent events still pending
+    pub async fn flush(&self, timeout: Duration) -> FlushReport {
+        let mut notifications = self.notifications();
+        let _ = time::timeout(timeout, async {
+            while self.delivery.lock().await.has_pending() {
+                if let Err(broadcast::error::RecvError::Closed) = notifications.recv().await {
+                    break;
+                }
+            }
+        })
+        .await;
+        self.delivery.lock().await.flush_report()
+    }
+
     /// Update subscription filters
     async fn update_subscription_filters(&self, filters: Vec<Filter>) {
         let mut f = self.filters.lock().await;
@@ -289,7 +586,8 @@ impl RelayPool {
                 self.notification_sender.clone(),
                 proxy,
                 opts,
-            );
+            )
+            .task_tracker(self.tasks.clone());
             relays.insert(relay.url(), relay);
         }
         Ok(())
@@ -339,11 +637,20 @@ impl RelayPool {
             {
                 log::error!("{e}");
             };
+
+            let mut delivery = self.delivery.lock().await;
+            for url in relays.keys() {
+                delivery.sent(event.id, url.clone());
+            }
         }
 
         for (url, relay) in relays.into_iter() {
             if let Err(e) = relay.send_msg(msg.clone(), wait).await {
                 log::error!("Impossible to send msg to {url}: {e}");
+                if let ClientMessage::Event(event) = &msg {
+                    let mut delivery = self.delivery.lock().await;
+                    delivery.received(event.id, url, false, e.to_string());
+                }
             }
         }
 
@@ -354,6 +661,10 @@ impl RelayPool {
     pub async fn send_msg_to(&self, url: Url, msg: ClientMessage, wait: bool) -> Result<(), Error> {
         let relays = self.relays().await;
         if let Some(relay) = relays.get(&url) {
+            if let ClientMessage::Event(event) = &msg {
+                let mut delivery = self.delivery.lock().await;
+                delivery.sent(event.id, url.clone());
+            }
             relay.send_msg(msg, wait).await?;
             Ok(())
         } else {
@@ -361,6 +672,32 @@ impl RelayPool {
         }
     }
 
+    /// Send a raw text frame to all relays
+    ///
+    /// See [`Relay::send_raw`].
+    pub async fn send_raw(&self, frame: String, wait: bool) -> Result<(), Error> {
+        let relays = self.relays().await;
+
+        if relays.is_empty() {
+            return Err(Error::NoRelayConnected);
+        }
+
+        for (url, relay) in relays.into_iter() {
+            if let Err(e) = relay.send_raw(frame.clone(), wait).await {
+                log::error!("Impossible to send raw frame to {url}: {e}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Broadcast a [`RelayMessage`] to all relays (relay mode)
+    ///
+    /// See [`Relay::send_relay_msg`].
+    pub async fn broadcast_relay_msg(&self, msg: RelayMessage, wait: bool) -> Result<(), Error> {
+        self.send_raw(msg.as_json(), wait).await
+    }
+
     /// Subscribe to filters
     pub async fn subscribe(&self, filters: Vec<Filter>, wait: bool) {
         let relays = self.relays().await;
@@ -372,9 +709,114 @@ impl RelayPool {
         }
     }
 
-    /// Unsubscribe from filters
+    /// Subscribe to filters with custom [`SubscriptionId`]
+    ///
+    /// Multiple subscriptions with different ids can be active at the same time.
+    pub async fn subscribe_with_id(&self, id: SubscriptionId, filters: Vec<Filter>, wait: bool) {
+        let relays = self.relays().await;
+        {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.insert(id.clone(), filters.clone());
+        }
+        for relay in relays.values() {
+            if let Err(e) = relay
+                .subscribe_with_id(id.clone(), filters.clone(), wait)
+                .await
+            {
+                log::error!("{e}");
+            }
+        }
+    }
+
+    /// Subscribe to filters and store every verified event matching them into the [`MemoryStore`]
+    ///
+    /// Events are stored until the subscription is closed
+    /// (see [`RelayPool::unsubscribe_from`] and [`RelayPool::unsubscribe`]).
+    pub async fn subscribe_and_store(
+        &self,
+        filters: Vec<Filter>,
+        store: MemoryStore,
+        wait: bool,
+    ) -> SubscriptionId {
+        let id = SubscriptionId::generate();
+        let mut notifications = self.notifications();
+
+        let cancel = CancellationToken::new();
+        self.store_tasks
+            .lock()
+            .await
+            .insert(id.clone(), cancel.clone());
+
+        let subscription_id = id.clone();
+        let store_filters = filters.clone();
+        let store_ephemeral = self.store_ephemeral.clone();
+        self.tasks.spawn(async move {
+            loop {
+                let notification = tokio::select! {
+                    notification = notifications.recv() => match notification {
+                        Ok(notification) => notification,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            log::warn!("Store thread of subscription {subscription_id:?} lagged: {skipped} notifications skipped");
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = cancel.cancelled() => break,
+                };
+                match notification {
+                    RelayPoolNotification::Message(
+                        _,
+                        RelayMessage::Event {
+                            subscription_id: sub_id,
+                            event,
+                        },
+                    ) if sub_id == subscription_id => {
+                        if event.verify().is_ok()
+                            && (!event.kind.is_ephemeral()
+                                || store_ephemeral.load(Ordering::SeqCst))
+                            && store_filters.iter().any(|f| f.matches_event(&event))
+                        {
+                            store.insert(*event).await;
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+            log::debug!("Exited from store thread of subscription {subscription_id:?}");
+        });
+
+        self.subscribe_with_id(id.clone(), filters, wait).await;
+        id
+    }
+
+    /// Unsubscribe from subscription with custom [`SubscriptionId`]
+    pub async fn unsubscribe_from(&self, id: SubscriptionId, wait: bool) {
+        let relays = self.relays().await;
+        {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.remove(&id);
+        }
+        if let Some(cancel) = self.store_tasks.lock().await.remove(&id) {
+            cancel.cancel();
+        }
+        for relay in relays.values() {
+            if let Err(e) = relay.unsubscribe_from(id.clone(), wait).await {
+                log::error!("{e}");
+            }
+        }
+    }
+
+    /// Unsubscribe from all subscriptions
     pub async fn unsubscribe(&self, wait: bool) {
         let relays = self.relays().await;
+        {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.clear();
+        }
+        for (_, cancel) in self.store_tasks.lock().await.drain() {
+            cancel.cancel();
+        }
         for relay in relays.values() {
             if let Err(e) = relay.unsubscribe(wait).await {
                 log::error!("{e}");
@@ -388,16 +830,109 @@ impl RelayPool {
         filters: Vec<Filter>,
         timeout: Option<Duration>,
     ) -> Result<Vec<Event>, Error> {
-        let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
+        self.get_events_of_with_opts(filters, None, timeout).await
+    }
+
+    /// Get events of filters from a subset of relays
+    ///
+    /// If `relays` is `None`, the request is sent to all the relays of the pool.
+    /// Return [`Error::RelayNotFound`] if one of the `relays` isn't in the pool.
+    pub async fn get_events_of_with_opts(
+        &self,
+        filters: Vec<Filter>,
+        relays: Option<Vec<Url>>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        Ok(self
+            .fetch_events_of(filters, relays, timeout, None)
+            .await?
+            .into_iter()
+            .map(|(_, event)| event)
+            .collect())
+    }
+
+    /// Get events of filters, with the relays that served each of them
+    ///
+    /// Events are deduplicated by id: the sources of the duplicates are merged.
+    pub async fn get_events_of_with_sources(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<(Event, HashSet<Url>)>, Error> {
+        let mut events: Vec<(Event, HashSet<Url>)> = Vec::new();
+        let mut positions: HashMap<EventId, usize> = HashMap::new();
+        for (url, event) in self
+            .fetch_events_of(filters, None, timeout, None)
+            .await?
+            .into_iter()
+        {
+            match positions.get(&event.id) {
+                Some(pos) => {
+                    events[*pos].1.insert(url);
+                }
+                None => {
+                    positions.insert(event.id, events.len());
+                    events.push((event, HashSet::from([url])));
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Get events of filters, stopping early when `cancel` is canceled
+    ///
+    /// The subscriptions are closed on cancellation and the events received so far are returned.
+    pub async fn get_events_of_cancelable(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+        cancel: CancellationToken,
+    ) -> Result<Vec<Event>, Error> {
+        Ok(self
+            .fetch_events_of(filters, None, timeout, Some(cancel))
+            .await?
+            .into_iter()
+            .map(|(_, event)| event)
+            .collect())
+    }
+
+    /// Get events of filters, paired with the relay that sent them
+    async fn fetch_events_of(
+        &self,
+        filters: Vec<Filter>,
+        relays: Option<Vec<Url>>,
+        timeout: Option<Duration>,
+        cancel: Option<CancellationToken>,
+    ) -> Result<Vec<(Url, Event)>, Error> {
+        let mut pool_relays = self.relays().await;
+        let relays: HashMap<Url, Relay> = match relays {
+            Some(urls) => {
+                let mut selected = HashMap::with_capacity(urls.len());
+                for url in urls.into_iter() {
+                    let relay = pool_relays.remove(&url).ok_or(Error::RelayNotFound)?;
+                    selected.insert(url, relay);
+                }
+                selected
+            }
+            None => pool_relays,
+        };
+
+        let events: Arc<Mutex<Vec<(Url, Event)>>> = Arc::new(Mutex::new(Vec::new()));
         let mut handles = Vec::new();
-        let relays = self.relays().await;
+        // `exclude_authors` is client-side only
+        let post_filter: bool = filters.iter().any(|f| f.exclude_authors.is_some());
         for (url, relay) in relays.into_iter() {
             let filters = filters.clone();
             let events = events.clone();
-            let handle = thread::spawn(async move {
+            let cancel = cancel.clone();
+            let handle = self.tasks.spawn(async move {
+                let matching_filters = filters.clone();
                 if let Err(e) = relay
-                    .get_events_of_with_callback(filters, timeout, |event| async {
-                        events.lock().await.push(event);
+                    .get_events_of_with_cancel(filters, timeout, cancel, |event| async {
+                        if !post_filter || matching_filters.iter().any(|f| f.matches_event(&event))
+                        {
+                            events.lock().await.push((url.clone(), event));
+                        }
                     })
                     .await
                 {
@@ -414,6 +949,83 @@ impl RelayPool {
         Ok(events.lock_owned().await.clone())
     }
 
+    /// Race the query across relays and return the first verified [`Event`] matching the filters
+    ///
+    /// The subscriptions are closed as soon as an event is received.
+    /// Return `None` if no relay has a matching event (or on timeout).
+    pub async fn get_event_fastest(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Event>, Error> {
+        let relays = self.relays().await;
+
+        if relays.is_empty() {
+            return Err(Error::NoRelayConnected);
+        }
+
+        let id = SubscriptionId::generate();
+        let mut notifications = self.notifications();
+
+        let mut requested: usize = 0;
+        for (url, relay) in relays.iter() {
+            match relay
+                .send_msg(ClientMessage::new_req(id.clone(), filters.clone()), false)
+                .await
+            {
+                Ok(_) => requested += 1,
+                Err(e) => log::error!("Impossible to send REQ to {url}: {e}"),
+            }
+        }
+
+        let recv = async {
+            let mut eose: usize = 0;
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::Message(_, msg) = notification {
+                    match msg {
+                        RelayMessage::Event {
+                            subscription_id,
+                            event,
+                        } if subscription_id == id => {
+                            if event.verify().is_ok()
+                                && filters.iter().any(|f| f.matches_event(&event))
+                            {
+                                return Some(*event);
+                            }
+                        }
+                        RelayMessage::EndOfStoredEvents(subscription_id)
+                            if subscription_id == id =>
+                        {
+                            eose += 1;
+                            if eose >= requested {
+                                break;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            None
+        };
+
+        let event: Option<Event> = match timeout {
+            Some(timeout) => time::timeout(timeout, recv).await.unwrap_or_default(),
+            None => recv.await,
+        };
+
+        // Close subscriptions
+        for (url, relay) in relays.into_iter() {
+            if let Err(e) = relay
+                .send_msg(ClientMessage::close(id.clone()), false)
+                .await
+            {
+                log::error!("Impossible to close subscription with {url}: {e}");
+            }
+        }
+
+        Ok(event)
+    }
+
     /// Request events of filter. All events will be sent to notification listener
     pub async fn req_events_of(&self, filters: Vec<Filter>, timeout: Option<Duration>) {
         let relays = self.relays().await;
@@ -444,6 +1056,10 @@ impl RelayPool {
     pub async fn connect_relay(&self, relay: &Relay, wait_for_connection: bool) {
         let filters: Vec<Filter> = self.subscription_filters().await;
         relay.update_subscription_filters(filters).await;
+        let subscriptions = self.subscriptions.lock().await.clone();
+        for (id, filters) in subscriptions.into_iter() {
+            relay.update_subscription_filters_with_id(id, filters).await;
+        }
         relay.connect(wait_for_connection).await;
         #[cfg(feature = "sqlite")]
         if let Some(store) = &self.store {
@@ -472,6 +1088,12 @@ impl RelayPool {
         if let Err(e) = self.pool_task_sender.send(RelayPoolMessage::Shutdown).await {
             log::error!("Impossible to shutdown pool: {e}");
         };
+        if time::timeout(SHUTDOWN_TASKS_TIMEOUT, self.tasks.wait())
+            .await
+            .is_err()
+        {
+            log::warn!("{} tasks still running after shutdown", self.tasks.count());
+        }
         #[cfg(feature = "sqlite")]
         if let Some(store) = self.store {
             store.close();
@@ -479,3 +1101,133 @@ impl RelayPool {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+
+    #[test]
+    fn test_delivery_report() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("test", &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let relay1 = Url::parse("wss://relay1.example.com").unwrap();
+        let relay2 = Url::parse("wss://relay2.example.com").unwrap();
+        let relay3 = Url::parse("wss://relay3.example.com").unwrap();
+
+        let mut tracker = DeliveryTracker::default();
+        tracker.sent(event.id, relay1.clone());
+        tracker.sent(event.id, relay2.clone());
+        tracker.sent(event.id, relay3.clone());
+
+        tracker.received(event.id, relay1.clone(), true, String::new());
+        tracker.received(
+            event.id,
+            relay2.clone(),
+            false,
+            String::from("blocked: spam"),
+        );
+
+        let report = tracker.get(&event.id).unwrap();
+        assert_eq!(report.acked, vec![relay1]);
+        assert_eq!(
+            report.rejected,
+            vec![(relay2, String::from("blocked: spam"))]
+        );
+        assert_eq!(report.pending, vec![relay3]);
+
+        let other = EventBuilder::new_text_note("other", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert!(tracker.get(&other.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_relays_status() {
+        let pool = RelayPool::new();
+        let url1 = Url::parse("wss://relay1.example.com").unwrap();
+        let url2 = Url::parse("wss://relay2.example.com").unwrap();
+        pool.add_relay(url1.clone(), None, RelayOptions::default())
+            .await
+            .unwrap();
+        pool.add_relay(url2.clone(), None, RelayOptions::default())
+            .await
+            .unwrap();
+
+        let statuses = pool.relays_status().await;
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses.get(&url1), Some(&RelayStatus::Initialized));
+
+        let relays = pool.relays().await;
+        relays[&url1].set_status(RelayStatus::Connected).await;
+        relays[&url2].set_status(RelayStatus::Disconnected).await;
+
+        let statuses = pool.relays_status().await;
+        assert_eq!(statuses.get(&url1), Some(&RelayStatus::Connected));
+        assert_eq!(statuses.get(&url2), Some(&RelayStatus::Disconnected));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_store() {
+        let pool = RelayPool::new();
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        let store = MemoryStore::new();
+
+        let keys = Keys::generate();
+        let other_keys = Keys::generate();
+        let event = EventBuilder::new_text_note("stored", &[])
+            .to_event(&keys)
+            .unwrap();
+        let not_matching = EventBuilder::new_text_note("not stored", &[])
+            .to_event(&other_keys)
+            .unwrap();
+
+        let filters = vec![Filter::new().author(keys.public_key())];
+        let id = pool
+            .subscribe_and_store(filters, store.clone(), false)
+            .await;
+
+        for event in [event.clone(), event.clone(), not_matching] {
+            pool.pool_task_sender
+                .send(RelayPoolMessage::ReceivedMsg {
+                    relay_url: url.clone(),
+                    msg: RelayMessage::new_event(id.clone(), event),
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            events = store.query(vec![Filter::new()]).await;
+            if !events.is_empty() {
+                break;
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(events, vec![event]);
+        assert_eq!(store.len().await, 1);
+
+        // The storing task is stopped on unsubscribe
+        pool.unsubscribe_from(id.clone(), false).await;
+        assert!(pool.store_tasks.lock().await.is_empty());
+
+        let after = EventBuilder::new_text_note("after unsubscribe", &[])
+            .to_event(&keys)
+            .unwrap();
+        pool.pool_task_sender
+            .send(RelayPoolMessage::ReceivedMsg {
+                relay_url: url.clone(),
+                msg: RelayMessage::new_event(id, after),
+            })
+            .await
+            .unwrap();
+        time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(store.len().await, 1);
+    }
+}
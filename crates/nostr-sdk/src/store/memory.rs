@@ -0,0 +1,334 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Memory store
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use nostr::key::XOnlyPublicKey;
+use nostr::{Event, EventId, Filter, Tag};
+use tokio::sync::RwLock;
+
+/// Replaceable event key: author, kind and `d` tag (only for parameterized replaceable events)
+type ReplaceableKey = (XOnlyPublicKey, u64, Option<String>);
+
+#[derive(Debug, Default)]
+struct Indexes {
+    events: HashMap<EventId, Event>,
+    authors: HashMap<XOnlyPublicKey, HashSet<EventId>>,
+    kinds: HashMap<u64, HashSet<EventId>>,
+    /// `e` tags
+    referenced_events: HashMap<EventId, HashSet<EventId>>,
+    /// `p` tags
+    referenced_pubkeys: HashMap<XOnlyPublicKey, HashSet<EventId>>,
+    replaceable: HashMap<ReplaceableKey, EventId>,
+}
+
+impl Indexes {
+    fn insert(&mut self, event: Event) -> bool {
+        if self.events.contains_key(&event.id) {
+            return false;
+        }
+
+        if let Some(key) = replaceable_key(&event) {
+            if let Some(stored_id) = self.replaceable.get(&key).copied() {
+                if let Some(stored) = self.events.get(&stored_id) {
+                    // Keep the newest one (the lowest id in case of same timestamp)
+                    if (stored.created_at, event.id) < (event.created_at, stored.id) {
+                        self.remove(&stored_id);
+                    } else {
+                        return false;
+                    }
+                }
+            }
+            self.replaceable.insert(key, event.id);
+        }
+
+        let id: EventId = event.id;
+        self.authors.entry(event.pubkey).or_default().insert(id);
+        self.kinds
+            .entry(event.kind.as_u64())
+            .or_default()
+            .insert(id);
+        for tag in event.tags.iter() {
+            match tag {
+                Tag::Event(event_id, ..) => {
+                    self.referenced_events
+                        .entry(*event_id)
+                        .or_default()
+                        .insert(id);
+                }
+                Tag::PubKey(pk, ..) | Tag::ContactList { pk, .. } => {
+                    self.referenced_pubkeys.entry(*pk).or_default().insert(id);
+                }
+                _ => (),
+            }
+        }
+        self.events.insert(id, event);
+
+        true
+    }
+
+    fn remove(&mut self, id: &EventId) -> Option<Event> {
+        let event: Event = self.events.remove(id)?;
+
+        if let Some(ids) = self.authors.get_mut(&event.pubkey) {
+            ids.remove(id);
+        }
+        if let Some(ids) = self.kinds.get_mut(&event.kind.as_u64()) {
+            ids.remove(id);
+        }
+        for tag in event.tags.iter() {
+            match tag {
+                Tag::Event(event_id, ..) => {
+                    if let Some(ids) = self.referenced_events.get_mut(event_id) {
+                        ids.remove(id);
+                    }
+                }
+                Tag::PubKey(pk, ..) | Tag::ContactList { pk, .. } => {
+                    if let Some(ids) = self.referenced_pubkeys.get_mut(pk) {
+                        ids.remove(id);
+                    }
+                }
+                _ => (),
+            }
+        }
+        if let Some(key) = replaceable_key(&event) {
+            if self.replaceable.get(&key) == Some(id) {
+                self.replaceable.remove(&key);
+            }
+        }
+
+        Some(event)
+    }
+
+    /// Get the smallest candidates set for the [`Filter`] using the indexes
+    fn candidates(&self, filter: &Filter) -> Vec<EventId> {
+        let mut sets: Vec<HashSet<EventId>> = Vec::new();
+
+        if let Some(authors) = &filter.authors {
+            sets.push(collect(authors.iter().filter_map(|a| self.authors.get(a))));
+        }
+        if let Some(kinds) = &filter.kinds {
+            sets.push(collect(
+                kinds.iter().filter_map(|k| self.kinds.get(&k.as_u64())),
+            ));
+        }
+        if let Some(events) = &filter.events {
+            sets.push(collect(
+                events.iter().filter_map(|e| self.referenced_events.get(e)),
+            ));
+        }
+        if let Some(pubkeys) = &filter.pubkeys {
+            sets.push(collect(
+                pubkeys
+                    .iter()
+                    .filter_map(|p| self.referenced_pubkeys.get(p)),
+            ));
+        }
+
+        match sets.into_iter().min_by_key(|set| set.len()) {
+            Some(set) => set.into_iter().collect(),
+            None => self.events.keys().copied().collect(),
+        }
+    }
+
+    fn query(&self, filters: &[Filter]) -> Vec<Event> {
+        let mut ids: HashSet<EventId> = HashSet::new();
+        let mut result: Vec<Event> = Vec::new();
+
+        for filter in filters.iter() {
+            let mut matched: Vec<&Event> = self
+                .candidates(filter)
+                .iter()
+                .filter_map(|id| self.events.get(id))
+                .filter(|event| filter.matches_event(event))
+                .collect();
+
+            // Newest first
+            matched.sort_by(|a, b| a.cmp_by_date(b));
+
+            if let Some(limit) = filter.limit {
+                matched.truncate(limit);
+            }
+
+            for event in matched.into_iter() {
+                if ids.insert(event.id) {
+                    result.push(event.clone());
+                }
+            }
+        }
+
+        result.sort_by(Event::cmp_by_date);
+        result
+    }
+}
+
+fn replaceable_key(event: &Event) -> Option<ReplaceableKey> {
+    if event.kind.is_replaceable() {
+        Some((event.pubkey, event.kind.as_u64(), None))
+    } else if event.kind.is_parameterized_replaceable() {
+        let identifier: String = event
+            .tags
+            .iter()
+            .find_map(|tag| match tag {
+                Tag::Identifier(id) => Some(id.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Some((event.pubkey, event.kind.as_u64(), Some(identifier)))
+    } else {
+        None
+    }
+}
+
+fn collect<'a, I>(sets: I) -> HashSet<EventId>
+where
+    I: Iterator<Item = &'a HashSet<EventId>>,
+{
+    sets.flat_map(|set| set.iter().copied()).collect()
+}
+
+/// In-memory event store
+///
+/// Events are indexed by id, author, kind and `e`/`p` tags.
+/// Replaceable events (NIP-16 and NIP-33) supersede the older ones.
+/// Ephemeral events are stored too: whether to keep them is up to the caller (see `Options::store_ephemeral`).
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    indexes: Arc<RwLock<Indexes>>,
+}
+
+impl MemoryStore {
+    /// New empty [`MemoryStore`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert [`Event`]
+    ///
+    /// Return `false` if the event was not stored (already stored or superseded by a newer one)
+    pub async fn insert(&self, event: Event) -> bool {
+        let mut indexes = self.indexes.write().await;
+        indexes.insert(event)
+    }
+
+    /// Get [`Event`] by [`EventId`]
+    pub async fn get(&self, id: &EventId) -> Option<Event> {
+        let indexes = self.indexes.read().await;
+        indexes.events.get(id).cloned()
+    }
+
+    /// Remove [`Event`] by [`EventId`]
+    pub async fn remove(&self, id: &EventId) -> Option<Event> {
+        let mut indexes = self.indexes.write().await;
+        indexes.remove(id)
+    }
+
+    /// Query stored events matching at least one of the [`Filter`]s
+    ///
+    /// Events are sorted from the newest to the oldest.
+    pub async fn query(&self, filters: Vec<Filter>) -> Vec<Event> {
+        let indexes = self.indexes.read().await;
+        indexes.query(&filters)
+    }
+
+    /// Get number of stored events
+    pub async fn len(&self) -> usize {
+        let indexes = self.indexes.read().await;
+        indexes.events.len()
+    }
+
+    /// Check if store is empty
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Kind, Metadata, Timestamp, UnsignedEvent};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_store_query() {
+        let keys = Keys::generate();
+        let other_keys = Keys::generate();
+        let store = MemoryStore::new();
+
+        let note = EventBuilder::new_text_note("Hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        let reply = EventBuilder::new_text_note(
+            "Reply",
+            &[
+                Tag::Event(note.id, None, None),
+                Tag::PubKey(keys.public_key(), None),
+            ],
+        )
+        .to_event(&other_keys)
+        .unwrap();
+
+        assert!(store.insert(note.clone()).await);
+        assert!(store.insert(reply.clone()).await);
+        assert!(!store.insert(note.clone()).await);
+        assert_eq!(store.len().await, 2);
+
+        let events = store
+            .query(vec![Filter::new().author(keys.public_key())])
+            .await;
+        assert_eq!(events, vec![note.clone()]);
+
+        let events = store.query(vec![Filter::new().event(note.id)]).await;
+        assert_eq!(events, vec![reply.clone()]);
+
+        let events = store
+            .query(vec![Filter::new().pubkey(keys.public_key())])
+            .await;
+        assert_eq!(events, vec![reply.clone()]);
+
+        let events = store
+            .query(vec![Filter::new().kind(Kind::TextNote).limit(1)])
+            .await;
+        assert_eq!(events.len(), 1);
+
+        let events = store.query(vec![Filter::new()]).await;
+        assert_eq!(events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_replaceable() {
+        let keys = Keys::generate();
+        let store = MemoryStore::new();
+
+        let old = EventBuilder::set_metadata(Metadata::new().name("old"))
+            .to_unsigned_event(keys.public_key());
+        let created_at = Timestamp::from(1000);
+        let old = UnsignedEvent {
+            id: EventId::new(&old.pubkey, created_at, &old.kind, &old.tags, &old.content),
+            created_at,
+            ..old
+        }
+        .sign(&keys)
+        .unwrap();
+        let new = EventBuilder::set_metadata(Metadata::new().name("new"))
+            .to_event(&keys)
+            .unwrap();
+
+        assert!(store.insert(new.clone()).await);
+        // Older replaceable event is not stored
+        assert!(!store.insert(old.clone()).await);
+
+        let events = store.query(vec![Filter::new().kind(Kind::Metadata)]).await;
+        assert_eq!(events, vec![new]);
+
+        // Ephemeral events are stored as any other event
+        let ephemeral = EventBuilder::new(Kind::Ephemeral(20001), "", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert!(store.insert(ephemeral).await);
+        assert_eq!(store.len().await, 2);
+    }
+}
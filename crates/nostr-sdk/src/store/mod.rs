@@ -0,0 +1,8 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Event store
+
+mod memory;
+
+pub use self::memory::MemoryStore;
@@ -3,12 +3,15 @@
 
 //! Thread
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 #[cfg(feature = "blocking")]
 use std::time::Duration;
 
 use futures_util::Future;
 #[cfg(feature = "blocking")]
 use tokio::runtime::{Builder, Runtime};
+use tokio::sync::Notify;
 
 #[cfg(feature = "blocking")]
 fn new_current_thread() -> nostr::Result<Runtime> {
@@ -63,3 +66,53 @@ where
         Some(JoinHandle::Tokio(handle))
     }
 }
+
+/// Counter of the running tasks spawned with [`TaskTracker::spawn`]
+#[derive(Debug, Clone, Default)]
+pub struct TaskTracker {
+    count: Arc<AtomicUsize>,
+    notify: Arc<Notify>,
+}
+
+/// Decrease the counter when the task completes (or is dropped)
+struct TaskGuard(TaskTracker);
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.notify.notify_waiters();
+        }
+    }
+}
+
+impl TaskTracker {
+    /// Number of running tasks
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Spawn a tracked task
+    pub fn spawn<T>(&self, future: T) -> Option<JoinHandle<T::Output>>
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        let guard = TaskGuard(self.clone());
+        spawn(async move {
+            let _guard = guard;
+            future.await
+        })
+    }
+
+    /// Wait until all the tracked tasks are completed
+    pub async fn wait(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.count() == 0 {
+                break;
+            }
+            notified.await;
+        }
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::Hash;
+use nostr::secp256k1::XOnlyPublicKey;
+use nostr::serde_json::{json, Value};
+use nostr::{EventBuilder, EventId, Keys, Kind, Tag, Timestamp};
+
+/// Previous implementation: serialize the event data to an intermediate JSON `String` and then hash it
+fn event_id_from_string(
+    pubkey: &XOnlyPublicKey,
+    created_at: Timestamp,
+    kind: &Kind,
+    tags: &[Tag],
+    content: &str,
+) -> EventId {
+    let json: Value = json!([0, pubkey, created_at, kind, tags, content]);
+    let event_str: String = json.to_string();
+    EventId::from(Sha256Hash::hash(event_str.as_bytes()))
+}
+
+fn event_id(c: &mut Criterion) {
+    let keys = Keys::generate();
+    let tags: Vec<Tag> = (0..20)
+        .map(|i| Tag::Hashtag(format!("hashtag{i}")))
+        .chain([Tag::PubKey(keys.public_key(), None)])
+        .collect();
+    let event = EventBuilder::new_text_note("Lorem ipsum dolor sit amet. ".repeat(40), &tags)
+        .to_event(&keys)
+        .unwrap();
+
+    // Both paths must compute the same id
+    assert_eq!(
+        event_id_from_string(
+            &event.pubkey,
+            event.created_at,
+            &event.kind,
+            &event.tags,
+            &event.content
+        ),
+        event.id
+    );
+
+    let mut group = c.benchmark_group("EventId::new");
+    group.bench_function("intermediate string", |b| {
+        b.iter(|| {
+            event_id_from_string(
+                black_box(&event.pubkey),
+                black_box(event.created_at),
+                black_box(&event.kind),
+                black_box(&event.tags),
+                black_box(&event.content),
+            )
+        })
+    });
+    group.bench_function("streaming", |b| {
+        b.iter(|| {
+            EventId::new(
+                black_box(&event.pubkey),
+                black_box(event.created_at),
+                black_box(&event.kind),
+                black_box(&event.tags),
+                black_box(&event.content),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, event_id);
+criterion_main!(benches);
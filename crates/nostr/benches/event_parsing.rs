@@ -0,0 +1,79 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nostr::event::EventRef;
+use nostr::serde_json;
+use nostr::{Event, EventBuilder, Keys, Tag};
+
+/// Global allocator counting the allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Number of allocations made by `f`
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before: usize = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn event_parsing(c: &mut Criterion) {
+    let keys = Keys::generate();
+    let tags: Vec<Tag> = (0..20)
+        .map(|i| Tag::Hashtag(format!("hashtag{i}")))
+        .chain([Tag::PubKey(keys.public_key(), None)])
+        .collect();
+    let event = EventBuilder::new_text_note("Lorem ipsum dolor sit amet. ".repeat(40), &tags)
+        .to_event(&keys)
+        .unwrap();
+    let json: String = event.as_json();
+
+    let owned: usize = count_allocations(|| serde_json::from_str::<Event>(&json).unwrap());
+    let borrowed: usize = count_allocations(|| EventRef::from_json(&json).unwrap());
+    println!("Allocations per parse: Event {owned}, EventRef {borrowed}");
+    assert!(borrowed < owned);
+
+    // The signature verification is excluded: only the deserialization is compared
+    let mut group = c.benchmark_group("Event parsing");
+    group.bench_function("Event", |b| {
+        b.iter(|| serde_json::from_str::<Event>(black_box(&json)).unwrap())
+    });
+    group.bench_function("EventRef", |b| {
+        b.iter(|| EventRef::from_json(black_box(&json)).unwrap())
+    });
+    group.bench_function("EventRef into Event", |b| {
+        b.iter(|| {
+            EventRef::from_json(black_box(&json))
+                .unwrap()
+                .into_event_unverified()
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, event_parsing);
+criterion_main!(benches);
@@ -82,6 +82,9 @@ fn main() -> Result<()> {
                 RelayMessage::Auth { challenge } => {
                     println!("Got a auth challenge: {}", challenge);
                 }
+                RelayMessage::NegMsg { .. } | RelayMessage::NegErr { .. } => {
+                    println!("Got a negentropy message");
+                }
                 RelayMessage::Empty => {
                     println!("Empty message");
                 }
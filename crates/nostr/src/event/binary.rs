@@ -0,0 +1,107 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Binary serialization
+//!
+//! Compact internal representation, useful for caching events on disk.
+//! **It's not interoperable with other clients.**
+
+use bitcoin_hashes::Hash;
+use borsh::{BorshDeserialize, BorshSerialize};
+use secp256k1::schnorr::Signature;
+use secp256k1::XOnlyPublicKey;
+
+use super::{Error, Event, EventId, Kind, Tag};
+use crate::Timestamp;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct RawEvent {
+    id: [u8; 32],
+    pubkey: [u8; 32],
+    created_at: i64,
+    kind: u64,
+    tags: Vec<Vec<String>>,
+    content: String,
+    sig: [u8; 64],
+    ots: Option<String>,
+}
+
+impl Event {
+    /// Serialize [`Event`] to compact binary format
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let raw = RawEvent {
+            id: self.id.inner().to_byte_array(),
+            pubkey: self.pubkey.serialize(),
+            created_at: self.created_at.as_i64(),
+            kind: self.kind.as_u64(),
+            tags: self.tags.iter().map(|t| t.as_vec()).collect(),
+            content: self.content.clone(),
+            sig: *self.sig.as_ref(),
+            #[cfg(feature = "nip03")]
+            ots: self.ots.clone(),
+            #[cfg(not(feature = "nip03"))]
+            ots: None,
+        };
+        Ok(raw.try_to_vec()?)
+    }
+
+    /// Deserialize [`Event`] from compact binary format
+    ///
+    /// **The event is not verified**
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let raw = RawEvent::try_from_slice(bytes)?;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(raw.tags.len());
+        for tag in raw.tags.into_iter() {
+            tags.push(Tag::try_from(tag).map_err(|e| Error::Tag(Box::new(e)))?);
+        }
+
+        Ok(Self {
+            id: EventId::from_slice(&raw.id)?,
+            pubkey: XOnlyPublicKey::from_slice(&raw.pubkey)?,
+            created_at: Timestamp::from_i64(raw.created_at),
+            kind: Kind::from(raw.kind),
+            tags,
+            content: raw.content,
+            sig: Signature::from_slice(&raw.sig)?,
+            #[cfg(feature = "nip03")]
+            ots: raw.ots,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_binary_round_trip() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note(
+            "Binary serialization test",
+            &[Tag::Hashtag(String::from("nostr"))],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let bytes = event.to_bytes().unwrap();
+        assert_eq!(Event::from_bytes(&bytes).unwrap(), event);
+
+        // Binary form is more compact than JSON (raw bytes instead of hex for id, pubkey and sig)
+        assert!(bytes.len() < event.as_json().len());
+    }
+
+    #[test]
+    fn test_binary_negative_created_at() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("Before the epoch", &[])
+            .custom_created_at(Timestamp::from_i64(-3600))
+            .to_event(&keys)
+            .unwrap();
+
+        let event = Event::from_bytes(&event.to_bytes().unwrap()).unwrap();
+        assert_eq!(event.created_at.as_i64(), -3600);
+        assert!(event.verify().is_ok());
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Borrowed event
+
+use std::borrow::Cow;
+use std::fmt;
+
+use secp256k1::schnorr::Signature;
+use secp256k1::XOnlyPublicKey;
+use serde::de::{Deserializer, Error as DeError, Visitor};
+
+use super::{Error, Event, EventId, Kind, Tag};
+use crate::Timestamp;
+
+/// Borrowed [`Event`]
+///
+/// Deserialize the `content` and the `tags` without copying them when the input outlives the parse
+/// (strings with escape sequences are still allocated).
+/// Use [`EventRef::into_event`] to get an owned [`Event`] on demand.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EventRef<'a> {
+    /// Id
+    pub id: EventId,
+    /// Author
+    pub pubkey: XOnlyPublicKey,
+    /// Timestamp (seconds)
+    pub created_at: Timestamp,
+    /// Kind
+    pub kind: Kind,
+    /// Raw tags
+    #[serde(borrow, deserialize_with = "deserialize_tags")]
+    pub tags: Vec<Vec<Cow<'a, str>>>,
+    /// Content
+    #[serde(borrow)]
+    pub content: Cow<'a, str>,
+    /// Signature
+    pub sig: Signature,
+    /// OpenTimestamps Attestations
+    #[cfg(feature = "nip03")]
+    #[serde(default, borrow)]
+    pub ots: Option<Cow<'a, str>>,
+}
+
+impl<'a> EventRef<'a> {
+    /// Deserialize [`EventRef`] from json string
+    ///
+    /// **The event is not verified**, use [`EventRef::into_event`] to verify it.
+    pub fn from_json(json: &'a str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Convert into owned [`Event`] without verifying it
+    pub fn into_event_unverified(self) -> Result<Event, Error> {
+        let mut tags: Vec<Tag> = Vec::with_capacity(self.tags.len());
+        for tag in self.tags.into_iter() {
+            tags.push(Tag::try_from(tag).map_err(|e| Error::Tag(Box::new(e)))?);
+        }
+
+        Ok(Event {
+            id: self.id,
+            pubkey: self.pubkey,
+            created_at: self.created_at,
+            kind: self.kind,
+            tags,
+            content: self.content.into_owned(),
+            sig: self.sig,
+            #[cfg(feature = "nip03")]
+            ots: self.ots.map(|ots| ots.into_owned()),
+        })
+    }
+
+    /// Convert into owned and verified [`Event`]
+    pub fn into_event(self) -> Result<Event, Error> {
+        let event: Event = self.into_event_unverified()?;
+        event.verify()?;
+        Ok(event)
+    }
+}
+
+impl<'a> TryFrom<EventRef<'a>> for Event {
+    type Error = Error;
+
+    fn try_from(event: EventRef<'a>) -> Result<Self, Self::Error> {
+        event.into_event()
+    }
+}
+
+struct CowStr<'a>(Cow<'a, str>);
+
+impl<'de> serde::Deserialize<'de> for CowStr<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CowStrVisitor)
+    }
+}
+
+struct CowStrVisitor;
+
+impl<'de> Visitor<'de> for CowStrVisitor {
+    type Value = CowStr<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: DeError,
+    {
+        Ok(CowStr(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: DeError,
+    {
+        Ok(CowStr(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: DeError,
+    {
+        Ok(CowStr(Cow::Owned(v)))
+    }
+}
+
+fn deserialize_tags<'de, D>(deserializer: D) -> Result<Vec<Vec<Cow<'de, str>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let tags: Vec<Vec<CowStr<'de>>> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(tags
+        .into_iter()
+        .map(|tag| tag.into_iter().map(|s| s.0).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_EVENT: &str = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+
+    #[test]
+    fn test_event_ref_borrowed() {
+        let event = EventRef::from_json(SAMPLE_EVENT).unwrap();
+        assert!(matches!(event.content, Cow::Borrowed(_)));
+        assert!(matches!(event.tags[0][0], Cow::Borrowed("p")));
+
+        let owned: Event = event.into_event().unwrap();
+        assert_eq!(owned, Event::from_json(SAMPLE_EVENT).unwrap());
+    }
+
+    #[test]
+    fn test_event_ref_escaped_content() {
+        let json = r#"{"content":"line\nline","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":1,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[]}"#;
+        let event = EventRef::from_json(json).unwrap();
+        assert_eq!(event.content, "line\nline");
+        assert!(event.into_event().is_err());
+    }
+}
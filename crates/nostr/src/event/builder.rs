@@ -3,6 +3,8 @@
 
 //! Event builder
 
+use std::collections::BTreeMap;
+
 use secp256k1::{Message, XOnlyPublicKey};
 use serde_json::{json, Value};
 use url::Url;
@@ -16,7 +18,12 @@ use crate::nips::nip04;
 use crate::nips::nip13;
 #[cfg(feature = "nip46")]
 use crate::nips::nip46::Message as NostrConnectMessage;
-use crate::types::{ChannelId, Contact, Metadata, Timestamp};
+use crate::nips::nip51;
+#[cfg(feature = "nip59")]
+use crate::nips::nip59;
+use crate::nips::nip68::{self, PictureImage};
+use crate::nips::nip94::FileMetadata;
+use crate::types::{ChannelId, Contact, Metadata, RelayPermissions, Timestamp, UncheckedUrl};
 
 /// [`EventBuilder`] error
 #[derive(Debug, thiserror::Error)]
@@ -34,6 +41,13 @@ pub enum Error {
     #[cfg(feature = "nip04")]
     #[error(transparent)]
     NIP04(#[from] nip04::Error),
+    /// NIP59 error
+    #[cfg(feature = "nip59")]
+    #[error(transparent)]
+    NIP59(#[from] nip59::Error),
+    /// NIP68 error
+    #[error(transparent)]
+    NIP68(#[from] nip68::Error),
 }
 
 /// [`Event`] builder
@@ -42,6 +56,7 @@ pub struct EventBuilder {
     kind: Kind,
     tags: Vec<Tag>,
     content: String,
+    custom_created_at: Option<Timestamp>,
 }
 
 impl EventBuilder {
@@ -54,13 +69,24 @@ impl EventBuilder {
             kind,
             tags: tags.to_vec(),
             content: content.into(),
+            custom_created_at: None,
+        }
+    }
+
+    /// Set a custom `created_at` [`Timestamp`]
+    ///
+    /// By default, [`Timestamp::now`] is used when the event is built.
+    pub fn custom_created_at(self, created_at: Timestamp) -> Self {
+        Self {
+            custom_created_at: Some(created_at),
+            ..self
         }
     }
 
     /// Build [`Event`]
     pub fn to_event(self, keys: &Keys) -> Result<Event, Error> {
         let pubkey: XOnlyPublicKey = keys.public_key();
-        let created_at: Timestamp = Timestamp::now();
+        let created_at: Timestamp = self.custom_created_at.unwrap_or_else(Timestamp::now);
 
         let id = EventId::new(&pubkey, created_at, &self.kind, &self.tags, &self.content);
         let message = Message::from_slice(id.as_bytes())?;
@@ -79,6 +105,10 @@ impl EventBuilder {
     }
 
     /// Build POW [`Event`]
+    ///
+    /// Works with any [`Kind`]: the `nonce` tag is mined until the [`EventId`] has at least `difficulty` leading zero bits.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/13.md>
     pub fn to_pow_event(self, keys: &Keys, difficulty: u8) -> Result<Event, Error> {
         #[cfg(target_arch = "wasm32")]
         use instant::Instant;
@@ -97,7 +127,7 @@ impl EventBuilder {
 
             tags.push(Tag::POW { nonce, difficulty });
 
-            let created_at: Timestamp = Timestamp::now();
+            let created_at: Timestamp = self.custom_created_at.unwrap_or_else(Timestamp::now);
             let id = EventId::new(&pubkey, created_at, &self.kind, &tags, &self.content);
 
             if nip13::get_leading_zero_bits(id.inner()) >= difficulty {
@@ -129,16 +159,8 @@ impl EventBuilder {
 
     /// Build [`UnsignedEvent`]
     pub fn to_unsigned_event(self, pubkey: XOnlyPublicKey) -> UnsignedEvent {
-        let created_at: Timestamp = Timestamp::now();
-        let id = EventId::new(&pubkey, created_at, &self.kind, &self.tags, &self.content);
-        UnsignedEvent {
-            id,
-            pubkey,
-            created_at,
-            kind: self.kind,
-            tags: self.tags,
-            content: self.content,
-        }
+        let created_at: Timestamp = self.custom_created_at.unwrap_or_else(Timestamp::now);
+        UnsignedEvent::new(pubkey, created_at, self.kind, self.tags, self.content)
     }
 }
 
@@ -216,6 +238,16 @@ impl EventBuilder {
 
     /// Set contact list
     pub fn set_contact_list(list: Vec<Contact>) -> Self {
+        Self::set_contact_list_with_relays(list, BTreeMap::new())
+    }
+
+    /// Set contact list with the relay map (JSON content)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/02.md>
+    pub fn set_contact_list_with_relays(
+        list: Vec<Contact>,
+        relays: BTreeMap<UncheckedUrl, RelayPermissions>,
+    ) -> Self {
         let tags: Vec<Tag> = list
             .iter()
             .map(|contact| Tag::ContactList {
@@ -225,26 +257,146 @@ impl EventBuilder {
             })
             .collect();
 
-        Self::new(Kind::ContactList, "", &tags)
+        let content: String = if relays.is_empty() {
+            String::new()
+        } else {
+            json!(relays).to_string()
+        };
+
+        Self::new(Kind::ContactList, content, &tags)
+    }
+
+    /// Set interests list (kind `10015`)
+    ///
+    /// The hashtags are normalized (see [`nip51::normalize_hashtag`]) and deduplicated.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub fn interests<I, S>(hashtags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut tags: Vec<Tag> = Vec::new();
+        for hashtag in hashtags.into_iter() {
+            let hashtag: String = nip51::normalize_hashtag(hashtag);
+            let tag = Tag::Hashtag(hashtag.clone());
+            if !hashtag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        Self::new(Kind::Interests, "", &tags)
     }
 
     /// Create encrypted direct msg event
+    ///
+    /// If `reply_to` is set, an `e` tag (with the optional `relay_url` hint) referencing the replied message is added.
     #[cfg(feature = "nip04")]
     pub fn new_encrypted_direct_msg<S>(
         sender_keys: &Keys,
         receiver_pubkey: XOnlyPublicKey,
         content: S,
+        reply_to: Option<EventId>,
+        relay_url: Option<UncheckedUrl>,
+    ) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let mut tags: Vec<Tag> = Vec::new();
+        if let Some(reply_to) = reply_to {
+            tags.push(Tag::Event(
+                reply_to,
+                relay_url.map(|url| url.to_string()),
+                None,
+            ));
+        }
+        Self::new_encrypted_direct_msg_with_tags(sender_keys, receiver_pubkey, content, &tags)
+    }
+
+    /// Create encrypted direct msg event with extra tags
+    ///
+    /// The `tags` (i.e. `e`, `subject` or additional `p` tags) are appended after the mandatory receiver `p` tag.
+    #[cfg(feature = "nip04")]
+    pub fn new_encrypted_direct_msg_with_tags<S>(
+        sender_keys: &Keys,
+        receiver_pubkey: XOnlyPublicKey,
+        content: S,
+        tags: &[Tag],
     ) -> Result<Self, Error>
     where
         S: Into<String>,
     {
+        let mut all_tags: Vec<Tag> = Vec::with_capacity(tags.len() + 1);
+        all_tags.push(Tag::PubKey(receiver_pubkey, None));
+        all_tags.extend_from_slice(tags);
+
         Ok(Self::new(
             Kind::EncryptedDirectMessage,
             nip04::encrypt(&sender_keys.secret_key()?, &receiver_pubkey, content.into())?,
-            &[Tag::PubKey(receiver_pubkey, None)],
+            &all_tags,
         ))
     }
 
+    /// Private direct message rumor (kind `14`)
+    ///
+    /// Must not be signed: see [`EventBuilder::private_msg`].
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    pub fn private_msg_rumor<S>(
+        receiver_pubkey: XOnlyPublicKey,
+        content: S,
+        reply_to: Option<EventId>,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut tags: Vec<Tag> = vec![Tag::PubKey(receiver_pubkey, None)];
+        if let Some(reply_to) = reply_to {
+            tags.push(Tag::Event(reply_to, None, None));
+        }
+        Self::new(Kind::PrivateDirectMessage, content, &tags)
+    }
+
+    /// Private direct message
+    ///
+    /// Build the kind `14` rumor and gift-wrap it for `receiver_pubkey`.
+    /// Return the gift wrap, signed by an ephemeral key.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    #[cfg(feature = "nip59")]
+    pub fn private_msg<S>(
+        sender_keys: &Keys,
+        receiver_pubkey: XOnlyPublicKey,
+        content: S,
+        reply_to: Option<EventId>,
+    ) -> Result<Event, Error>
+    where
+        S: Into<String>,
+    {
+        let rumor: UnsignedEvent = Self::private_msg_rumor(receiver_pubkey, content, reply_to)
+            .to_unsigned_event(sender_keys.public_key());
+        Self::gift_wrap(sender_keys, receiver_pubkey, rumor)
+    }
+
+    /// Gift wrap
+    ///
+    /// Seal the `rumor` (kind `13`) and wrap it (kind `1059`) for `receiver_pubkey`.
+    /// The gift wrap is signed by a fresh ephemeral key and both the seal and the gift wrap
+    /// have a randomized `created_at`.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+    #[cfg(feature = "nip59")]
+    pub fn gift_wrap(
+        sender_keys: &Keys,
+        receiver_pubkey: XOnlyPublicKey,
+        rumor: UnsignedEvent,
+    ) -> Result<Event, Error> {
+        Ok(nip59::make_gift_wrap(
+            sender_keys,
+            &receiver_pubkey,
+            &rumor,
+        )?)
+    }
+
     /// Repost event
     pub fn repost(event_id: EventId, public_key: XOnlyPublicKey) -> Self {
         Self::new(
@@ -286,6 +438,30 @@ impl EventBuilder {
         )
     }
 
+    /// Add reaction with a custom emoji (`:shortcode:` content and `emoji` tag) to an event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/30.md>
+    pub fn custom_emoji_reaction<S>(
+        event_id: EventId,
+        public_key: XOnlyPublicKey,
+        shortcode: S,
+        url: Url,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let shortcode: String = shortcode.into();
+        Self::new(
+            Kind::Reaction,
+            format!(":{shortcode}:"),
+            &[
+                Tag::Event(event_id, None, None),
+                Tag::PubKey(public_key, None),
+                Tag::Emoji { shortcode, url },
+            ],
+        )
+    }
+
     /// Create new channel
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -330,6 +506,37 @@ impl EventBuilder {
         )
     }
 
+    /// New channel message in reply to another message of the channel
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub fn new_channel_msg_reply<S>(
+        channel_id: ChannelId,
+        relay_url: Url,
+        reply_to: &Event,
+        content: S,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(
+            Kind::ChannelMessage,
+            content,
+            &[
+                Tag::Event(
+                    channel_id.into(),
+                    Some(relay_url.to_string()),
+                    Some(Marker::Root),
+                ),
+                Tag::Event(
+                    reply_to.id,
+                    Some(relay_url.to_string()),
+                    Some(Marker::Reply),
+                ),
+                Tag::PubKey(reply_to.pubkey, Some(relay_url.to_string())),
+            ],
+        )
+    }
+
     /// Hide message
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -409,6 +616,55 @@ impl EventBuilder {
         Self::new(Kind::Reporting, content, tags)
     }
 
+    /// Create label event
+    ///
+    /// `targets` are the labeled events, public keys, relays or topics (`e`, `p`, `a`, `r` and `t` tags).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/32.md>
+    pub fn label<S>(namespace: S, label: S, targets: &[Tag]) -> Self
+    where
+        S: Into<String>,
+    {
+        let namespace: String = namespace.into();
+        let mut tags: Vec<Tag> = vec![
+            Tag::LabelNamespace(namespace.clone()),
+            Tag::Label {
+                label: label.into(),
+                namespace: Some(namespace),
+            },
+        ];
+        tags.extend_from_slice(targets);
+        Self::new(Kind::Label, "", &tags)
+    }
+
+    /// Create picture-first post
+    ///
+    /// At least one image is required.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/68.md>
+    pub fn picture_post<S>(title: S, description: S, images: &[PictureImage]) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        if images.is_empty() {
+            return Err(Error::NIP68(nip68::Error::NoImages));
+        }
+
+        let mut tags: Vec<Tag> = vec![Tag::Title(title.into())];
+        tags.extend(images.iter().map(|image| image.to_tag()));
+        Ok(Self::new(Kind::Picture, description, &tags))
+    }
+
+    /// Create file metadata event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/94.md>
+    pub fn file_metadata<S>(description: S, metadata: FileMetadata) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(Kind::FileMetadata, description, &metadata.to_tags())
+    }
+
     /// Create zap event
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/57.md>
@@ -442,7 +698,7 @@ impl EventBuilder {
             tags.push(tag);
         }
 
-        Self::new(Kind::Zap, "", &tags)
+        Self::new(Kind::ZapReceipt, "", &tags)
     }
 }
 
@@ -452,7 +708,12 @@ mod tests {
 
     use secp256k1::SecretKey;
 
-    use crate::{Event, EventBuilder, Keys, Result};
+    use crate::nips::nip13;
+    use crate::{
+        ChannelId, Event, EventBuilder, Keys, Kind, Metadata, Result, Tag, Timestamp, Url,
+    };
+    #[cfg(feature = "nip04")]
+    use crate::{EventId, UncheckedUrl};
 
     #[test]
     fn round_trip() -> Result<()> {
@@ -485,9 +746,202 @@ mod tests {
             &sender_keys,
             receiver_keys.public_key(),
             content,
+            None,
+            None,
         )?
         .to_event(&sender_keys)?;
 
         Ok(event.verify()?)
     }
+
+    #[test]
+    #[cfg(feature = "nip04")]
+    fn test_encrypted_direct_msg_reply() -> Result<()> {
+        let sender_keys = Keys::generate();
+        let receiver_keys = Keys::generate();
+        let reply_to =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")?;
+
+        let event = EventBuilder::new_encrypted_direct_msg(
+            &sender_keys,
+            receiver_keys.public_key(),
+            "Reply",
+            Some(reply_to),
+            Some(UncheckedUrl::from("wss://relay.damus.io")),
+        )?
+        .to_event(&sender_keys)?;
+
+        assert_eq!(
+            event.tags,
+            vec![
+                Tag::PubKey(receiver_keys.public_key(), None),
+                Tag::Event(reply_to, Some(String::from("wss://relay.damus.io")), None),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "nip04")]
+    fn test_encrypted_direct_msg_with_tags() -> Result<()> {
+        let sender_keys = Keys::generate();
+        let receiver_keys = Keys::generate();
+        let parent = EventBuilder::new_encrypted_direct_msg(
+            &receiver_keys,
+            sender_keys.public_key(),
+            "Hi",
+            None,
+            None,
+        )?
+        .to_event(&receiver_keys)?;
+
+        let tags = vec![
+            Tag::Event(parent.id, None, None),
+            Tag::Subject(String::from("Greetings")),
+        ];
+        let event = EventBuilder::new_encrypted_direct_msg_with_tags(
+            &sender_keys,
+            receiver_keys.public_key(),
+            "Hello",
+            &tags,
+        )?
+        .to_event(&sender_keys)?;
+
+        assert_eq!(
+            event.tags,
+            vec![
+                Tag::PubKey(receiver_keys.public_key(), None),
+                Tag::Event(parent.id, None, None),
+                Tag::Subject(String::from("Greetings")),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "nip59")]
+    fn test_private_msg() -> Result<()> {
+        use crate::nips::nip17;
+
+        let sender_keys = Keys::generate();
+        let receiver_keys = Keys::generate();
+        let reply_to = EventBuilder::new_text_note("Hi", &[])
+            .to_event(&receiver_keys)?
+            .id;
+
+        let gift_wrap = EventBuilder::private_msg(
+            &sender_keys,
+            receiver_keys.public_key(),
+            "Saturn, bringer of old age",
+            Some(reply_to),
+        )?;
+        assert_eq!(gift_wrap.kind, Kind::GiftWrap);
+        assert_ne!(gift_wrap.pubkey, sender_keys.public_key());
+
+        let msg = nip17::unwrap_private_msg(&receiver_keys, &gift_wrap)?;
+        assert_eq!(msg.sender, sender_keys.public_key());
+        assert_eq!(msg.receivers, vec![receiver_keys.public_key()]);
+        assert_eq!(msg.content, "Saturn, bringer of old age");
+        assert_eq!(msg.reply_to, Some(reply_to));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_channel_msg_reply() -> Result<()> {
+        let keys = Keys::generate();
+        let relay_url = Url::parse("wss://relay.damus.io")?;
+        let channel = EventBuilder::new_channel(Metadata::new().name("channel")).to_event(&keys)?;
+        let channel_id = ChannelId::from(channel.id);
+
+        let parent = EventBuilder::new_channel_msg(channel_id.clone(), relay_url.clone(), "Hi")
+            .to_event(&Keys::generate())?;
+        let reply =
+            EventBuilder::new_channel_msg_reply(channel_id.clone(), relay_url, &parent, "Hello")
+                .to_event(&keys)?;
+
+        assert_eq!(channel.channel_id(), Some(channel_id.clone()));
+        assert_eq!(parent.channel_id(), Some(channel_id.clone()));
+        assert_eq!(parent.channel_reply_to(), None);
+        assert_eq!(reply.channel_id(), Some(channel_id));
+        assert_eq!(reply.channel_reply_to(), Some(parent.id));
+        assert!(reply.tags.contains(&Tag::PubKey(
+            parent.pubkey,
+            Some(String::from("wss://relay.damus.io/"))
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_emoji_reaction() -> Result<()> {
+        let keys = Keys::generate();
+        let note = EventBuilder::new_text_note("hello", &[]).to_event(&keys)?;
+
+        let url = Url::parse("https://example.com/soapbox.png")?;
+        let event =
+            EventBuilder::custom_emoji_reaction(note.id, note.pubkey, "soapbox", url.clone())
+                .to_event(&keys)?;
+
+        assert_eq!(event.content, ":soapbox:");
+        assert_eq!(
+            event.custom_emoji(),
+            Some((String::from("soapbox"), url.clone()))
+        );
+
+        let event = Event::from_json(event.as_json())?;
+        assert_eq!(event.custom_emoji(), Some((String::from("soapbox"), url)));
+
+        // Standard reaction
+        let event = EventBuilder::new_reaction(note.id, note.pubkey, "+").to_event(&keys)?;
+        assert_eq!(event.custom_emoji(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_created_at() -> Result<()> {
+        let keys = Keys::generate();
+
+        let event1 = EventBuilder::new_text_note("hello", &[])
+            .custom_created_at(Timestamp::from(1000))
+            .to_event(&keys)?;
+        let event2 = EventBuilder::new_text_note("hello", &[])
+            .custom_created_at(Timestamp::from(2000))
+            .to_event(&keys)?;
+
+        assert_eq!(event1.created_at, Timestamp::from(1000));
+        assert_eq!(event2.created_at, Timestamp::from(2000));
+        assert_ne!(event1.id, event2.id);
+        assert!(event1.is_valid().is_ok());
+
+        let unsigned = EventBuilder::new_text_note("hello", &[])
+            .custom_created_at(Timestamp::from(1000))
+            .to_unsigned_event(keys.public_key());
+        assert_eq!(unsigned.id, event1.id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pow_reaction() -> Result<()> {
+        let keys = Keys::generate();
+        let note = EventBuilder::new_text_note("hello", &[]).to_event(&keys)?;
+
+        let difficulty: u8 = 8;
+        let event = EventBuilder::new_reaction(note.id, note.pubkey, "+")
+            .to_pow_event(&keys, difficulty)?;
+
+        assert_eq!(event.kind, Kind::Reaction);
+        assert!(nip13::get_leading_zero_bits(event.id.inner()) >= difficulty);
+        assert!(event
+            .tags
+            .iter()
+            .any(|t| matches!(t, Tag::POW { difficulty: d, .. } if *d == difficulty)));
+        assert!(event.is_valid().is_ok());
+
+        Ok(())
+    }
 }
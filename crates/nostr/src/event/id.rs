@@ -9,7 +9,8 @@ use std::str::FromStr;
 use bitcoin_hashes::sha256::Hash as Sha256Hash;
 use bitcoin_hashes::Hash;
 use secp256k1::XOnlyPublicKey;
-use serde_json::{json, Value};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer};
 
 use super::{Kind, Tag};
 use crate::Timestamp;
@@ -30,7 +31,7 @@ pub enum Error {
 /// 32-bytes lowercase hex-encoded sha256 of the the serialized event data
 ///
 /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub struct EventId(Sha256Hash);
 
 impl EventId {
@@ -42,9 +43,12 @@ impl EventId {
         tags: &[Tag],
         content: &str,
     ) -> Self {
-        let json: Value = json!([0, pubkey, created_at, kind, tags, content]);
-        let event_str: String = json.to_string();
-        Self(Sha256Hash::hash(event_str.as_bytes()))
+        // Serialize the `[0, pubkey, created_at, kind, tags, content]` array
+        // directly into the hash engine, without building an intermediate `String`
+        let mut engine = Sha256Hash::engine();
+        serde_json::to_writer(&mut engine, &(0, pubkey, created_at, kind, tags, content))
+            .expect("Writing into hash engine never fails");
+        Self(Sha256Hash::from_engine(engine))
     }
 
     /// [`EventId`] hex string
@@ -76,6 +80,46 @@ impl EventId {
     }
 }
 
+impl<'de> Deserialize<'de> for EventId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            return Ok(Self(Sha256Hash::deserialize(deserializer)?));
+        }
+
+        let hex: String = String::deserialize(deserializer)?;
+        if let Some(e) = hex_error(&hex, 64) {
+            return Err(D::Error::custom(format!("invalid event id: {e}")));
+        }
+        Self::from_hex(hex).map_err(D::Error::custom)
+    }
+}
+
+/// Serialize the `[0, pubkey, created_at, kind, tags, content]` array hashed by [`EventId::new`]
+pub(crate) fn canonical_json(
+    pubkey: &XOnlyPublicKey,
+    created_at: Timestamp,
+    kind: &Kind,
+    tags: &[Tag],
+    content: &str,
+) -> String {
+    serde_json::to_string(&(0, pubkey, created_at, kind, tags, content))
+        .expect("Serializing the canonical array never fails")
+}
+
+/// Describe why `hex` isn't a valid hex string of `len` chars
+pub(crate) fn hex_error(hex: &str, len: usize) -> Option<String> {
+    if let Some((pos, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Some(format!("invalid hex char '{c}' at position {pos}"));
+    }
+    if hex.len() != len {
+        return Some(format!("expected {len} hex chars, found {}", hex.len()));
+    }
+    None
+}
+
 impl FromStr for EventId {
     type Err = Error;
     fn from_str(hex: &str) -> Result<Self, Self::Err> {
@@ -106,3 +150,43 @@ impl From<EventId> for String {
         event_id.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_event_id_canonical() {
+        let pubkey = XOnlyPublicKey::from_str(
+            "f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785",
+        )
+        .unwrap();
+        let tags = vec![Tag::PubKey(
+            XOnlyPublicKey::from_str(
+                "13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d",
+            )
+            .unwrap(),
+            None,
+        )];
+        let content = "uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==";
+        let created_at = Timestamp::from(1640839235);
+        let kind = Kind::EncryptedDirectMessage;
+
+        let id = EventId::new(&pubkey, created_at, &kind, &tags, content);
+        assert_eq!(
+            id.to_hex(),
+            "2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45"
+        );
+
+        // Same result of the intermediate JSON string
+        let content = "Quotes \" and \\ backslashes\n, unicode ✓";
+        let json: Value = json!([0, pubkey, created_at, kind, tags, content]);
+        let expected = EventId(Sha256Hash::hash(json.to_string().as_bytes()));
+        assert_eq!(
+            EventId::new(&pubkey, created_at, &kind, &tags, content),
+            expected
+        );
+    }
+}
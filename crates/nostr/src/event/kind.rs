@@ -25,8 +25,14 @@ pub enum Kind {
     EncryptedDirectMessage,
     /// Event Deletion (NIP09)
     EventDeletion,
+    /// Seal (NIP59)
+    Seal,
+    /// Private Direct Message (NIP17)
+    PrivateDirectMessage,
     /// Repost (NIP18)
     Repost,
+    /// Picture-first post (NIP68)
+    Picture,
     /// Reaction (NIP25)
     Reaction,
     /// Channel Creation (NIP28)
@@ -49,20 +55,30 @@ pub enum Kind {
     PublicChatReserved48,
     /// Public Chat Reserved (NIP28)
     PublicChatReserved49,
+    /// Gift Wrap (NIP59)
+    GiftWrap,
+    /// File Metadata (NIP94)
+    FileMetadata,
     /// Reporting (NIP56)
     Reporting,
+    /// Label (NIP32)
+    Label,
     /// Zap Request (NIP57)
     ZapRequest,
-    /// Zap (NIP57)
-    Zap,
+    /// Zap Receipt (NIP57)
+    ZapReceipt,
     /// Client Authentication (NIP42)
     Authentication,
     /// Nostr Connect (NIP46)
     NostrConnect,
     /// Long-form Text Note (NIP23)
     LongFormTextNote,
+    /// Mute List (NIP51)
+    MuteList,
     /// Relay List Metadata (NIP65)
     RelayList,
+    /// Interests (NIP51)
+    Interests,
     /// Replacabe event (must be between 10000 and <20000)
     Replaceable(u16),
     /// Ephemeral event (must be between 20000 and <30000)
@@ -74,6 +90,11 @@ pub enum Kind {
 }
 
 impl Kind {
+    /// Zap Receipt (NIP57)
+    #[deprecated(note = "use Kind::ZapReceipt")]
+    #[allow(non_upper_case_globals)]
+    pub const Zap: Kind = Kind::ZapReceipt;
+
     /// Get [`Kind`] as `u32`
     pub fn as_u32(&self) -> u32 {
         self.as_u64() as u32
@@ -83,6 +104,66 @@ impl Kind {
     pub fn as_u64(&self) -> u64 {
         (*self).into()
     }
+
+    /// Check if [`Kind`] is replaceable (`0`, `3` or between `10000` and `<20000`)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/16.md>
+    pub fn is_replaceable(&self) -> bool {
+        let kind: u64 = self.as_u64();
+        kind == 0 || kind == 3 || (10_000..20_000).contains(&kind)
+    }
+
+    /// Check if [`Kind`] is ephemeral (between `20000` and `<30000`)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/16.md>
+    pub fn is_ephemeral(&self) -> bool {
+        (20_000..30_000).contains(&self.as_u64())
+    }
+
+    /// Check if [`Kind`] is parameterized replaceable (between `30000` and `<40000`)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/33.md>
+    pub fn is_parameterized_replaceable(&self) -> bool {
+        (30_000..40_000).contains(&self.as_u64())
+    }
+
+    /// Get human readable name of known [`Kind`]s
+    ///
+    /// Return `None` for unknown kinds.
+    pub fn name(&self) -> Option<&'static str> {
+        match self.as_u64() {
+            0 => Some("Metadata"),
+            1 => Some("Text Note"),
+            2 => Some("Recommend Relay"),
+            3 => Some("Contacts"),
+            4 => Some("Encrypted Direct Message"),
+            5 => Some("Event Deletion"),
+            6 => Some("Repost"),
+            7 => Some("Reaction"),
+            13 => Some("Seal"),
+            14 => Some("Private Direct Message"),
+            20 => Some("Picture"),
+            40 => Some("Channel Creation"),
+            41 => Some("Channel Metadata"),
+            42 => Some("Channel Message"),
+            43 => Some("Channel Hide Message"),
+            44 => Some("Channel Mute User"),
+            45..=49 => Some("Public Chat Reserved"),
+            1059 => Some("Gift Wrap"),
+            1063 => Some("File Metadata"),
+            1984 => Some("Reporting"),
+            1985 => Some("Label"),
+            9734 => Some("Zap Request"),
+            9735 => Some("Zap Receipt"),
+            10000 => Some("Mute List"),
+            10002 => Some("Relay List Metadata"),
+            10015 => Some("Interests"),
+            22242 => Some("Client Authentication"),
+            24133 => Some("Nostr Connect"),
+            30023 => Some("Long-form Content"),
+            _ => None,
+        }
+    }
 }
 
 impl From<u64> for Kind {
@@ -96,6 +177,9 @@ impl From<u64> for Kind {
             5 => Self::EventDeletion,
             6 => Self::Repost,
             7 => Self::Reaction,
+            13 => Self::Seal,
+            14 => Self::PrivateDirectMessage,
+            20 => Self::Picture,
             40 => Self::ChannelCreation,
             41 => Self::ChannelMetadata,
             42 => Self::ChannelMessage,
@@ -106,10 +190,15 @@ impl From<u64> for Kind {
             47 => Self::PublicChatReserved47,
             48 => Self::PublicChatReserved48,
             49 => Self::PublicChatReserved49,
+            1059 => Self::GiftWrap,
+            1063 => Self::FileMetadata,
             1984 => Self::Reporting,
+            1985 => Self::Label,
             9734 => Self::ZapRequest,
-            9735 => Self::Zap,
+            9735 => Self::ZapReceipt,
+            10000 => Self::MuteList,
             10002 => Self::RelayList,
+            10015 => Self::Interests,
             22242 => Self::Authentication,
             24133 => Self::NostrConnect,
             30023 => Self::LongFormTextNote,
@@ -132,6 +221,9 @@ impl From<Kind> for u64 {
             Kind::EventDeletion => 5,
             Kind::Repost => 6,
             Kind::Reaction => 7,
+            Kind::Seal => 13,
+            Kind::PrivateDirectMessage => 14,
+            Kind::Picture => 20,
             Kind::ChannelCreation => 40,
             Kind::ChannelMetadata => 41,
             Kind::ChannelMessage => 42,
@@ -142,10 +234,15 @@ impl From<Kind> for u64 {
             Kind::PublicChatReserved47 => 47,
             Kind::PublicChatReserved48 => 48,
             Kind::PublicChatReserved49 => 49,
+            Kind::GiftWrap => 1059,
+            Kind::FileMetadata => 1063,
             Kind::Reporting => 1984,
+            Kind::Label => 1985,
             Kind::ZapRequest => 9734,
-            Kind::Zap => 9735,
+            Kind::ZapReceipt => 9735,
+            Kind::MuteList => 10000,
             Kind::RelayList => 10002,
+            Kind::Interests => 10015,
             Kind::Authentication => 22242,
             Kind::NostrConnect => 24133,
             Kind::LongFormTextNote => 30023,
@@ -223,4 +320,60 @@ mod tests {
         assert_ne!(Kind::Authentication, Kind::EncryptedDirectMessage);
         assert_ne!(Kind::TextNote, Kind::Custom(2));
     }
+
+    #[test]
+    fn test_kind_classification() {
+        assert!(Kind::Metadata.is_replaceable());
+        assert!(Kind::ContactList.is_replaceable());
+        assert!(Kind::RelayList.is_replaceable());
+        assert!(!Kind::TextNote.is_replaceable());
+        assert!(Kind::Authentication.is_ephemeral());
+        assert!(Kind::Custom(20001).is_ephemeral());
+        assert!(Kind::LongFormTextNote.is_parameterized_replaceable());
+        assert!(!Kind::LongFormTextNote.is_replaceable());
+    }
+
+    #[test]
+    fn test_kind_name() {
+        assert_eq!(Kind::TextNote.name(), Some("Text Note"));
+        assert_eq!(Kind::LongFormTextNote.name(), Some("Long-form Content"));
+        assert_eq!(Kind::Custom(7).name(), Some("Reaction"));
+        assert_eq!(Kind::ZapRequest.name(), Some("Zap Request"));
+        assert_eq!(Kind::Custom(123).name(), None);
+        assert_eq!(Kind::Ephemeral(20100).name(), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_zap_alias() {
+        assert_eq!(Kind::Zap, Kind::ZapReceipt);
+        assert_eq!(Kind::Zap.as_u64(), 9735);
+    }
+
+    #[test]
+    fn test_named_kinds() {
+        let kinds = [
+            (Kind::ZapRequest, 9734),
+            (Kind::ZapReceipt, 9735),
+            (Kind::MuteList, 10000),
+            (Kind::RelayList, 10002),
+            (Kind::Authentication, 22242),
+            (Kind::NostrConnect, 24133),
+            (Kind::LongFormTextNote, 30023),
+        ];
+        for (kind, num) in kinds {
+            assert_eq!(kind.as_u64(), num);
+            assert_eq!(serde_json::to_string(&kind).unwrap(), num.to_string());
+
+            // Deserialize to the named variant, not to `Custom` or `Replaceable`
+            let deserialized: Kind = serde_json::from_str(&num.to_string()).unwrap();
+            assert_eq!(
+                std::mem::discriminant(&deserialized),
+                std::mem::discriminant(&kind)
+            );
+        }
+
+        let custom: Kind = serde_json::from_str("9999").unwrap();
+        assert!(matches!(custom, Kind::Custom(9999)));
+    }
 }
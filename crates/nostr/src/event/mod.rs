@@ -4,24 +4,40 @@
 
 //! Event
 
+use std::cmp::Ordering;
 use std::str::FromStr;
+use std::time::Duration;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use secp256k1::schnorr::Signature;
-use secp256k1::{Message, XOnlyPublicKey};
+use secp256k1::{Message, Secp256k1, Verification, XOnlyPublicKey};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 
+#[cfg(feature = "borsh")]
+mod binary;
+pub mod borrowed;
 pub mod builder;
 pub mod id;
 pub mod kind;
 pub mod tag;
 pub mod unsigned;
+pub mod verified;
 
+pub use self::borrowed::EventRef;
 pub use self::builder::EventBuilder;
 pub use self::id::EventId;
 pub use self::kind::Kind;
 pub use self::tag::{Marker, Tag, TagKind};
 pub use self::unsigned::UnsignedEvent;
-use crate::{Timestamp, SECP256K1};
+pub use self::verified::VerifiedEvent;
+#[cfg(feature = "nip04")]
+use crate::nips::nip04;
+#[cfg(feature = "nip04")]
+use crate::Keys;
+use crate::{ChannelId, Timestamp, Url, SECP256K1};
 
 /// [`Event`] error
 #[derive(Debug, thiserror::Error)]
@@ -29,6 +45,9 @@ pub enum Error {
     /// Invalid signature
     #[error("invalid signature")]
     InvalidSignature,
+    /// Invalid event id
+    #[error("invalid event id")]
+    InvalidId,
     /// Error serializing or deserializing JSON data
     #[error(transparent)]
     Json(#[from] serde_json::Error),
@@ -38,10 +57,48 @@ pub enum Error {
     /// Hex decoding error
     #[error(transparent)]
     Hex(#[from] bitcoin_hashes::hex::Error),
+    /// Tag error
+    #[error(transparent)]
+    Tag(Box<tag::Error>),
+    /// Event Id error
+    #[error(transparent)]
+    EventId(#[from] id::Error),
+    /// Binary serialization error
+    #[cfg(feature = "borsh")]
+    #[error(transparent)]
+    Binary(#[from] std::io::Error),
     /// OpenTimestamps error
     #[cfg(feature = "nip03")]
     #[error(transparent)]
     OpenTimestamps(#[from] nostr_ots::Error),
+    /// Keys error
+    #[error(transparent)]
+    Keys(#[from] crate::key::Error),
+    /// NIP04 error
+    #[cfg(feature = "nip04")]
+    #[error(transparent)]
+    NIP04(#[from] nip04::Error),
+    /// Not an encrypted direct message
+    #[error("not an encrypted direct message")]
+    NotEncryptedDirectMessage,
+    /// Keys are not the sender or the recipient of the message
+    #[error("keys are not the sender or the recipient of the message")]
+    NotParty,
+}
+
+/// Tags needed to render an [`Event`]
+///
+/// See [`Event::render_context`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderContext {
+    /// Subject (NIP14)
+    pub subject: Option<String>,
+    /// Content warning reason (NIP36), empty if the warning has no reason
+    pub content_warning: Option<String>,
+    /// Alt text (NIP31)
+    pub alt: Option<String>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
 }
 
 /// [`Event`] struct
@@ -50,6 +107,7 @@ pub struct Event {
     /// Id
     pub id: EventId,
     /// Author
+    #[serde(deserialize_with = "deserialize_pubkey")]
     pub pubkey: XOnlyPublicKey,
     /// Timestamp (seconds)
     pub created_at: Timestamp,
@@ -67,20 +125,114 @@ pub struct Event {
     pub ots: Option<String>,
 }
 
+/// Deserialize the `pubkey` with an error explaining why the hex is malformed
+fn deserialize_pubkey<'de, D>(deserializer: D) -> Result<XOnlyPublicKey, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return XOnlyPublicKey::deserialize(deserializer);
+    }
+
+    let hex: String = String::deserialize(deserializer)?;
+    if let Some(e) = id::hex_error(&hex, 64) {
+        return Err(D::Error::custom(format!("invalid pubkey: {e}")));
+    }
+    XOnlyPublicKey::from_str(&hex).map_err(|e| D::Error::custom(format!("invalid pubkey: {e}")))
+}
+
 impl Event {
     /// Verify event
     pub fn verify(&self) -> Result<(), Error> {
-        let id = EventId::new(
+        let id = self.recompute_id();
+        let message = Message::from_slice(id.as_bytes())?;
+        SECP256K1
+            .verify_schnorr(&self.sig, &message, &self.pubkey)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Recompute the [`EventId`] from the event fields
+    ///
+    /// Useful to compare the expected and the stored id when they diverge.
+    pub fn recompute_id(&self) -> EventId {
+        EventId::new(
             &self.pubkey,
             self.created_at,
             &self.kind,
             &self.tags,
             &self.content,
-        );
-        let message = Message::from_slice(id.as_bytes())?;
-        SECP256K1
-            .verify_schnorr(&self.sig, &message, &self.pubkey)
-            .map_err(|_| Error::InvalidSignature)
+        )
+    }
+
+    /// Verify that the stored [`EventId`] match the recomputed one
+    pub fn verify_id(&self) -> Result<(), Error> {
+        if self.recompute_id() == self.id {
+            Ok(())
+        } else {
+            Err(Error::InvalidId)
+        }
+    }
+
+    /// Verify both the [`EventId`] and the signature
+    ///
+    /// Return [`Error::InvalidId`] if the stored id doesn't match the content
+    /// and [`Error::InvalidSignature`] if the signature is invalid.
+    pub fn is_valid(&self) -> Result<(), Error> {
+        self.verify_id()?;
+        self.verify()
+    }
+
+    /// Check if `created_at` is more than `tolerance` ahead of [`Timestamp::now`]
+    pub fn is_too_far_in_future(&self, tolerance: Duration) -> bool {
+        self.created_at > Timestamp::now() + tolerance
+    }
+
+    /// Compare by date: newest `created_at` first, then lowest [`EventId`] first
+    ///
+    /// Use it to sort the events of a feed (`events.sort_by(Event::cmp_by_date)`).
+    /// Note that the derived [`Ord`] of [`Event`] compares the ids first, so it's not chronological.
+    pub fn cmp_by_date(&self, other: &Self) -> Ordering {
+        other
+            .created_at
+            .cmp(&self.created_at)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+
+    /// Verify a batch of events
+    ///
+    /// Return the indices of the events with an invalid signature.
+    /// Verifications run in parallel if the `rayon` feature is enabled.
+    pub fn verify_batch<C>(events: &[Event], secp: &Secp256k1<C>) -> Result<(), Vec<usize>>
+    where
+        C: Verification + Sync,
+    {
+        let verify = |(index, event): (usize, &Event)| -> Option<usize> {
+            let id = EventId::new(
+                &event.pubkey,
+                event.created_at,
+                &event.kind,
+                &event.tags,
+                &event.content,
+            );
+            match Message::from_slice(id.as_bytes()) {
+                Ok(message) => match secp.verify_schnorr(&event.sig, &message, &event.pubkey) {
+                    Ok(()) => None,
+                    Err(_) => Some(index),
+                },
+                Err(_) => Some(index),
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        let failed: Vec<usize> = events.par_iter().enumerate().filter_map(verify).collect();
+        #[cfg(not(feature = "rayon"))]
+        let failed: Vec<usize> = events.iter().enumerate().filter_map(verify).collect();
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(failed)
+        }
     }
 
     /// New event from [`Value`]
@@ -100,11 +252,306 @@ impl Event {
         Ok(event)
     }
 
-    /// Get event as json string
+    /// New event from json string, verifying both the event id and the signature
+    ///
+    /// Use this for untrusted input.
+    pub fn from_json_verified<S>(json: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let event: Self = serde_json::from_str(&json.into())?;
+        event.is_valid()?;
+        Ok(event)
+    }
+
+    /// Compose [`Event`] from its parts, verifying both the event id and the signature
+    pub fn from_raw_parts(
+        id: EventId,
+        pubkey: XOnlyPublicKey,
+        created_at: Timestamp,
+        kind: Kind,
+        tags: Vec<Tag>,
+        content: String,
+        sig: Signature,
+    ) -> Result<Self, Error> {
+        let event: Self =
+            Self::from_raw_parts_unchecked(id, pubkey, created_at, kind, tags, content, sig);
+        event.is_valid()?;
+        Ok(event)
+    }
+
+    /// Compose [`Event`] from its parts, **without** verifying the event id and the signature
+    ///
+    /// Meant for bulk imports of events already verified (i.e. migrating between stores).
+    /// Use [`Event::from_raw_parts`] for untrusted input.
+    pub fn from_raw_parts_unchecked(
+        id: EventId,
+        pubkey: XOnlyPublicKey,
+        created_at: Timestamp,
+        kind: Kind,
+        tags: Vec<Tag>,
+        content: String,
+        sig: Signature,
+    ) -> Self {
+        Self {
+            id,
+            pubkey,
+            created_at,
+            kind,
+            tags,
+            content,
+            sig,
+            #[cfg(feature = "nip03")]
+            ots: None,
+        }
+    }
+
+    /// Get the custom emoji `shortcode` and `url` of a reaction
+    ///
+    /// Return `None` if the content isn't a `:shortcode:` with the matching `emoji` tag.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/30.md>
+    pub fn custom_emoji(&self) -> Option<(String, Url)> {
+        let shortcode: &str = self
+            .content
+            .strip_prefix(':')
+            .and_then(|c| c.strip_suffix(':'))?;
+        self.tags.iter().find_map(|tag| match tag {
+            Tag::Emoji { shortcode: s, url } if s == shortcode => {
+                Some((shortcode.to_string(), url.clone()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Decrypt the content of a NIP04 encrypted direct message
+    ///
+    /// `keys` must be the author or the `p`-tagged recipient of the message.
+    #[cfg(feature = "nip04")]
+    pub fn decrypted_content(&self, keys: &Keys) -> Result<String, Error> {
+        if self.kind != Kind::EncryptedDirectMessage {
+            return Err(Error::NotEncryptedDirectMessage);
+        }
+
+        let recipient: Option<XOnlyPublicKey> = self.tags.iter().find_map(|tag| match tag {
+            Tag::PubKey(pk, ..) => Some(*pk),
+            _ => None,
+        });
+
+        let public_key: XOnlyPublicKey = keys.public_key();
+        let counterparty: XOnlyPublicKey = if public_key == self.pubkey {
+            recipient.ok_or(Error::NotParty)?
+        } else if Some(public_key) == recipient {
+            self.pubkey
+        } else {
+            return Err(Error::NotParty);
+        };
+
+        Ok(nip04::decrypt(
+            &keys.secret_key()?,
+            &counterparty,
+            &self.content,
+        )?)
+    }
+
+    /// Get the [`ChannelId`] of a NIP28 channel event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub fn channel_id(&self) -> Option<ChannelId> {
+        match self.kind {
+            Kind::ChannelCreation => Some(ChannelId::from(self.id)),
+            Kind::ChannelMetadata | Kind::ChannelMessage => {
+                let mut channel_id: Option<EventId> = None;
+                for tag in self.tags.iter() {
+                    if let Tag::Event(id, _, marker) = tag {
+                        if marker == &Some(Marker::Root) {
+                            return Some(ChannelId::from(*id));
+                        }
+                        if channel_id.is_none() && marker.is_none() {
+                            channel_id = Some(*id);
+                        }
+                    }
+                }
+                channel_id.map(ChannelId::from)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the replied message of a NIP28 channel message
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub fn channel_reply_to(&self) -> Option<EventId> {
+        if self.kind != Kind::ChannelMessage {
+            return None;
+        }
+        self.tags.iter().find_map(|tag| match tag {
+            Tag::Event(id, _, Some(Marker::Reply)) => Some(*id),
+            _ => None,
+        })
+    }
+
+    /// Iterate the [`Tag`]s of a [`TagKind`]
+    pub fn tags_by_kind(&self, kind: TagKind) -> impl Iterator<Item = &Tag> {
+        self.tags.iter().filter(move |tag| tag.kind() == kind)
+    }
+
+    /// Get the first value of each tag of [`TagKind`]
+    ///
+    /// Typed tags (i.e. `p` or `e`) yield their serialized value (hex public key or event id).
+    pub fn tag_values(&self, kind: TagKind) -> impl Iterator<Item = String> + '_ {
+        self.tags_by_kind(kind)
+            .filter_map(|tag| tag.as_vec().into_iter().nth(1))
+    }
+
+    /// Get the public keys of the `p` tags
+    pub fn public_keys(&self) -> Vec<XOnlyPublicKey> {
+        self.tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::PubKey(pk, ..) | Tag::PubKeyReport(pk, ..) | Tag::ContactList { pk, .. } => {
+                    Some(*pk)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the public keys to notify: the `p` tags, excluding the author, without duplicates
+    pub fn notified_pubkeys(&self) -> Vec<XOnlyPublicKey> {
+        let mut pubkeys: Vec<XOnlyPublicKey> = Vec::new();
+        for pk in self.public_keys().into_iter() {
+            if pk != self.pubkey && !pubkeys.contains(&pk) {
+                pubkeys.push(pk);
+            }
+        }
+        pubkeys
+    }
+
+    /// Get the event ids of the `e` tags
+    pub fn event_ids(&self) -> Vec<EventId> {
+        self.tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::Event(id, ..) | Tag::EventReport(id, ..) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the identifier of the first `d` tag
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/33.md>
+    pub fn identifier(&self) -> Option<&str> {
+        self.tags.iter().find_map(|tag| match tag {
+            Tag::Identifier(identifier) => Some(identifier.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Get the zap amount (millisats)
+    ///
+    /// For zap receipts without an `amount` tag, the amount of the zap request in the `description` tag is returned.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/57.md>
+    pub fn zap_amount(&self) -> Option<u64> {
+        self.tags.iter().find_map(|tag| match tag {
+            Tag::Amount(amount) => Some(*amount),
+            Tag::Description(description) if self.kind == Kind::ZapReceipt => {
+                Event::from_json(description).ok()?.zap_amount()
+            }
+            _ => None,
+        })
+    }
+
+    /// Get the `bolt11` invoice of a zap receipt
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/57.md>
+    pub fn zap_bolt11(&self) -> Option<&str> {
+        self.tags.iter().find_map(|tag| match tag {
+            Tag::Bolt11(bolt11) => Some(bolt11.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Collect the subject, content warning, alt text and hashtags in a single pass
+    pub fn render_context(&self) -> RenderContext {
+        let mut ctx = RenderContext::default();
+        for tag in self.tags.iter() {
+            match tag {
+                Tag::Subject(subject) if ctx.subject.is_none() => {
+                    ctx.subject = Some(subject.clone());
+                }
+                Tag::ContentWarning { reason } if ctx.content_warning.is_none() => {
+                    ctx.content_warning = Some(reason.clone().unwrap_or_default());
+                }
+                Tag::Generic(TagKind::Custom(kind), values)
+                    if kind == "alt" && ctx.alt.is_none() =>
+                {
+                    ctx.alt = values.first().cloned();
+                }
+                Tag::Hashtag(hashtag) => ctx.hashtags.push(hashtag.clone()),
+                _ => (),
+            }
+        }
+        ctx
+    }
+
+    /// Get event as compact json string
+    ///
+    /// This is the form that must be used for the wire.
     pub fn as_json(&self) -> String {
         serde_json::json!(self).to_string()
     }
 
+    /// Approximate size (bytes) of [`Event::as_json`], without serializing the event
+    ///
+    /// Useful to check the relay max message size before sending.
+    pub fn estimated_json_size(&self) -> usize {
+        // {"content":"","created_at":,"id":"","kind":,"pubkey":"","sig":"","tags":[]}
+        let mut size: usize = 75 + 64 + 64 + 128;
+        let created_at: i64 = self.created_at.as_i64();
+        size += digits(created_at.unsigned_abs()) + usize::from(created_at < 0);
+        size += digits(self.kind.as_u64());
+        size += json_str_len(&self.content);
+
+        for (index, tag) in self.tags.iter().enumerate() {
+            if index > 0 {
+                size += 1;
+            }
+            let values: Vec<String> = tag.as_vec();
+            // [] and the commas
+            size += 2 + values.len().saturating_sub(1);
+            size += values.iter().map(|v| 2 + json_str_len(v)).sum::<usize>();
+        }
+
+        #[cfg(feature = "nip03")]
+        if let Some(ots) = &self.ots {
+            // ,"ots":""
+            size += 9 + json_str_len(ots);
+        }
+
+        size
+    }
+
+    /// Get the NIP-01 canonical serialization `[0, pubkey, created_at, kind, tags, content]` hashed into the [`EventId`]
+    ///
+    /// Useful to debug the id mismatches. See [`UnsignedEvent::from_canonical_json`] for the inverse.
+    pub fn id_canonical_json(&self) -> String {
+        id::canonical_json(
+            &self.pubkey,
+            self.created_at,
+            &self.kind,
+            &self.tags,
+            &self.content,
+        )
+    }
+
+    /// Get event as pretty json string (for debugging)
+    pub fn as_json_pretty(&self) -> String {
+        format!("{:#}", serde_json::json!(self))
+    }
+
     /// Timestamp this event with OpenTimestamps, according to NIP-03
     #[cfg(feature = "nip03")]
     pub fn timestamp(&mut self) -> Result<(), Error> {
@@ -114,6 +561,22 @@ impl Event {
     }
 }
 
+fn digits(n: u64) -> usize {
+    n.to_string().len()
+}
+
+/// Length of the escaped JSON string (without the quotes)
+fn json_str_len(s: &str) -> usize {
+    s.len()
+        + s.chars()
+            .map(|c| match c {
+                '"' | '\\' | '\n' | '\r' | '\t' | '\u{08}' | '\u{0c}' => 1,
+                c if (c as u32) < 0x20 => 5,
+                _ => 0,
+            })
+            .sum::<usize>()
+}
+
 impl Event {
     /// This is just for serde sanity checking
     #[allow(dead_code)]
@@ -149,6 +612,16 @@ impl Event {
     }
 }
 
+/// Get the newest of multiple versions of the same replaceable event (ex. the kind `3` contact lists fetched from many relays)
+///
+/// If two events have the same `created_at`, the one with the lowest [`EventId`] is kept.
+pub fn newest_replaceable<I>(events: I) -> Option<Event>
+where
+    I: IntoIterator<Item = Event>,
+{
+    events.into_iter().min_by(Event::cmp_by_date)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +636,402 @@ mod tests {
         assert_eq!(ev_ser.as_json(), sample_event);
     }
 
+    #[test]
+    fn test_from_json_verified() {
+        let sample_event = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        assert!(Event::from_json_verified(sample_event).is_ok());
+
+        // Tampered id
+        let tampered = sample_event.replace(
+            "2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        );
+        assert!(matches!(
+            Event::from_json_verified(tampered),
+            Err(Error::InvalidId)
+        ));
+
+        // Tampered content
+        let tampered = sample_event.replace("uRuvYr585B80L6rSJiHocw", "uRuvYr585B80L6rSJiHocW");
+        assert!(Event::from_json_verified(tampered).is_err());
+    }
+
+    #[test]
+    fn test_id_canonical_json() {
+        use bitcoin_hashes::sha256::Hash as Sha256Hash;
+        use bitcoin_hashes::Hash;
+
+        let sample_event = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        let event = Event::from_json(sample_event).unwrap();
+
+        let canonical = event.id_canonical_json();
+        assert_eq!(
+            canonical,
+            r#"[0,"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785",1640839235,4,[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]],"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA=="]"#
+        );
+        assert_eq!(Sha256Hash::hash(canonical.as_bytes()), event.id.inner());
+
+        let unsigned = UnsignedEvent::from_canonical_json(canonical).unwrap();
+        assert_eq!(unsigned.id, event.id);
+        assert_eq!(unsigned.pubkey, event.pubkey);
+        assert_eq!(unsigned.tags, event.tags);
+        assert_eq!(unsigned.content, event.content);
+
+        // Not a canonical array
+        assert!(UnsignedEvent::from_canonical_json(sample_event).is_err());
+        let wrong_prefix = event.id_canonical_json().replacen('0', "1", 1);
+        assert!(UnsignedEvent::from_canonical_json(wrong_prefix).is_err());
+    }
+
+    #[test]
+    fn test_as_json_pretty() {
+        let sample_event = r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#;
+        let event = Event::from_json(sample_event).unwrap();
+
+        let compact = event.as_json();
+        let pretty = event.as_json_pretty();
+        assert_eq!(compact, sample_event);
+        assert_ne!(compact, pretty);
+        assert!(pretty.contains('\n'));
+
+        let from_pretty = Event::from_json(pretty).unwrap();
+        assert_eq!(from_pretty, Event::from_json(compact).unwrap());
+        assert!(from_pretty.is_valid().is_ok());
+    }
+
+    #[test]
+    fn test_is_valid_stale_id() {
+        let keys = Keys::generate();
+        let mut event = EventBuilder::new_text_note("Original", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert!(event.is_valid().is_ok());
+
+        // Content swapped, wire id is stale
+        event.content = String::from("Swapped");
+        assert!(matches!(event.verify_id(), Err(Error::InvalidId)));
+        assert!(matches!(event.is_valid(), Err(Error::InvalidId)));
+
+        // Id recomputed, signature is invalid
+        event.id = EventId::new(
+            &event.pubkey,
+            event.created_at,
+            &event.kind,
+            &event.tags,
+            &event.content,
+        );
+        assert!(event.verify_id().is_ok());
+        assert!(matches!(event.is_valid(), Err(Error::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let keys = Keys::generate();
+        let mut events: Vec<Event> = (0..5)
+            .map(|i| {
+                EventBuilder::new_text_note(format!("Note {i}"), &[])
+                    .to_event(&keys)
+                    .unwrap()
+            })
+            .collect();
+        let secp = Secp256k1::verification_only();
+        assert!(Event::verify_batch(&events, &secp).is_ok());
+
+        events[1].content = String::from("Tampered");
+        events[3].kind = Kind::Reaction;
+        assert_eq!(Event::verify_batch(&events, &secp), Err(vec![1, 3]));
+    }
+
+    #[test]
+    fn test_estimated_json_size() {
+        let keys = Keys::generate();
+        let events: Vec<Event> = vec![
+            EventBuilder::new_text_note("", &[]),
+            EventBuilder::new_text_note("Hello \"world\"\n\t\u{1}", &[]),
+            EventBuilder::new_text_note(
+                "Reply",
+                &[
+                    Tag::Event(
+                        EventId::from_hex(
+                            "2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45",
+                        )
+                        .unwrap(),
+                        Some(String::from("wss://relay.damus.io")),
+                        Some(Marker::Reply),
+                    ),
+                    Tag::PubKey(keys.public_key(), None),
+                    Tag::Hashtag(String::from("nostr")),
+                ],
+            ),
+            EventBuilder::new(Kind::Custom(30023), "Long-form ✓", &[]),
+            EventBuilder::new_text_note("Before the epoch", &[])
+                .custom_created_at(Timestamp::from(0) - 3600u64),
+        ]
+        .into_iter()
+        .map(|builder| builder.to_event(&keys).unwrap())
+        .collect();
+
+        for event in events.into_iter() {
+            assert_eq!(event.estimated_json_size(), event.as_json().len());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "nip04")]
+    fn test_decrypted_content() {
+        let alice_keys = Keys::generate();
+        let bob_keys = Keys::generate();
+        let event = EventBuilder::new_encrypted_direct_msg(
+            &alice_keys,
+            bob_keys.public_key(),
+            "Hi Bob",
+            None,
+            None,
+        )
+        .unwrap()
+        .to_event(&alice_keys)
+        .unwrap();
+
+        assert_eq!(event.decrypted_content(&bob_keys).unwrap(), "Hi Bob");
+        assert_eq!(event.decrypted_content(&alice_keys).unwrap(), "Hi Bob");
+        assert!(matches!(
+            event.decrypted_content(&Keys::generate()),
+            Err(Error::NotParty)
+        ));
+
+        let note = EventBuilder::new_text_note("Hi", &[])
+            .to_event(&alice_keys)
+            .unwrap();
+        assert!(matches!(
+            note.decrypted_content(&bob_keys),
+            Err(Error::NotEncryptedDirectMessage)
+        ));
+    }
+
+    #[test]
+    fn test_render_context() {
+        let keys = Keys::generate();
+        let tags = vec![
+            Tag::Subject(String::from("Weekly update")),
+            Tag::ContentWarning {
+                reason: Some(String::from("spoilers")),
+            },
+            Tag::Generic(
+                TagKind::Custom(String::from("alt")),
+                vec![String::from("A text note")],
+            ),
+            Tag::Hashtag(String::from("nostr")),
+            Tag::Hashtag(String::from("rust")),
+        ];
+        let event = EventBuilder::new_text_note("Hello", &tags)
+            .to_event(&keys)
+            .unwrap();
+
+        assert_eq!(
+            event.render_context(),
+            RenderContext {
+                subject: Some(String::from("Weekly update")),
+                content_warning: Some(String::from("spoilers")),
+                alt: Some(String::from("A text note")),
+                hashtags: vec![String::from("nostr"), String::from("rust")],
+            }
+        );
+
+        let empty = EventBuilder::new_text_note("Hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(empty.render_context(), RenderContext::default());
+    }
+
+    #[test]
+    fn test_malformed_hex_errors() {
+        let json = r#"{"content":"","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d4","kind":1,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[]}"#;
+        let e = Event::from_json(json).unwrap_err().to_string();
+        assert!(e.contains("invalid event id: expected 64 hex chars, found 63"));
+
+        let json = r#"{"content":"","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":1,"pubkey":"x86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[]}"#;
+        let e = Event::from_json(json).unwrap_err().to_string();
+        assert!(e.contains("invalid pubkey: invalid hex char 'x' at position 0"));
+    }
+
+    #[test]
+    fn test_is_too_far_in_future() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("Hello from the future", &[])
+            .custom_created_at(Timestamp::now() + Duration::from_secs(3600))
+            .to_event(&keys)
+            .unwrap();
+        assert!(event.is_too_far_in_future(Duration::from_secs(60)));
+        assert!(!event.is_too_far_in_future(Duration::from_secs(2 * 3600)));
+    }
+
+    #[test]
+    fn test_from_raw_parts() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("Imported", &[Tag::Hashtag(String::from("nostr"))])
+            .to_event(&keys)
+            .unwrap();
+
+        let imported = Event::from_raw_parts_unchecked(
+            event.id,
+            event.pubkey,
+            event.created_at,
+            event.kind,
+            event.tags.clone(),
+            event.content.clone(),
+            event.sig,
+        );
+        assert_eq!(imported, event);
+        assert!(imported.is_valid().is_ok());
+
+        let checked = Event::from_raw_parts(
+            event.id,
+            event.pubkey,
+            event.created_at,
+            event.kind,
+            event.tags.clone(),
+            String::from("Tampered"),
+            event.sig,
+        );
+        assert!(matches!(checked, Err(Error::InvalidId)));
+    }
+
+    #[test]
+    fn test_tag_values() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note(
+            "#nostr #rust #bitcoin",
+            &[
+                Tag::Hashtag(String::from("nostr")),
+                Tag::PubKey(keys.public_key(), None),
+                Tag::Hashtag(String::from("rust")),
+                Tag::Generic(TagKind::T, vec![String::from("bitcoin")]),
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let hashtags: Vec<String> = event.tag_values(TagKind::T).collect();
+        assert_eq!(hashtags, vec!["nostr", "rust", "bitcoin"]);
+
+        let event_id = EventId::from_slice(&[1; 32]).unwrap();
+        let event = EventBuilder::new_text_note(
+            "Reply",
+            &[
+                Tag::Event(event_id, None, None),
+                Tag::PubKey(keys.public_key(), None),
+                Tag::Generic(TagKind::P, vec![keys.public_key().to_string()]),
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let pubkeys: Vec<String> = event.tag_values(TagKind::P).collect();
+        assert_eq!(
+            pubkeys,
+            vec![keys.public_key().to_string(), keys.public_key().to_string()]
+        );
+        let event_ids: Vec<String> = event.tag_values(TagKind::E).collect();
+        assert_eq!(event_ids, vec![event_id.to_hex()]);
+    }
+
+    #[test]
+    fn test_zap_amount_and_bolt11() {
+        let keys = Keys::generate();
+        let zap_request = EventBuilder::new(
+            Kind::ZapRequest,
+            "",
+            &[Tag::PubKey(keys.public_key(), None), Tag::Amount(21000)],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let receipt = EventBuilder::new(
+            Kind::ZapReceipt,
+            "",
+            &[
+                Tag::PubKey(keys.public_key(), None),
+                Tag::Bolt11(String::from("lnbc210n1pj")),
+                Tag::Description(zap_request.as_json()),
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        assert_eq!(receipt.zap_bolt11(), Some("lnbc210n1pj"));
+        assert_eq!(receipt.zap_amount(), Some(21000));
+
+        let tags: Vec<Tag> = vec![Tag::parse(vec!["amount", "1000"]).unwrap()];
+        let event = EventBuilder::new(Kind::ZapRequest, "", &tags)
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.zap_amount(), Some(1000));
+        assert_eq!(event.zap_bolt11(), None);
+    }
+
+    #[test]
+    fn test_tag_accessors() {
+        let keys = Keys::generate();
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+        let event_id =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")
+                .unwrap();
+        let tags = vec![
+            Tag::PubKey(alice, None),
+            Tag::Hashtag(String::from("nostr")),
+            Tag::Event(event_id, None, None),
+            Tag::Identifier(String::from("article")),
+            Tag::PubKey(bob, Some(String::from("wss://relay.damus.io"))),
+        ];
+        let event = EventBuilder::new(Kind::LongFormTextNote, "", &tags)
+            .to_event(&keys)
+            .unwrap();
+
+        assert_eq!(event.public_keys(), vec![alice, bob]);
+        assert_eq!(event.event_ids(), vec![event_id]);
+        assert_eq!(event.identifier(), Some("article"));
+        assert_eq!(
+            event.tags_by_kind(TagKind::P).collect::<Vec<&Tag>>(),
+            vec![&tags[0], &tags[4]]
+        );
+        assert_eq!(event.tags_by_kind(TagKind::A).count(), 0);
+
+        let note = EventBuilder::new_text_note("Hi", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(note.identifier(), None);
+    }
+
+    #[test]
+    fn test_notified_pubkeys() {
+        let keys = Keys::generate();
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+        let tags = vec![
+            Tag::PubKey(alice, None),
+            Tag::PubKey(keys.public_key(), None),
+            Tag::PubKey(bob, None),
+            Tag::PubKey(alice, Some(String::from("wss://relay.damus.io"))),
+        ];
+        let event = EventBuilder::new_text_note("Hi alice and bob", &tags)
+            .to_event(&keys)
+            .unwrap();
+
+        assert_eq!(event.notified_pubkeys(), vec![alice, bob]);
+    }
+
+    #[test]
+    fn test_recompute_id() {
+        let keys = Keys::generate();
+        let mut event = EventBuilder::new_text_note("Hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.recompute_id(), event.id);
+
+        event.content = String::from("Tampered");
+        assert_ne!(event.recompute_id(), event.id);
+    }
+
     #[test]
     fn test_custom_kind() {
         let keys = Keys::generate();
@@ -177,4 +1046,52 @@ mod tests {
         assert_eq!(Kind::Custom(123), e.kind);
         assert_eq!(Kind::Custom(123), deserialized.kind);
     }
+
+    #[test]
+    fn test_cmp_by_date() {
+        let keys = Keys::generate();
+        let note = |created_at: u64, content: &str| {
+            EventBuilder::new_text_note(content, &[])
+                .custom_created_at(Timestamp::from(created_at))
+                .to_event(&keys)
+                .unwrap()
+        };
+
+        let mut events = [
+            note(2_000, "b"),
+            note(1_000, "a"),
+            note(3_000, "c"),
+            note(2_000, "d"),
+        ];
+        events.sort_by(Event::cmp_by_date);
+
+        let created_at: Vec<u64> = events.iter().map(|e| e.created_at.as_u64()).collect();
+        assert_eq!(created_at, vec![3_000, 2_000, 2_000, 1_000]);
+        assert!(events[1].id < events[2].id);
+    }
+
+    #[test]
+    fn test_newest_replaceable() {
+        let keys = Keys::generate();
+        let contact_list = |created_at: u64, content: &str| {
+            EventBuilder::new(Kind::ContactList, content, &[])
+                .custom_created_at(Timestamp::from(created_at))
+                .to_event(&keys)
+                .unwrap()
+        };
+
+        let events = vec![
+            contact_list(1_000, "a"),
+            contact_list(3_000, "b"),
+            contact_list(2_000, "c"),
+        ];
+        assert_eq!(newest_replaceable(events.clone()), Some(events[1].clone()));
+
+        // Same `created_at`: the lowest id wins
+        let tie = vec![contact_list(3_000, "d"), events[1].clone()];
+        let lowest = tie.iter().min_by_key(|e| e.id).cloned();
+        assert_eq!(newest_replaceable(tie), lowest);
+
+        assert_eq!(newest_replaceable(Vec::new()), None);
+    }
 }
@@ -3,6 +3,7 @@
 
 //! Tag
 
+use std::convert::Infallible;
 use std::fmt;
 use std::num::ParseIntError;
 use std::str::FromStr;
@@ -16,7 +17,7 @@ use url::Url;
 
 use super::id::{self, EventId};
 use crate::nips::nip26::Conditions;
-use crate::{Kind, Timestamp};
+use crate::{Kind, Timestamp, UncheckedUrl};
 
 /// [`Tag`] error
 #[derive(Debug, thiserror::Error)]
@@ -154,6 +155,8 @@ pub enum TagKind {
     D,
     /// Referencing and tagging
     A,
+    /// External identity (NIP39)
+    I,
     /// Relay
     Relay,
     /// Nonce
@@ -186,7 +189,29 @@ pub enum TagKind {
     Relays,
     /// Amount (NIP57)
     Amount,
-    /// Custom tag kind
+    /// Custom emoji (NIP30)
+    Emoji,
+    /// Label namespace (NIP32)
+    LabelNamespace,
+    /// Label (NIP32)
+    Label,
+    /// Inline metadata (NIP92)
+    Imeta,
+    /// Url (NIP94)
+    Url,
+    /// Mime type (NIP94)
+    MimeType,
+    /// SHA256 hash (NIP94)
+    Sha256,
+    /// Size in bytes (NIP94)
+    Size,
+    /// Dimensions (NIP94)
+    Dim,
+    /// Magnet URI (NIP94)
+    Magnet,
+    /// Blurhash (NIP94)
+    Blurhash,
+    /// Custom (unknown) tag kind
     Custom(String),
 }
 
@@ -200,6 +225,7 @@ impl fmt::Display for TagKind {
             Self::G => write!(f, "g"),
             Self::D => write!(f, "d"),
             Self::A => write!(f, "a"),
+            Self::I => write!(f, "i"),
             Self::Relay => write!(f, "relay"),
             Self::Nonce => write!(f, "nonce"),
             Self::Delegation => write!(f, "delegation"),
@@ -216,6 +242,17 @@ impl fmt::Display for TagKind {
             Self::Preimage => write!(f, "preimage"),
             Self::Relays => write!(f, "relays"),
             Self::Amount => write!(f, "amount"),
+            Self::Emoji => write!(f, "emoji"),
+            Self::LabelNamespace => write!(f, "L"),
+            Self::Label => write!(f, "l"),
+            Self::Imeta => write!(f, "imeta"),
+            Self::Url => write!(f, "url"),
+            Self::MimeType => write!(f, "m"),
+            Self::Sha256 => write!(f, "x"),
+            Self::Size => write!(f, "size"),
+            Self::Dim => write!(f, "dim"),
+            Self::Magnet => write!(f, "magnet"),
+            Self::Blurhash => write!(f, "blurhash"),
             Self::Custom(tag) => write!(f, "{tag}"),
         }
     }
@@ -235,6 +272,7 @@ where
             "g" => Self::G,
             "d" => Self::D,
             "a" => Self::A,
+            "i" => Self::I,
             "relay" => Self::Relay,
             "nonce" => Self::Nonce,
             "delegation" => Self::Delegation,
@@ -251,11 +289,31 @@ where
             "preimage" => Self::Preimage,
             "relays" => Self::Relays,
             "amount" => Self::Amount,
+            "emoji" => Self::Emoji,
+            "L" => Self::LabelNamespace,
+            "l" => Self::Label,
+            "imeta" => Self::Imeta,
+            "url" => Self::Url,
+            "m" => Self::MimeType,
+            "x" => Self::Sha256,
+            "size" => Self::Size,
+            "dim" => Self::Dim,
+            "magnet" => Self::Magnet,
+            "blurhash" => Self::Blurhash,
             tag => Self::Custom(tag.to_string()),
         }
     }
 }
 
+impl FromStr for TagKind {
+    type Err = Infallible;
+
+    /// Parse a [`TagKind`]: unknown kinds are parsed as [`TagKind::Custom`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
 pub enum Tag {
@@ -302,9 +360,18 @@ pub enum Tag {
     Description(String),
     Bolt11(String),
     Preimage(String),
-    Relays(Vec<Url>),
+    Relays(Vec<UncheckedUrl>),
     Amount(u64),
     PublishedAt(Timestamp),
+    Emoji {
+        shortcode: String,
+        url: Url,
+    },
+    LabelNamespace(String),
+    Label {
+        label: String,
+        namespace: Option<String>,
+    },
 }
 
 impl Tag {
@@ -316,6 +383,58 @@ impl Tag {
         Tag::try_from(data)
     }
 
+    /// Compose a [`Tag::PubKey`] for each public key
+    pub fn public_keys<I>(public_keys: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = XOnlyPublicKey>,
+    {
+        public_keys
+            .into_iter()
+            .map(|pk| Self::PubKey(pk, None))
+            .collect()
+    }
+
+    /// Compose a [`Tag::Event`] for each event id
+    pub fn event_ids<I>(event_ids: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = EventId>,
+    {
+        event_ids
+            .into_iter()
+            .map(|id| Self::Event(id, None, None))
+            .collect()
+    }
+
+    /// Get the first value of the [`Tag`], if it's stored as a string
+    ///
+    /// Return `None` for the tags with a typed value (i.e. `p`, `e`, `expiration`, `amount`).
+    pub fn content(&self) -> Option<&str> {
+        match self {
+            Tag::Generic(_, values) => values.first().map(|v| v.as_str()),
+            Tag::Reference(value)
+            | Tag::RelayMetadata(value, ..)
+            | Tag::Hashtag(value)
+            | Tag::Geohash(value)
+            | Tag::Identifier(value)
+            | Tag::Subject(value)
+            | Tag::Challenge(value)
+            | Tag::Title(value)
+            | Tag::Image(value)
+            | Tag::Summary(value)
+            | Tag::Description(value)
+            | Tag::Bolt11(value)
+            | Tag::Preimage(value)
+            | Tag::LabelNamespace(value)
+            | Tag::Emoji {
+                shortcode: value, ..
+            }
+            | Tag::Label { label: value, .. } => Some(value.as_str()),
+            Tag::ContentWarning { reason } => reason.as_deref(),
+            Tag::Relay(url) => Some(url.as_str()),
+            _ => None,
+        }
+    }
+
     /// Get [`Tag`] as string vector
     pub fn as_vec(&self) -> Vec<String> {
         self.clone().into()
@@ -352,6 +471,9 @@ impl Tag {
             Tag::Preimage(..) => TagKind::Preimage,
             Tag::Relays(..) => TagKind::Relays,
             Tag::Amount(..) => TagKind::Amount,
+            Tag::Emoji { .. } => TagKind::Emoji,
+            Tag::LabelNamespace(..) => TagKind::LabelNamespace,
+            Tag::Label { .. } => TagKind::Label,
         }
     }
 }
@@ -373,10 +495,10 @@ where
         if tag_kind.eq(&TagKind::Relays) {
             // Relays vec is of unknown length so checked here based on kind
             let urls = tag
-                .iter()
+                .into_iter()
                 .skip(1)
-                .filter_map(|tag_str| Url::parse(tag_str).ok())
-                .collect::<Vec<Url>>();
+                .map(UncheckedUrl::from)
+                .collect::<Vec<UncheckedUrl>>();
 
             Ok(Self::Relays(urls))
         } else if tag_len == 1 {
@@ -408,6 +530,11 @@ where
                 TagKind::Bolt11 => Ok(Self::Bolt11(content.to_string())),
                 TagKind::Preimage => Ok(Self::Preimage(content.to_string())),
                 TagKind::Amount => Ok(Self::Amount(content.parse()?)),
+                TagKind::LabelNamespace => Ok(Self::LabelNamespace(content.to_string())),
+                TagKind::Label => Ok(Self::Label {
+                    label: content.to_string(),
+                    namespace: None,
+                }),
                 _ => Ok(Self::Generic(tag_kind, vec![content.to_string()])),
             }
         } else if tag_len == 3 {
@@ -451,13 +578,21 @@ where
                         Err(Error::InvalidLength)
                     }
                 }
+                TagKind::Emoji => Ok(Self::Emoji {
+                    shortcode: tag[1].clone(),
+                    url: Url::parse(&tag[2])?,
+                }),
+                TagKind::Label => Ok(Self::Label {
+                    label: tag[1].clone(),
+                    namespace: Some(tag[2].clone()),
+                }),
                 _ => Ok(Self::Generic(tag_kind, tag[1..].to_vec())),
             }
         } else if tag_len == 4 {
             match tag_kind {
                 TagKind::P => Ok(Self::ContactList {
                     pk: XOnlyPublicKey::from_str(&tag[1])?,
-                    relay_url: Some(tag[2].clone()),
+                    relay_url: (!tag[2].is_empty()).then_some(tag[2].clone()),
                     alias: (!tag[3].is_empty()).then_some(tag[3].clone()),
                 }),
                 TagKind::E => Ok(Self::Event(
@@ -534,12 +669,19 @@ impl From<Tag> for Vec<String> {
                 pk,
                 relay_url,
                 alias,
-            } => vec![
-                TagKind::P.to_string(),
-                pk.to_string(),
-                relay_url.unwrap_or_default(),
-                alias.unwrap_or_default(),
-            ],
+            } => {
+                let mut tag = vec![TagKind::P.to_string(), pk.to_string()];
+                // Empty relay url only if followed by the petname
+                match (relay_url, alias) {
+                    (relay_url, Some(alias)) => {
+                        tag.push(relay_url.unwrap_or_default());
+                        tag.push(alias);
+                    }
+                    (Some(relay_url), None) => tag.push(relay_url),
+                    (None, None) => (),
+                }
+                tag
+            }
             Tag::POW { nonce, difficulty } => vec![
                 TagKind::Nonce.to_string(),
                 nonce.to_string(),
@@ -584,15 +726,24 @@ impl From<Tag> for Vec<String> {
             }
             Tag::Relays(relays) => vec![TagKind::Relays.to_string()]
                 .into_iter()
-                .chain(
-                    relays
-                        .iter()
-                        .map(|relay| relay.to_string().trim_end_matches('/').to_string()),
-                )
+                .chain(relays.iter().map(|relay| relay.to_string()))
                 .collect::<Vec<_>>(),
             Tag::Amount(amount) => {
                 vec![TagKind::Amount.to_string(), amount.to_string()]
             }
+            Tag::Emoji { shortcode, url } => {
+                vec![TagKind::Emoji.to_string(), shortcode, url.to_string()]
+            }
+            Tag::LabelNamespace(namespace) => {
+                vec![TagKind::LabelNamespace.to_string(), namespace]
+            }
+            Tag::Label { label, namespace } => {
+                let mut tag = vec![TagKind::Label.to_string(), label];
+                if let Some(namespace) = namespace {
+                    tag.push(namespace);
+                }
+                tag
+            }
         }
     }
 }
@@ -627,6 +778,34 @@ mod tests {
     use super::*;
     use crate::{Event, Result, Timestamp};
 
+    #[test]
+    fn test_tag_kind_from_str_display() {
+        let known = [
+            ("e", TagKind::E),
+            ("p", TagKind::P),
+            ("a", TagKind::A),
+            ("d", TagKind::D),
+            ("t", TagKind::T),
+            ("r", TagKind::R),
+            ("g", TagKind::G),
+            ("i", TagKind::I),
+            ("nonce", TagKind::Nonce),
+            ("subject", TagKind::Subject),
+            ("expiration", TagKind::Expiration),
+            ("content-warning", TagKind::ContentWarning),
+            ("relay", TagKind::Relay),
+            ("challenge", TagKind::Challenge),
+        ];
+        for (s, kind) in known.into_iter() {
+            assert_eq!(TagKind::from_str(s).unwrap(), kind);
+            assert_eq!(kind.to_string(), s);
+        }
+
+        let unknown = TagKind::from_str("proxy").unwrap();
+        assert_eq!(unknown, TagKind::Custom(String::from("proxy")));
+        assert_eq!(unknown.to_string(), "proxy");
+    }
+
     #[test]
     fn test_deserialize_tag_from_event() -> Result<()> {
         // Got this fresh off the wire
@@ -1107,9 +1286,9 @@ mod tests {
                 "wss://nostr.fmt.wiz.biz"
             ])?,
             Tag::Relays(vec![
-                Url::from_str("wss://relay.damus.io")?,
-                Url::from_str("wss://nostr-relay.wlvs.space")?,
-                Url::from_str("wss://nostr.fmt.wiz.biz")?
+                UncheckedUrl::from("wss://relay.damus.io/"),
+                UncheckedUrl::from("wss://nostr-relay.wlvs.space"),
+                UncheckedUrl::from("wss://nostr.fmt.wiz.biz")
             ])
         );
 
@@ -1142,4 +1321,99 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_relays_tag_round_trip() -> Result<()> {
+        let tag = Tag::Relays(vec![
+            UncheckedUrl::from("wss://relay.damus.io"),
+            UncheckedUrl::from("wss://nostr.wine/"),
+            UncheckedUrl::from("ws://monad.jb55.com:8080"),
+        ]);
+
+        let vec = tag.as_vec();
+        assert_eq!(
+            vec,
+            vec![
+                "relays",
+                "wss://relay.damus.io",
+                "wss://nostr.wine/",
+                "ws://monad.jb55.com:8080"
+            ]
+        );
+        assert_eq!(Tag::parse(vec)?, tag);
+
+        let json = serde_json::to_string(&tag)?;
+        assert_eq!(
+            json,
+            r#"["relays","wss://relay.damus.io","wss://nostr.wine/","ws://monad.jb55.com:8080"]"#
+        );
+        assert_eq!(serde_json::from_str::<Tag>(&json)?, tag);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_contact_list_tag_arity() -> Result<()> {
+        let pk = XOnlyPublicKey::from_str(
+            "13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d",
+        )?;
+        let hex: String = pk.to_string();
+        let hex: &str = hex.as_str();
+
+        // 2 elements
+        let tag = Tag::ContactList {
+            pk,
+            relay_url: None,
+            alias: None,
+        };
+        assert_eq!(tag.as_vec(), vec!["p", hex]);
+        assert_eq!(Tag::parse(tag.as_vec())?, Tag::PubKey(pk, None));
+
+        // 3 elements
+        let tag = Tag::ContactList {
+            pk,
+            relay_url: Some(String::from("wss://relay.damus.io")),
+            alias: None,
+        };
+        assert_eq!(tag.as_vec(), vec!["p", hex, "wss://relay.damus.io"]);
+        assert_eq!(
+            Tag::parse(tag.as_vec())?,
+            Tag::PubKey(pk, Some(String::from("wss://relay.damus.io")))
+        );
+
+        // 4 elements, without relay
+        let tag = Tag::ContactList {
+            pk,
+            relay_url: None,
+            alias: Some(String::from("alice")),
+        };
+        assert_eq!(tag.as_vec(), vec!["p", hex, "", "alice"]);
+        assert_eq!(Tag::parse(tag.as_vec())?, tag);
+
+        // 4 elements
+        let tag = Tag::ContactList {
+            pk,
+            relay_url: Some(String::from("wss://relay.damus.io")),
+            alias: Some(String::from("alice")),
+        };
+        assert_eq!(
+            tag.as_vec(),
+            vec!["p", hex, "wss://relay.damus.io", "alice"]
+        );
+        assert_eq!(Tag::parse(tag.as_vec())?, tag);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_keys_tags() {
+        let public_keys: Vec<XOnlyPublicKey> = (0..3)
+            .map(|_| crate::Keys::generate().public_key())
+            .collect();
+        let tags = Tag::public_keys(public_keys.clone());
+        assert_eq!(tags.len(), 3);
+        for (tag, pk) in tags.into_iter().zip(public_keys) {
+            assert_eq!(tag.as_vec(), vec![String::from("p"), pk.to_string()]);
+        }
+    }
 }
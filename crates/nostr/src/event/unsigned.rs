@@ -4,9 +4,10 @@
 //! Unsigned Event
 
 use secp256k1::schnorr::Signature;
-use secp256k1::{Message, XOnlyPublicKey};
+use secp256k1::{KeyPair, Message, Secp256k1, Signing, XOnlyPublicKey};
+use serde::de::Error as DeError;
 
-use crate::{Event, EventId, Keys, Kind, Tag, Timestamp};
+use crate::{Event, EventId, Keys, Kind, Tag, Timestamp, SECP256K1};
 
 /// [`UnsignedEvent`] error
 #[derive(Debug, thiserror::Error)]
@@ -23,6 +24,12 @@ pub enum Error {
     /// Event error
     #[error(transparent)]
     Event(#[from] super::Error),
+    /// The public key of the [`Keys`] doesn't match the event author
+    #[error("public key mismatch")]
+    PublicKeyMismatch,
+    /// The [`EventId`] doesn't match the event fields
+    #[error("invalid event id")]
+    InvalidId,
 }
 
 /// [`UnsignedEvent`] struct
@@ -43,8 +50,62 @@ pub struct UnsignedEvent {
 }
 
 impl UnsignedEvent {
+    /// New [`UnsignedEvent`]
+    ///
+    /// The [`EventId`] is computed from the fields.
+    pub fn new<S>(
+        pubkey: XOnlyPublicKey,
+        created_at: Timestamp,
+        kind: Kind,
+        tags: Vec<Tag>,
+        content: S,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let content: String = content.into();
+        Self {
+            id: EventId::new(&pubkey, created_at, &kind, &tags, &content),
+            pubkey,
+            created_at,
+            kind,
+            tags,
+            content,
+        }
+    }
+
     /// Sign an [`UnsignedEvent`]
+    ///
+    /// Return [`Error::PublicKeyMismatch`] if the [`Keys`] public key is not the event author
+    /// and [`Error::InvalidId`] if the [`EventId`] doesn't match the event fields.
     pub fn sign(self, keys: &Keys) -> Result<Event, Error> {
+        self.sign_with_keys(keys, SECP256K1)
+    }
+
+    /// Sign an [`UnsignedEvent`] using the provided [`Secp256k1`] context
+    ///
+    /// Return [`Error::PublicKeyMismatch`] if the [`Keys`] public key is not the event author
+    /// and [`Error::InvalidId`] if the [`EventId`] doesn't match the event fields.
+    pub fn sign_with_keys<C>(self, keys: &Keys, secp: &Secp256k1<C>) -> Result<Event, Error>
+    where
+        C: Signing,
+    {
+        if keys.public_key() != self.pubkey {
+            return Err(Error::PublicKeyMismatch);
+        }
+
+        let id = EventId::new(
+            &self.pubkey,
+            self.created_at,
+            &self.kind,
+            &self.tags,
+            &self.content,
+        );
+        if id != self.id {
+            return Err(Error::InvalidId);
+        }
+
+        let keypair: KeyPair = keys.key_pair()?;
         let message = Message::from_slice(self.id.as_bytes())?;
         Ok(Event {
             id: self.id,
@@ -53,7 +114,7 @@ impl UnsignedEvent {
             kind: self.kind,
             tags: self.tags,
             content: self.content,
-            sig: keys.sign_schnorr(&message)?,
+            sig: secp.sign_schnorr(&message, &keypair),
             #[cfg(feature = "nip03")]
             ots: None,
         })
@@ -88,4 +149,89 @@ impl UnsignedEvent {
     pub fn as_json(&self) -> String {
         serde_json::json!(self).to_string()
     }
+
+    /// Reconstruct the hashable fields from the NIP-01 canonical serialization `[0, pubkey, created_at, kind, tags, content]`
+    ///
+    /// The [`EventId`] is computed from the fields. See [`Event::id_canonical_json`].
+    pub fn from_canonical_json<S>(json: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let (zero, pubkey, created_at, kind, tags, content): (
+            u8,
+            XOnlyPublicKey,
+            Timestamp,
+            Kind,
+            Vec<Tag>,
+            String,
+        ) = serde_json::from_str(&json.into())?;
+        if zero != 0 {
+            return Err(Error::Json(DeError::custom(format!(
+                "expected 0 as first element, found {zero}"
+            ))));
+        }
+        Ok(Self::new(pubkey, created_at, kind, tags, content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventBuilder;
+
+    #[test]
+    fn test_sign_with_keys() {
+        let keys = Keys::generate();
+        let secp = Secp256k1::signing_only();
+
+        let unsigned =
+            EventBuilder::new_text_note("hello", &[]).to_unsigned_event(keys.public_key());
+        let event = unsigned.clone().sign_with_keys(&keys, &secp).unwrap();
+
+        assert_eq!(event.id, unsigned.id);
+        assert!(event.verify().is_ok());
+    }
+
+    #[test]
+    fn test_new_matches_builder() {
+        let keys = Keys::generate();
+        let created_at = Timestamp::from(1_690_000_000);
+        let tags = vec![Tag::Hashtag(String::from("nostr"))];
+
+        let unsigned = UnsignedEvent::new(
+            keys.public_key(),
+            created_at,
+            Kind::TextNote,
+            tags.clone(),
+            "hello",
+        );
+        let built = EventBuilder::new_text_note("hello", &tags)
+            .custom_created_at(created_at)
+            .to_unsigned_event(keys.public_key());
+
+        assert_eq!(unsigned, built);
+    }
+
+    #[test]
+    fn test_sign_public_key_mismatch() {
+        let keys = Keys::generate();
+        let other_keys = Keys::generate();
+
+        let unsigned =
+            EventBuilder::new_text_note("hello", &[]).to_unsigned_event(keys.public_key());
+        assert!(matches!(
+            unsigned.sign(&other_keys),
+            Err(Error::PublicKeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_sign_tampered_id() {
+        let keys = Keys::generate();
+
+        let mut unsigned =
+            EventBuilder::new_text_note("hello", &[]).to_unsigned_event(keys.public_key());
+        unsigned.content = String::from("tampered");
+        assert!(matches!(unsigned.sign(&keys), Err(Error::InvalidId)));
+    }
 }
@@ -0,0 +1,95 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Verified event
+
+use std::ops::Deref;
+
+use super::{Error, Event};
+
+/// [`Event`] with a checked [`EventId`](super::EventId) and signature
+///
+/// Can be constructed only with [`Event::into_verified`] or [`TryFrom<Event>`],
+/// so the APIs that take a [`VerifiedEvent`] can skip the verification.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct VerifiedEvent(Event);
+
+impl VerifiedEvent {
+    /// Get the inner [`Event`]
+    pub fn into_inner(self) -> Event {
+        self.0
+    }
+}
+
+impl Deref for VerifiedEvent {
+    type Target = Event;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<Event> for VerifiedEvent {
+    fn as_ref(&self) -> &Event {
+        &self.0
+    }
+}
+
+impl From<VerifiedEvent> for Event {
+    fn from(event: VerifiedEvent) -> Self {
+        event.0
+    }
+}
+
+impl TryFrom<Event> for VerifiedEvent {
+    type Error = Error;
+
+    fn try_from(event: Event) -> Result<Self, Self::Error> {
+        event.is_valid()?;
+        Ok(Self(event))
+    }
+}
+
+impl Event {
+    /// Verify both the [`EventId`](super::EventId) and the signature and wrap the event into a [`VerifiedEvent`]
+    pub fn into_verified(self) -> Result<VerifiedEvent, Error> {
+        VerifiedEvent::try_from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_verified_event() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("Verified", &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let verified = event.clone().into_verified().unwrap();
+        assert_eq!(verified.content, "Verified");
+        assert_eq!(verified.as_json(), event.as_json());
+        assert_eq!(verified.into_inner(), event);
+
+        // Tampered content
+        let mut tampered = event.clone();
+        tampered.content = String::from("Tampered");
+        assert!(matches!(
+            VerifiedEvent::try_from(tampered),
+            Err(Error::InvalidId)
+        ));
+
+        // Tampered content with recomputed id
+        let mut tampered = event;
+        tampered.content = String::from("Tampered");
+        tampered.id = tampered.recompute_id();
+        assert!(matches!(
+            tampered.into_verified(),
+            Err(Error::InvalidSignature)
+        ));
+    }
+}
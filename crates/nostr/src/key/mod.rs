@@ -6,11 +6,12 @@
 //!
 //! This module defines the [`Keys`] structure.
 
+use std::fmt;
 #[cfg(feature = "nip19")]
 use std::str::FromStr;
 
-use secp256k1::rand::rngs::OsRng;
-use secp256k1::rand::Rng;
+use secp256k1::rand::rngs::{OsRng, StdRng};
+use secp256k1::rand::{Rng, SeedableRng};
 use secp256k1::schnorr::Signature;
 use secp256k1::Message;
 pub use secp256k1::{KeyPair, PublicKey, SecretKey, XOnlyPublicKey};
@@ -60,13 +61,52 @@ pub trait FromPkStr: Sized {
 }
 
 /// Keys
-#[derive(Debug, Clone, Eq, PartialEq)]
+///
+/// With the `zeroize` feature, the secret key is erased from memory on drop.
+#[derive(Clone, Eq, PartialEq)]
 pub struct Keys {
     public_key: XOnlyPublicKey,
     key_pair: Option<KeyPair>,
     secret_key: Option<SecretKey>,
 }
 
+impl fmt::Debug for Keys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keys")
+            .field("public_key", &self.public_key.to_string())
+            .field(
+                "secret_key",
+                &self.secret_key.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Keys {
+    fn zeroize(&mut self) {
+        if let Some(key_pair) = self.key_pair.as_mut() {
+            key_pair.non_secure_erase();
+        }
+        if let Some(secret_key) = self.secret_key.as_mut() {
+            secret_key.non_secure_erase();
+        }
+        self.key_pair = None;
+        self.secret_key = None;
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Keys {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Keys {}
+
 impl Keys {
     /// Initialize from secret key.
     pub fn new(secret_key: SecretKey) -> Self {
@@ -120,6 +160,21 @@ impl Keys {
         }
     }
 
+    /// Init [`Keys`] from the 32 bytes of a secret key
+    pub fn from_secret_bytes(bytes: [u8; 32]) -> Result<Self, Error> {
+        let secret_key = SecretKey::from_slice(&bytes).map_err(|_| Error::InvalidSecretKey)?;
+        Ok(Self::new(secret_key))
+    }
+
+    /// Generate deterministic [`Keys`] from a seed, without [`KeyPair`]
+    ///
+    /// **Only for tests**: the same seed always gives the same keys, so never use it to generate production keys.
+    /// The output may change across `rand` versions.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let mut rng = StdRng::from_seed(seed);
+        Self::generate_without_keypair(&mut rng)
+    }
+
     /// Get [`XOnlyPublicKey`]
     pub fn public_key(&self) -> XOnlyPublicKey {
         self.public_key
@@ -174,6 +229,25 @@ impl FromSkStr for Keys {
     }
 }
 
+#[cfg(feature = "nip19")]
+impl TryFrom<&str> for Keys {
+    type Error = Error;
+
+    /// Init [`Keys`] from `hex` or `bech32` secret key (see [`FromSkStr::from_sk_str`])
+    fn try_from(secret_key: &str) -> Result<Self, Self::Error> {
+        Self::from_sk_str(secret_key)
+    }
+}
+
+impl TryFrom<[u8; 32]> for Keys {
+    type Error = Error;
+
+    /// Init [`Keys`] from secret key bytes (see [`Keys::from_secret_bytes`])
+    fn try_from(bytes: [u8; 32]) -> Result<Self, Self::Error> {
+        Self::from_secret_bytes(bytes)
+    }
+}
+
 #[cfg(feature = "nip19")]
 impl FromPkStr for Keys {
     type Err = Error;
@@ -189,3 +263,78 @@ impl FromPkStr for Keys {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "nip19")]
+    #[test]
+    fn test_try_from_str() {
+        use crate::nips::nip19::ToBech32;
+
+        let keys = Keys::from_seed([7; 32]);
+        let secret_key: SecretKey = keys.secret_key().unwrap();
+        let hex: String = secret_key.display_secret().to_string();
+        let bech32: String = secret_key.to_bech32().unwrap();
+
+        assert_eq!(
+            Keys::try_from(hex.as_str()).unwrap().secret_key(),
+            Ok(secret_key)
+        );
+        assert_eq!(
+            Keys::try_from(bech32.as_str()).unwrap().secret_key(),
+            Ok(secret_key)
+        );
+        assert_eq!(Keys::try_from("invalid"), Err(Error::InvalidSecretKey));
+    }
+
+    #[test]
+    fn test_try_from_bytes() {
+        let keys = Keys::from_seed([7; 32]);
+        let secret_key: SecretKey = keys.secret_key().unwrap();
+
+        let from_bytes = Keys::try_from(secret_key.secret_bytes()).unwrap();
+        assert_eq!(from_bytes.public_key(), keys.public_key());
+        assert_eq!(from_bytes.secret_key(), Ok(secret_key));
+
+        // Zero is not a valid secret key
+        assert_eq!(Keys::try_from([0u8; 32]), Err(Error::InvalidSecretKey));
+    }
+
+    #[test]
+    fn test_debug_hides_secret_key() {
+        let keys = Keys::from_seed([7; 32]);
+        let secret_hex: String = keys.secret_key().unwrap().display_secret().to_string();
+        let debug: String = format!("{keys:?}");
+        assert!(debug.contains(&keys.public_key().to_string()));
+        assert!(!debug.contains(&secret_hex));
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut keys = Keys::from_seed([7; 32]);
+        keys.zeroize();
+        assert_eq!(keys.secret_key(), Err(Error::SkMissing));
+    }
+
+    #[test]
+    fn test_from_seed() {
+        let keys = Keys::from_seed([7; 32]);
+        assert_eq!(keys, Keys::from_seed([7; 32]));
+        assert_ne!(keys.public_key(), Keys::from_seed([8; 32]).public_key());
+
+        #[cfg(feature = "nip19")]
+        {
+            use crate::nips::nip19::ToBech32;
+            assert_eq!(
+                keys.public_key().to_bech32().unwrap(),
+                Keys::from_seed([7; 32]).public_key().to_bech32().unwrap()
+            );
+        }
+    }
+}
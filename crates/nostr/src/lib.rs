@@ -32,10 +32,15 @@ pub mod nips;
 pub mod prelude;
 pub mod types;
 
-pub use self::event::{Event, EventBuilder, EventId, Kind, Tag, UnsignedEvent};
+pub use self::event::{
+    Event, EventBuilder, EventId, Kind, RenderContext, Tag, UnsignedEvent, VerifiedEvent,
+};
 pub use self::key::Keys;
 pub use self::message::{ClientMessage, Filter, RelayMessage, SubscriptionId};
-pub use self::types::{ChannelId, Contact, Entity, Metadata, Profile, Timestamp};
+pub use self::types::{
+    ChannelId, Contact, ContactList, Entity, Geohash, Metadata, Profile, RelayPermissions,
+    Timestamp, UncheckedUrl,
+};
 
 /// Result
 pub type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
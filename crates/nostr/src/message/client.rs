@@ -26,6 +26,19 @@ pub enum ClientMessage {
     Close(SubscriptionId),
     /// Auth
     Auth(Box<Event>),
+    /// Negentropy open (NIP77)
+    NegOpen {
+        subscription_id: SubscriptionId,
+        filter: Box<Filter>,
+        initial_message: String,
+    },
+    /// Negentropy message (NIP77)
+    NegMsg {
+        subscription_id: SubscriptionId,
+        message: String,
+    },
+    /// Negentropy close (NIP77)
+    NegClose { subscription_id: SubscriptionId },
 }
 
 impl Serialize for ClientMessage {
@@ -72,6 +85,43 @@ impl ClientMessage {
         Self::Auth(Box::new(event))
     }
 
+    /// Create new `AUTH` message
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/42.md>
+    pub fn auth(event: Event) -> Self {
+        Self::new_auth(event)
+    }
+
+    /// Create new `NEG-OPEN` message
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/77.md>
+    pub fn neg_open<S>(subscription_id: SubscriptionId, filter: Filter, initial_message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::NegOpen {
+            subscription_id,
+            filter: Box::new(filter),
+            initial_message: initial_message.into(),
+        }
+    }
+
+    /// Create new `NEG-MSG` message
+    pub fn neg_msg<S>(subscription_id: SubscriptionId, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::NegMsg {
+            subscription_id,
+            message: message.into(),
+        }
+    }
+
+    /// Create new `NEG-CLOSE` message
+    pub fn neg_close(subscription_id: SubscriptionId) -> Self {
+        Self::NegClose { subscription_id }
+    }
+
     /// Serialize as [`Value`]
     pub fn as_value(&self) -> Value {
         match self {
@@ -93,6 +143,16 @@ impl ClientMessage {
             }
             Self::Close(subscription_id) => json!(["CLOSE", subscription_id]),
             Self::Auth(event) => json!(["AUTH", event]),
+            Self::NegOpen {
+                subscription_id,
+                filter,
+                initial_message,
+            } => json!(["NEG-OPEN", subscription_id, filter, initial_message]),
+            Self::NegMsg {
+                subscription_id,
+                message,
+            } => json!(["NEG-MSG", subscription_id, message]),
+            Self::NegClose { subscription_id } => json!(["NEG-CLOSE", subscription_id]),
         }
     }
 
@@ -102,65 +162,63 @@ impl ClientMessage {
     }
 
     /// Deserialize from [`Value`]
+    ///
+    /// The command must be one of the known ones and the array must have its exact arity,
+    /// otherwise [`MessageHandleError::InvalidMessageFormat`] is returned.
     pub fn from_value(msg: Value) -> Result<Self, MessageHandleError> {
         let v = msg
             .as_array()
             .ok_or(MessageHandleError::InvalidMessageFormat)?;
 
-        if v.is_empty() {
-            return Err(MessageHandleError::InvalidMessageFormat);
-        }
+        let command: &str = v
+            .first()
+            .and_then(|c| c.as_str())
+            .ok_or(MessageHandleError::InvalidMessageFormat)?;
 
         let v_len: usize = v.len();
 
-        // Event
-        // ["EVENT", <event JSON>]
-        if v[0] == "EVENT" {
-            if v_len != 2 {
-                return Err(MessageHandleError::InvalidMessageFormat);
+        match command {
+            // ["EVENT", <event JSON>]
+            "EVENT" if v_len == 2 => {
+                let event = Event::from_json(v[1].to_string())?;
+                Ok(Self::new_event(event))
             }
-            let event = Event::from_json(v[1].to_string())?;
-            return Ok(Self::new_event(event));
-        }
-
-        // Req
-        // ["REQ", <subscription_id>, <filter JSON>, <filter JSON>...]
-        if v[0] == "REQ" {
-            if v_len == 2 {
-                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
-                return Ok(Self::new_req(subscription_id, Vec::new()));
-            } else if v_len >= 3 {
+            // ["REQ", <subscription_id>, <filter JSON>, <filter JSON>...]
+            "REQ" if v_len >= 2 => {
                 let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
                 let filters: Vec<Filter> = serde_json::from_value(Value::Array(v[2..].to_vec()))?;
-                return Ok(Self::new_req(subscription_id, filters));
-            } else {
-                return Err(MessageHandleError::InvalidMessageFormat);
+                Ok(Self::new_req(subscription_id, filters))
             }
-        }
-
-        // Close
-        // ["CLOSE", <subscription_id>]
-        if v[0] == "CLOSE" {
-            if v_len != 2 {
-                return Err(MessageHandleError::InvalidMessageFormat);
+            // ["CLOSE", <subscription_id>]
+            "CLOSE" if v_len == 2 => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                Ok(Self::close(subscription_id))
             }
-
-            let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
-
-            return Ok(Self::close(subscription_id));
-        }
-
-        // Auth
-        // ["AUTH", <event JSON>]
-        if v[0] == "AUTH" {
-            if v_len != 2 {
-                return Err(MessageHandleError::InvalidMessageFormat);
+            // ["AUTH", <event JSON>]
+            "AUTH" if v_len == 2 => {
+                let event = Event::from_json(v[1].to_string())?;
+                Ok(Self::new_auth(event))
+            }
+            // ["NEG-OPEN", <subscription_id>, <filter JSON>, <initial message>] (NIP-77)
+            "NEG-OPEN" if v_len == 4 => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                let filter: Filter = serde_json::from_value(v[2].clone())?;
+                let initial_message: String = serde_json::from_value(v[3].clone())?;
+                Ok(Self::neg_open(subscription_id, filter, initial_message))
+            }
+            // ["NEG-MSG", <subscription_id>, <message>] (NIP-77)
+            "NEG-MSG" if v_len == 3 => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                let message: String = serde_json::from_value(v[2].clone())?;
+                Ok(Self::neg_msg(subscription_id, message))
             }
-            let event = Event::from_json(v[1].to_string())?;
-            return Ok(Self::new_auth(event));
+            // ["NEG-CLOSE", <subscription_id>] (NIP-77)
+            "NEG-CLOSE" if v_len == 2 => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                Ok(Self::neg_close(subscription_id))
+            }
+            _ => Err(MessageHandleError::InvalidMessageFormat),
         }
-
-        Err(MessageHandleError::InvalidMessageFormat)
     }
 
     /// Deserialize [`ClientMessage`] from JSON string
@@ -188,7 +246,7 @@ mod tests {
 
     use secp256k1::XOnlyPublicKey;
 
-    use crate::Kind;
+    use crate::{EventBuilder, Keys, Kind, Tag, Url};
 
     #[test]
     fn test_client_message_req() {
@@ -248,4 +306,73 @@ mod tests {
 
         assert_eq!(msg.as_value(), req)
     }
+
+    #[test]
+    fn test_client_message_auth() {
+        let keys = Keys::generate();
+        let relay_url = Url::parse("wss://relay.example.com").unwrap();
+        let event = EventBuilder::auth("challenge-string", relay_url.clone())
+            .to_event(&keys)
+            .unwrap();
+
+        let msg = ClientMessage::auth(event.clone());
+        assert_eq!(msg.as_value(), json!(["AUTH", event]));
+
+        let event = match ClientMessage::from_json(msg.as_json()).unwrap() {
+            ClientMessage::Auth(event) => event,
+            _ => panic!("Expected AUTH message"),
+        };
+        assert_eq!(event.kind, Kind::Authentication);
+        assert!(event
+            .tags
+            .contains(&Tag::Challenge(String::from("challenge-string"))));
+        assert!(event.tags.contains(&Tag::Relay(relay_url)));
+    }
+
+    #[test]
+    fn test_strict_arity() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("test", &[])
+            .to_event(&keys)
+            .unwrap()
+            .as_json();
+
+        let valid: Vec<String> = vec![
+            format!(r#"["EVENT",{event}]"#),
+            String::from(r#"["REQ","sub",{"kinds":[1]},{"limit":10}]"#),
+            String::from(r#"["CLOSE","sub"]"#),
+            format!(r#"["AUTH",{event}]"#),
+            String::from(r#"["NEG-OPEN","sub",{"kinds":[1]},"6100"]"#),
+            String::from(r#"["NEG-MSG","sub","6100"]"#),
+            String::from(r#"["NEG-CLOSE","sub"]"#),
+        ];
+        for msg in valid.iter() {
+            let parsed = ClientMessage::from_json(msg.as_str()).unwrap();
+            assert_eq!(ClientMessage::from_json(parsed.as_json()).unwrap(), parsed);
+        }
+
+        let invalid: Vec<String> = vec![
+            String::from(r#"["EVENT"]"#),
+            format!(r#"["EVENT",{event},"extra"]"#),
+            String::from(r#"["REQ"]"#),
+            String::from(r#"["CLOSE"]"#),
+            String::from(r#"["CLOSE","sub","extra"]"#),
+            String::from(r#"["AUTH"]"#),
+            String::from(r#"["NEG-OPEN","sub",{"kinds":[1]}]"#),
+            String::from(r#"["NEG-MSG","sub"]"#),
+            String::from(r#"["NEG-CLOSE","sub","extra"]"#),
+            String::from(r#"["UNKNOWN","sub"]"#),
+            String::from(r#"[null,"sub"]"#),
+            String::from(r#"[]"#),
+        ];
+        for msg in invalid.into_iter() {
+            assert!(
+                matches!(
+                    ClientMessage::from_json(msg.as_str()),
+                    Err(MessageHandleError::InvalidMessageFormat)
+                ),
+                "{msg}"
+            );
+        }
+    }
 }
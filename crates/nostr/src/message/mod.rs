@@ -8,7 +8,7 @@ pub mod relay;
 pub mod subscription;
 
 pub use self::client::ClientMessage;
-pub use self::relay::RelayMessage;
+pub use self::relay::{RelayMessage, RelayMessageCategory, RelayMessageLimits};
 pub use self::subscription::{Filter, SubscriptionId};
 
 /// Messages error
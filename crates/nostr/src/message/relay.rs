@@ -4,13 +4,60 @@
 
 //! Relay messages
 
-use serde::de::Error;
+use std::cell::Cell;
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Number, Value};
 
 use super::MessageHandleError;
 use crate::{Event, EventId, SubscriptionId};
 
+/// Default max size (bytes) of a [`RelayMessage`]
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 5 * 1024 * 1024;
+/// Default max length of the arrays (i.e. tags) of a [`RelayMessage`]
+pub const DEFAULT_MAX_ARRAY_LEN: usize = 10_000;
+
+/// Limits applied when parsing untrusted [`RelayMessage`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayMessageLimits {
+    /// Max size (bytes) of the JSON message
+    pub max_size: usize,
+    /// Max length of each JSON array (i.e. the event tags)
+    pub max_array_len: usize,
+}
+
+impl Default for RelayMessageLimits {
+    fn default() -> Self {
+        Self {
+            max_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_array_len: DEFAULT_MAX_ARRAY_LEN,
+        }
+    }
+}
+
+/// High-level category of a [`RelayMessage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelayMessageCategory {
+    /// Stored event (received before the `EOSE` of its subscription)
+    StoredEvent,
+    /// Live event (received after the `EOSE` of its subscription)
+    LiveEvent,
+    /// End of stored events
+    EndOfStoredEvents,
+    /// Event accepted by the relay (`OK` with `true` status)
+    Ack,
+    /// Event rejected by the relay (`OK` with `false` status) or `NOTICE`
+    Error,
+    /// Authentication challenge
+    Auth,
+    /// Negentropy reconciliation message
+    Negentropy,
+    /// Empty message
+    Empty,
+}
+
 /// Messages sent by relays, received by clients
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -31,6 +78,16 @@ pub enum RelayMessage {
     Auth {
         challenge: String,
     },
+    /// Negentropy message (NIP77)
+    NegMsg {
+        subscription_id: SubscriptionId,
+        message: String,
+    },
+    /// Negentropy error (NIP77)
+    NegErr {
+        subscription_id: SubscriptionId,
+        code: String,
+    },
     Empty,
 }
 
@@ -100,6 +157,30 @@ impl RelayMessage {
         }
     }
 
+    /// Create new `NEG-MSG` message
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/77.md>
+    pub fn neg_msg<S>(subscription_id: SubscriptionId, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::NegMsg {
+            subscription_id,
+            message: message.into(),
+        }
+    }
+
+    /// Create new `NEG-ERR` message
+    pub fn neg_err<S>(subscription_id: SubscriptionId, code: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::NegErr {
+            subscription_id,
+            code: code.into(),
+        }
+    }
+
     fn as_value(&self) -> Value {
         match self {
             Self::Event {
@@ -116,6 +197,14 @@ impl RelayMessage {
                 message,
             } => json!(["OK", event_id, status, message]),
             Self::Auth { challenge } => json!(["AUTH", challenge]),
+            Self::NegMsg {
+                subscription_id,
+                message,
+            } => json!(["NEG-MSG", subscription_id, message]),
+            Self::NegErr {
+                subscription_id,
+                code,
+            } => json!(["NEG-ERR", subscription_id, code]),
             Self::Empty => Value::Null,
         }
     }
@@ -125,97 +214,234 @@ impl RelayMessage {
         self.as_value().to_string()
     }
 
+    /// Classify [`RelayMessage`] into a [`RelayMessageCategory`]
+    ///
+    /// `eose_received` tells if the `EOSE` of the event subscription was already received,
+    /// to distinguish stored events from live ones.
+    pub fn category(&self, eose_received: bool) -> RelayMessageCategory {
+        match self {
+            Self::Event { .. } => {
+                if eose_received {
+                    RelayMessageCategory::LiveEvent
+                } else {
+                    RelayMessageCategory::StoredEvent
+                }
+            }
+            Self::EndOfStoredEvents(..) => RelayMessageCategory::EndOfStoredEvents,
+            Self::Ok { status: true, .. } => RelayMessageCategory::Ack,
+            Self::Ok { status: false, .. } | Self::Notice { .. } => RelayMessageCategory::Error,
+            Self::Auth { .. } => RelayMessageCategory::Auth,
+            Self::NegMsg { .. } => RelayMessageCategory::Negentropy,
+            Self::NegErr { .. } => RelayMessageCategory::Error,
+            Self::Empty => RelayMessageCategory::Empty,
+        }
+    }
+
     /// Deserialize [`RelayMessage`] from [`Value`]
-    fn from_value(msg: Value) -> Result<Self, MessageHandleError> {
+    ///
+    /// The command must be one of the known ones and the array must have its exact arity,
+    /// otherwise [`MessageHandleError::InvalidMessageFormat`] is returned.
+    pub fn from_value(msg: Value) -> Result<Self, MessageHandleError> {
         let v = msg
             .as_array()
             .ok_or(MessageHandleError::InvalidMessageFormat)?;
 
-        if v.is_empty() {
+        let command: &str = v
+            .first()
+            .and_then(|c| c.as_str())
+            .ok_or(MessageHandleError::InvalidMessageFormat)?;
+
+        let arity: usize = match command {
+            // ["NOTICE", <message>]
+            "NOTICE" => 2,
+            // ["EVENT", <subscription id>, <event JSON>]
+            "EVENT" => 3,
+            // ["EOSE", <subscription_id>] (NIP-15)
+            "EOSE" => 2,
+            // ["OK", <event_id>, <true|false>, <message>] (NIP-20)
+            "OK" => 4,
+            // ["AUTH", <challenge>] (NIP-42)
+            "AUTH" => 2,
+            // ["NEG-MSG", <subscription_id>, <message>] (NIP-77)
+            "NEG-MSG" => 3,
+            // ["NEG-ERR", <subscription_id>, <code>] (NIP-77)
+            "NEG-ERR" => 3,
+            _ => return Err(MessageHandleError::InvalidMessageFormat),
+        };
+
+        if v.len() != arity {
             return Err(MessageHandleError::InvalidMessageFormat);
         }
 
-        let v_len: usize = v.len();
-
-        // Notice
-        // Relay response format: ["NOTICE", <message>]
-        if v[0] == "NOTICE" {
-            if v_len != 2 {
-                return Err(MessageHandleError::InvalidMessageFormat);
+        match command {
+            "NOTICE" => {
+                let message: String = serde_json::from_value(v[1].clone())?;
+                Ok(Self::Notice { message })
+            }
+            "EVENT" => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                let event = Event::from_json(v[2].to_string())?;
+                Ok(Self::new_event(subscription_id, event))
+            }
+            "EOSE" => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                Ok(Self::new_eose(subscription_id))
             }
-            let v_notice: String = serde_json::from_value(v[1].clone())?;
-            return Ok(Self::Notice { message: v_notice });
+            "OK" => {
+                let event_id: EventId = serde_json::from_value(v[1].clone())?;
+                let status: bool = serde_json::from_value(v[2].clone())?;
+                let message: String = serde_json::from_value(v[3].clone())?;
+                Ok(Self::new_ok(event_id, status, message))
+            }
+            "AUTH" => {
+                let challenge: String = serde_json::from_value(v[1].clone())?;
+                Ok(Self::Auth { challenge })
+            }
+            "NEG-MSG" => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                let message: String = serde_json::from_value(v[2].clone())?;
+                Ok(Self::neg_msg(subscription_id, message))
+            }
+            "NEG-ERR" => {
+                let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+                let code: String = serde_json::from_value(v[2].clone())?;
+                Ok(Self::neg_err(subscription_id, code))
+            }
+            _ => Err(MessageHandleError::InvalidMessageFormat),
         }
+    }
 
-        // Event
-        // Relay response format: ["EVENT", <subscription id>, <event JSON>]
-        if v[0] == "EVENT" {
-            if v_len != 3 {
-                return Err(MessageHandleError::InvalidMessageFormat);
-            }
+    /// Deserialize [`RelayMessage`] as JSON string
+    ///
+    /// The [`RelayMessageLimits::default`] limits are applied.
+    pub fn from_json<S>(msg: S) -> Result<Self, MessageHandleError>
+    where
+        S: Into<String>,
+    {
+        Self::from_json_with_limits(msg, RelayMessageLimits::default())
+    }
 
-            let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
-            let event = Event::from_json(v[2].to_string())?;
+    /// Deserialize [`RelayMessage`] as JSON string applying custom [`RelayMessageLimits`]
+    ///
+    /// Return [`MessageHandleError::InvalidMessageFormat`] as soon as a limit is exceeded,
+    /// without materializing the whole message.
+    pub fn from_json_with_limits<S>(
+        msg: S,
+        limits: RelayMessageLimits,
+    ) -> Result<Self, MessageHandleError>
+    where
+        S: Into<String>,
+    {
+        let msg: &str = &msg.into();
+        log::trace!("{}", msg);
 
-            return Ok(Self::new_event(subscription_id, event));
+        if msg.is_empty() {
+            return Ok(Self::Empty);
         }
 
-        // EOSE (NIP-15)
-        // Relay response format: ["EOSE", <subscription_id>]
-        if v[0] == "EOSE" {
-            if v_len != 2 {
-                return Err(MessageHandleError::InvalidMessageFormat);
-            }
+        if msg.len() > limits.max_size {
+            return Err(MessageHandleError::InvalidMessageFormat);
+        }
 
-            let subscription_id: SubscriptionId = serde_json::from_value(v[1].clone())?;
+        let exceeded: Cell<bool> = Cell::new(false);
+        let seed = BoundedValue {
+            max_array_len: limits.max_array_len,
+            exceeded: &exceeded,
+        };
+        let mut deserializer = serde_json::Deserializer::from_str(msg);
+        let value: Value = match seed.deserialize(&mut deserializer) {
+            Ok(value) => value,
+            Err(_) if exceeded.get() => return Err(MessageHandleError::InvalidMessageFormat),
+            Err(e) => return Err(e.into()),
+        };
+        deserializer.end()?;
 
-            return Ok(Self::new_eose(subscription_id));
-        }
+        Self::from_value(value)
+    }
+}
 
-        // OK (NIP-20)
-        // Relay response format: ["OK", <event_id>, <true|false>, <message>]
-        if v[0] == "OK" {
-            if v_len != 4 {
-                return Err(MessageHandleError::InvalidMessageFormat);
-            }
+/// Deserialize a [`Value`] failing as soon as an array exceeds `max_array_len`
+#[derive(Clone, Copy)]
+struct BoundedValue<'a> {
+    max_array_len: usize,
+    exceeded: &'a Cell<bool>,
+}
 
-            let event_id: EventId = serde_json::from_value(v[1].clone())?;
+impl<'de, 'a> DeserializeSeed<'de> for BoundedValue<'a> {
+    type Value = Value;
 
-            let status: bool = serde_json::from_value(v[2].clone())?;
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
 
-            let message: String = serde_json::from_value(v[3].clone())?;
+impl<'de, 'a> Visitor<'de> for BoundedValue<'a> {
+    type Value = Value;
 
-            return Ok(Self::new_ok(event_id, status, message));
-        }
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a JSON value")
+    }
 
-        // OK (NIP-42)
-        // Relay response format: ["AUTH", <challenge>]
-        if v[0] == "AUTH" {
-            if v_len != 2 {
-                return Err(MessageHandleError::InvalidMessageFormat);
-            }
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
 
-            let challenge: String = serde_json::from_value(v[1].clone())?;
-            return Ok(Self::Auth { challenge });
-        }
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Number(v.into()))
+    }
 
-        Err(MessageHandleError::InvalidMessageFormat)
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Number(v.into()))
     }
 
-    /// Deserialize [`RelayMessage`] as JSON string
-    pub fn from_json<S>(msg: S) -> Result<Self, MessageHandleError>
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(v).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
     where
-        S: Into<String>,
+        A: SeqAccess<'de>,
     {
-        let msg: &str = &msg.into();
-        log::trace!("{}", msg);
-
-        if msg.is_empty() {
-            return Ok(Self::Empty);
+        let mut vec: Vec<Value> = Vec::new();
+        while let Some(value) = seq.next_element_seed(self)? {
+            if vec.len() >= self.max_array_len {
+                self.exceeded.set(true);
+                return Err(A::Error::custom("array too long"));
+            }
+            vec.push(value);
         }
+        Ok(Value::Array(vec))
+    }
 
-        let value: Value = serde_json::from_str(msg)?;
-        Self::from_value(value)
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut values: Map<String, Value> = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value: Value = map.next_value_seed(self)?;
+            values.insert(key, value);
+        }
+        Ok(Value::Object(values))
     }
 }
 
@@ -367,4 +593,133 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_message_limits() {
+        let tags: Vec<String> = (0..100).map(|i| format!(r#"["t","tag{i}"]"#)).collect();
+        let msg = format!(
+            r#"["EVENT","random_string",{{"id":"70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5","pubkey":"379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe","created_at":1612809991,"kind":1,"tags":[{}],"content":"test","sig":"273a9cd5d11455590f4359500bccb7a89428262b96b3ea87a756b770964472f8c3e87f5d5e64d8d2e859a71462a3f477b554565c4f2f326cb01dd7620db71502"}}]"#,
+            tags.join(",")
+        );
+
+        // Too many tags
+        let limits = RelayMessageLimits {
+            max_array_len: 50,
+            ..Default::default()
+        };
+        assert!(matches!(
+            RelayMessage::from_json_with_limits(msg.clone(), limits),
+            Err(MessageHandleError::InvalidMessageFormat)
+        ));
+
+        // Too big
+        let limits = RelayMessageLimits {
+            max_size: 100,
+            ..Default::default()
+        };
+        assert!(matches!(
+            RelayMessage::from_json_with_limits(msg.clone(), limits),
+            Err(MessageHandleError::InvalidMessageFormat)
+        ));
+
+        // Within limits: parsed (the id doesn't match, so the event is rejected)
+        assert!(matches!(
+            RelayMessage::from_json(msg),
+            Err(MessageHandleError::Event(_))
+        ));
+
+        // Malformed JSON is still reported as JSON error
+        assert!(matches!(
+            RelayMessage::from_json(r#"["NOTICE", "#),
+            Err(MessageHandleError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_strict_arity() {
+        let event = r#"{"id":"70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5","pubkey":"379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe","created_at":1612809991,"kind":1,"tags":[],"content":"test","sig":"273a9cd5d11455590f4359500bccb7a89428262b96b3ea87a756b770964472f8c3e87f5d5e64d8d2e859a71462a3f477b554565c4f2f326cb01dd7620db71502"}"#;
+        let event_id = "b1a649ebe8b435ec71d3784793f3bbf4b93e64e17568a741aecd4c7ddeafce30";
+
+        let valid: Vec<String> = vec![
+            String::from(r#"["NOTICE","message"]"#),
+            format!(r#"["EVENT","sub",{event}]"#),
+            String::from(r#"["EOSE","sub"]"#),
+            format!(r#"["OK","{event_id}",true,""]"#),
+            String::from(r#"["AUTH","challenge"]"#),
+            String::from(r#"["NEG-MSG","sub","6100"]"#),
+            String::from(r#"["NEG-ERR","sub","blocked: too many records"]"#),
+        ];
+        for msg in valid.iter() {
+            let parsed = RelayMessage::from_json(msg.as_str()).unwrap();
+            assert_eq!(RelayMessage::from_json(parsed.as_json()).unwrap(), parsed);
+        }
+
+        let invalid: Vec<String> = vec![
+            String::from(r#"["NOTICE"]"#),
+            String::from(r#"["NOTICE","message","extra"]"#),
+            String::from(r#"["EVENT","sub"]"#),
+            format!(r#"["EVENT","sub",{event},"extra"]"#),
+            String::from(r#"["EOSE"]"#),
+            String::from(r#"["EOSE","sub","extra"]"#),
+            format!(r#"["OK","{event_id}",true]"#),
+            format!(r#"["OK","{event_id}",true,"",""]"#),
+            String::from(r#"["AUTH"]"#),
+            String::from(r#"["AUTH","challenge","extra"]"#),
+            String::from(r#"["NEG-MSG","sub"]"#),
+            String::from(r#"["NEG-ERR","sub","code","extra"]"#),
+            String::from(r#"["UNKNOWN","value"]"#),
+            String::from(r#"[1,"value"]"#),
+            String::from(r#"[]"#),
+            String::from(r#"{}"#),
+        ];
+        for msg in invalid.into_iter() {
+            assert!(
+                matches!(
+                    RelayMessage::from_json(msg.as_str()),
+                    Err(MessageHandleError::InvalidMessageFormat)
+                ),
+                "{msg}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_category() -> Result<()> {
+        let event = Event::from_json(
+            r#"{"id":"70b10f70c1318967eddf12527799411b1a9780ad9c43858f5e5fcd45486a13a5","pubkey":"379e863e8357163b5bce5d2688dc4f1dcc2d505222fb8d74db600f30535dfdfe","created_at":1612809991,"kind":1,"tags":[],"content":"test","sig":"273a9cd5d11455590f4359500bccb7a89428262b96b3ea87a756b770964472f8c3e87f5d5e64d8d2e859a71462a3f477b554565c4f2f326cb01dd7620db71502"}"#,
+        )?;
+        let subscription_id = SubscriptionId::new("test");
+
+        let msg = RelayMessage::new_event(subscription_id.clone(), event.clone());
+        assert_eq!(msg.category(false), RelayMessageCategory::StoredEvent);
+        assert_eq!(msg.category(true), RelayMessageCategory::LiveEvent);
+
+        let msg = RelayMessage::new_eose(subscription_id);
+        assert_eq!(msg.category(false), RelayMessageCategory::EndOfStoredEvents);
+
+        let msg = RelayMessage::new_ok(event.id, true, "");
+        assert_eq!(msg.category(false), RelayMessageCategory::Ack);
+
+        let msg = RelayMessage::new_ok(event.id, false, "blocked: spam");
+        assert_eq!(msg.category(false), RelayMessageCategory::Error);
+
+        let msg = RelayMessage::new_notice("rate limited");
+        assert_eq!(msg.category(false), RelayMessageCategory::Error);
+
+        let msg = RelayMessage::new_auth("challenge");
+        assert_eq!(msg.category(false), RelayMessageCategory::Auth);
+
+        let msg = RelayMessage::neg_msg(SubscriptionId::new("neg"), "6100");
+        assert_eq!(msg.category(false), RelayMessageCategory::Negentropy);
+
+        let msg = RelayMessage::neg_err(SubscriptionId::new("neg"), "closed: timeout");
+        assert_eq!(msg.category(false), RelayMessageCategory::Error);
+
+        assert_eq!(
+            RelayMessage::Empty.category(false),
+            RelayMessageCategory::Empty
+        );
+
+        Ok(())
+    }
 }
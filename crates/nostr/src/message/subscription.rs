@@ -6,7 +6,9 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::fmt;
+use std::str::FromStr;
 
 use bitcoin_hashes::sha256::Hash as Sha256Hash;
 use bitcoin_hashes::Hash;
@@ -17,9 +19,9 @@ use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
 use serde::ser::{Serialize, SerializeMap, Serializer};
 use serde_json::{json, Map, Value};
 
-use crate::{EventId, Kind, Timestamp};
+use crate::{Event, EventId, Kind, Timestamp};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct SubscriptionId(String);
 
 impl SubscriptionId {
@@ -58,11 +60,17 @@ pub struct Filter {
     pub hashtags: Option<Vec<String>>,
     /// #r tag
     pub references: Option<Vec<String>>,
+    /// #d tag
+    pub identifiers: Option<Vec<String>>,
     pub search: Option<String>,
     pub since: Option<Timestamp>,
     pub until: Option<Timestamp>,
     pub limit: Option<usize>,
+    /// Generic single-letter tag filters (`#<letter>`), except the ones with a dedicated field
+    pub generic_tags: BTreeMap<char, Vec<String>>,
     pub custom: Map<String, Value>,
+    /// Authors to exclude (client-side only, never sent to relays)
+    pub exclude_authors: Option<Vec<XOnlyPublicKey>>,
 }
 
 impl Default for Filter {
@@ -81,14 +89,31 @@ impl Filter {
             pubkeys: None,
             hashtags: None,
             references: None,
+            identifiers: None,
             search: None,
             since: None,
             until: None,
             limit: None,
+            generic_tags: BTreeMap::new(),
             custom: Map::new(),
+            exclude_authors: None,
         }
     }
 
+    /// Check if [`Filter`] is empty (no constraints, match everything)
+    pub fn is_empty(&self) -> bool {
+        self == &Filter::default()
+    }
+
+    /// Check if [`Filter`] constrains only the `limit`
+    pub fn is_limit_only(&self) -> bool {
+        let filter = Filter {
+            limit: None,
+            ..self.clone()
+        };
+        self.limit.is_some() && filter.is_empty()
+    }
+
     /// Deserialize from `JSON` string
     pub fn from_json<S>(json: S) -> Result<Self, serde_json::Error>
     where
@@ -103,6 +128,8 @@ impl Filter {
     }
 
     /// Set event id or prefix
+    ///
+    /// Accepts both an [`EventId`] and an hex prefix. Full 64-char ids are the common case.
     pub fn id(self, id: impl Into<String>) -> Self {
         Self {
             ids: Some(vec![id.into()]),
@@ -111,9 +138,15 @@ impl Filter {
     }
 
     /// Set event ids or prefixes
-    pub fn ids(self, ids: impl Into<Vec<String>>) -> Self {
+    ///
+    /// Accepts both [`EventId`]s and hex prefixes. Full 64-char ids are the common case.
+    pub fn ids<I, T>(self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
         Self {
-            ids: Some(ids.into()),
+            ids: Some(ids.into_iter().map(|id| id.into()).collect()),
             ..self
         }
     }
@@ -182,42 +215,53 @@ impl Filter {
         }
     }
 
-    /// Set hashtag
+    /// Add hashtag (`#t` tag)
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/12.md>
     pub fn hashtag(self, hashtag: impl Into<String>) -> Self {
-        Self {
-            hashtags: Some(vec![hashtag.into()]),
-            ..self
-        }
+        self.hashtags(vec![hashtag.into()])
     }
 
-    /// Set hashtags
+    /// Add hashtags (`#t` tag) to the existing ones
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/12.md>
     pub fn hashtags(self, hashtags: impl Into<Vec<String>>) -> Self {
         Self {
-            hashtags: Some(hashtags.into()),
+            hashtags: Some(extend_unique(self.hashtags, hashtags.into())),
             ..self
         }
     }
 
-    /// Set reference
+    /// Add reference (`#r` tag)
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/12.md>
     pub fn reference(self, v: impl Into<String>) -> Self {
+        self.references(vec![v.into()])
+    }
+
+    /// Add references (`#r` tag) to the existing ones
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/12.md>
+    pub fn references(self, v: impl Into<Vec<String>>) -> Self {
         Self {
-            references: Some(vec![v.into()]),
+            references: Some(extend_unique(self.references, v.into())),
             ..self
         }
     }
 
-    /// Set references
+    /// Add identifier (`#d` tag)
     ///
-    /// <https://github.com/nostr-protocol/nips/blob/master/12.md>
-    pub fn references(self, v: impl Into<Vec<String>>) -> Self {
+    /// <https://github.com/nostr-protocol/nips/blob/master/33.md>
+    pub fn identifier(self, identifier: impl Into<String>) -> Self {
+        self.identifiers(vec![identifier.into()])
+    }
+
+    /// Add identifiers (`#d` tag) to the existing ones
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/33.md>
+    pub fn identifiers(self, identifiers: impl Into<Vec<String>>) -> Self {
         Self {
-            references: Some(v.into()),
+            identifiers: Some(extend_unique(self.identifiers, identifiers.into())),
             ..self
         }
     }
@@ -250,6 +294,9 @@ impl Filter {
     }
 
     /// Set limit
+    ///
+    /// `0` is sent as `"limit":0`: most relays return no stored events and only stream the new ones.
+    /// Use [`Filter::no_limit`] to not send the `limit` at all.
     pub fn limit(self, limit: usize) -> Self {
         Self {
             limit: Some(limit),
@@ -257,6 +304,63 @@ impl Filter {
         }
     }
 
+    /// Remove the limit (the `limit` field is omitted)
+    pub fn no_limit(self) -> Self {
+        Self {
+            limit: None,
+            ..self
+        }
+    }
+
+    /// Add values to a generic single-letter tag filter (`#<tag>`)
+    ///
+    /// The letters with a dedicated field (`e`, `p`, `t`, `r` and `d`) are added to that field
+    /// (invalid event ids and public keys are skipped).
+    /// A `tag` that isn't an ASCII letter is invalid (NIP-12) and is ignored.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/12.md>
+    pub fn custom_tag<S>(self, tag: char, values: Vec<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        let values: Vec<String> = values.into_iter().map(|v| v.into()).collect();
+        match tag {
+            'e' => Self {
+                events: Some(extend_unique(
+                    self.events,
+                    values
+                        .into_iter()
+                        .filter_map(|v| EventId::from_hex(v).ok())
+                        .collect(),
+                )),
+                ..self
+            },
+            'p' => Self {
+                pubkeys: Some(extend_unique(
+                    self.pubkeys,
+                    values
+                        .into_iter()
+                        .filter_map(|v| XOnlyPublicKey::from_str(&v).ok())
+                        .collect(),
+                )),
+                ..self
+            },
+            't' => self.hashtags(values),
+            'r' => self.references(values),
+            'd' => self.identifiers(values),
+            tag if tag.is_ascii_alphabetic() => {
+                let mut generic_tags = self.generic_tags;
+                let current = generic_tags.remove(&tag);
+                generic_tags.insert(tag, extend_unique(current, values));
+                Self {
+                    generic_tags,
+                    ..self
+                }
+            }
+            _ => self,
+        }
+    }
+
     /// Set custom filters
     pub fn custom(self, map: Map<String, Value>) -> Self {
         Self {
@@ -264,6 +368,250 @@ impl Filter {
             ..self
         }
     }
+
+    /// Exclude events of these authors
+    ///
+    /// Relays can't express this constraint: it's not serialized and it's applied client-side
+    /// (see [`Filter::matches_event`]).
+    pub fn exclude_authors(self, authors: Vec<XOnlyPublicKey>) -> Self {
+        Self {
+            exclude_authors: Some(extend_unique(self.exclude_authors, authors)),
+            ..self
+        }
+    }
+
+    /// Add kinds to the existing ones
+    pub fn extend_kinds(self, kinds: Vec<Kind>) -> Self {
+        Self {
+            kinds: Some(extend_unique(self.kinds, kinds)),
+            ..self
+        }
+    }
+
+    /// Add authors to the existing ones
+    pub fn extend_authors(self, authors: Vec<XOnlyPublicKey>) -> Self {
+        Self {
+            authors: Some(extend_unique(self.authors, authors)),
+            ..self
+        }
+    }
+
+    /// Compute the logical AND of two [`Filter`]s
+    ///
+    /// A missing field means "any", so intersecting it with a populated field yields the populated one.
+    /// Return `None` when the intersection is provably empty (i.e. disjoint kinds or authors,
+    /// or `since` greater than `until`).
+    ///
+    /// If both filters have a different `search`, the one of `self` is kept.
+    pub fn intersection(&self, other: &Filter) -> Option<Filter> {
+        let ids = match (&self.ids, &other.ids) {
+            (Some(a), Some(b)) => {
+                let mut ids: Vec<String> = Vec::new();
+                for x in a.iter() {
+                    for y in b.iter() {
+                        let id = if x.starts_with(y.as_str()) {
+                            x
+                        } else if y.starts_with(x.as_str()) {
+                            y
+                        } else {
+                            continue;
+                        };
+                        if !ids.contains(id) {
+                            ids.push(id.clone());
+                        }
+                    }
+                }
+                Some(ids)
+            }
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+
+        let since = match (self.since, other.since) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+
+        let until = match (self.until, other.until) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        if let (Some(since), Some(until)) = (since, until) {
+            if since > until {
+                return None;
+            }
+        }
+
+        let limit = match (self.limit, other.limit) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        let mut generic_tags: BTreeMap<char, Vec<String>> = BTreeMap::new();
+        for tag in self.generic_tags.keys().chain(other.generic_tags.keys()) {
+            let values = intersect(
+                &self.generic_tags.get(tag).cloned(),
+                &other.generic_tags.get(tag).cloned(),
+            );
+            if let Some(values) = non_empty(values)? {
+                generic_tags.insert(*tag, values);
+            }
+        }
+
+        let mut custom = other.custom.clone();
+        custom.extend(self.custom.clone());
+
+        let exclude_authors = match (&self.exclude_authors, &other.exclude_authors) {
+            (Some(a), Some(b)) => Some(extend_unique(Some(a.clone()), b.clone())),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+
+        Some(Filter {
+            ids: non_empty(ids)?,
+            authors: non_empty(intersect(&self.authors, &other.authors))?,
+            kinds: non_empty(intersect(&self.kinds, &other.kinds))?,
+            events: non_empty(intersect(&self.events, &other.events))?,
+            pubkeys: non_empty(intersect(&self.pubkeys, &other.pubkeys))?,
+            hashtags: non_empty(intersect(&self.hashtags, &other.hashtags))?,
+            references: non_empty(intersect(&self.references, &other.references))?,
+            identifiers: non_empty(intersect(&self.identifiers, &other.identifiers))?,
+            search: self.search.clone().or_else(|| other.search.clone()),
+            since,
+            until,
+            limit,
+            generic_tags,
+            custom,
+            exclude_authors,
+        })
+    }
+
+    /// Check if [`Event`] match this [`Filter`]
+    ///
+    /// Ids are matched following NIP-01 prefix semantics: a filter id that is an hex prefix
+    /// of the event id matches. The `search` and `limit` fields are ignored.
+    pub fn matches_event(&self, event: &Event) -> bool {
+        if let Some(ids) = &self.ids {
+            let event_id: String = event.id.to_hex();
+            if !ids
+                .iter()
+                .any(|id| event_id.starts_with(&id.to_lowercase()))
+            {
+                return false;
+            }
+        }
+
+        if let Some(authors) = &self.authors {
+            if !authors.contains(&event.pubkey) {
+                return false;
+            }
+        }
+
+        if let Some(exclude_authors) = &self.exclude_authors {
+            if exclude_authors.contains(&event.pubkey) {
+                return false;
+            }
+        }
+
+        if let Some(kinds) = &self.kinds {
+            if !kinds.contains(&event.kind) {
+                return false;
+            }
+        }
+
+        if let Some(since) = self.since {
+            if event.created_at < since {
+                return false;
+            }
+        }
+
+        if let Some(until) = self.until {
+            if event.created_at > until {
+                return false;
+            }
+        }
+
+        if let Some(events) = &self.events {
+            let events: Vec<String> = events.iter().map(|id| id.to_hex()).collect();
+            if !match_tag_values(event, "e", &events) {
+                return false;
+            }
+        }
+
+        if let Some(pubkeys) = &self.pubkeys {
+            let pubkeys: Vec<String> = pubkeys.iter().map(|pk| pk.to_string()).collect();
+            if !match_tag_values(event, "p", &pubkeys) {
+                return false;
+            }
+        }
+
+        if let Some(hashtags) = &self.hashtags {
+            if !match_tag_values(event, "t", hashtags) {
+                return false;
+            }
+        }
+
+        if let Some(references) = &self.references {
+            if !match_tag_values(event, "r", references) {
+                return false;
+            }
+        }
+
+        if let Some(identifiers) = &self.identifiers {
+            if !match_tag_values(event, "d", identifiers) {
+                return false;
+            }
+        }
+
+        for (tag, values) in self.generic_tags.iter() {
+            if !match_tag_values(event, &tag.to_string(), values) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn extend_unique<T>(current: Option<Vec<T>>, values: Vec<T>) -> Vec<T>
+where
+    T: PartialEq,
+{
+    let mut current: Vec<T> = current.unwrap_or_default();
+    for value in values.into_iter() {
+        if !current.contains(&value) {
+            current.push(value);
+        }
+    }
+    current
+}
+
+fn intersect<T>(a: &Option<Vec<T>>, b: &Option<Vec<T>>) -> Option<Vec<T>>
+where
+    T: Clone + PartialEq,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.iter().filter(|v| b.contains(v)).cloned().collect()),
+        (a, b) => a.clone().or_else(|| b.clone()),
+    }
+}
+
+/// Return `None` if the field is populated but empty (provably empty intersection)
+fn non_empty<T>(field: Option<Vec<T>>) -> Option<Option<Vec<T>>> {
+    match field {
+        Some(v) if v.is_empty() => None,
+        field => Some(field),
+    }
+}
+
+/// Check if the [`Event`] has at least one tag of `kind` with a value contained in `values`
+fn match_tag_values(event: &Event, kind: &str, values: &[String]) -> bool {
+    event.tags.iter().any(|tag| {
+        let tag: Vec<String> = tag.as_vec();
+        match (tag.first(), tag.get(1)) {
+            (Some(k), Some(v)) => k == kind && values.contains(v),
+            _ => false,
+        }
+    })
 }
 
 impl Serialize for Filter {
@@ -271,7 +619,7 @@ impl Serialize for Filter {
     where
         S: Serializer,
     {
-        let len: usize = 11 + self.custom.len();
+        let len: usize = 12 + self.generic_tags.len() + self.custom.len();
         let mut map = serializer.serialize_map(Some(len))?;
         if let Some(value) = &self.ids {
             map.serialize_entry("ids", &json!(value))?;
@@ -294,6 +642,9 @@ impl Serialize for Filter {
         if let Some(value) = &self.references {
             map.serialize_entry("#r", &json!(value))?;
         }
+        if let Some(value) = &self.identifiers {
+            map.serialize_entry("#d", &json!(value))?;
+        }
         if let Some(value) = &self.search {
             map.serialize_entry("search", &json!(value))?;
         }
@@ -306,6 +657,9 @@ impl Serialize for Filter {
         if let Some(value) = &self.limit {
             map.serialize_entry("limit", &json!(value))?;
         }
+        for (tag, values) in &self.generic_tags {
+            map.serialize_entry(&format!("#{tag}"), &json!(values))?;
+        }
         for (k, v) in &self.custom {
             map.serialize_entry(&k, &v)?;
         }
@@ -380,6 +734,12 @@ impl<'de> Visitor<'de> for FilterVisitor {
             f.references = Some(references);
         }
 
+        if let Some(value) = map.remove("#d") {
+            let identifiers: Vec<String> =
+                serde_json::from_value(value).map_err(de::Error::custom)?;
+            f.identifiers = Some(identifiers);
+        }
+
         if let Some(Value::String(search)) = map.remove("search") {
             f.search = Some(search);
         }
@@ -399,6 +759,21 @@ impl<'de> Visitor<'de> for FilterVisitor {
             f.limit = Some(limit);
         }
 
+        // Generic tag filters: only single-letter keys are valid (NIP-12)
+        let tag_keys: Vec<String> = map.keys().filter(|k| k.starts_with('#')).cloned().collect();
+        for key in tag_keys.into_iter() {
+            let mut chars = key[1..].chars();
+            let tag: char = match (chars.next(), chars.next()) {
+                (Some(tag), None) if tag.is_ascii_alphabetic() => tag,
+                _ => return Err(de::Error::custom(format!("invalid tag filter key: {key}"))),
+            };
+            if let Some(value) = map.remove(&key) {
+                let values: Vec<String> =
+                    serde_json::from_value(value).map_err(de::Error::custom)?;
+                f.generic_tags.insert(tag, values);
+            }
+        }
+
         f.custom = map;
 
         Ok(f)
@@ -408,6 +783,8 @@ impl<'de> Visitor<'de> for FilterVisitor {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::event::TagKind;
+    use crate::{EventBuilder, Keys, Tag};
 
     #[test]
     fn test_filter_serialization() {
@@ -420,16 +797,201 @@ mod test {
 
     #[test]
     fn test_filter_deserialization() {
-        let json = r##"{"#a":"...","search":"test","ids":["myid", "mysecondid"]}"##;
+        let json = r##"{"#a":["..."],"search":"test","ids":["myid", "mysecondid"],"foo":"bar"}"##;
         let filter = Filter::from_json(json).unwrap();
         let mut custom = Map::new();
-        custom.insert("#a".to_string(), Value::String("...".to_string()));
+        custom.insert("foo".to_string(), Value::String("bar".to_string()));
         assert_eq!(
             filter,
             Filter::new()
-                .ids(vec!["myid".to_string(), "mysecondid".to_string()])
+                .ids(vec!["myid", "mysecondid"])
                 .search("test")
+                .custom_tag('a', vec!["..."])
                 .custom(custom)
         );
     }
+
+    #[test]
+    fn test_filter_limit_serialization() {
+        let filter = Filter::new().kind(Kind::TextNote);
+        assert_eq!(filter.as_json(), r#"{"kinds":[1]}"#);
+
+        let filter = filter.limit(10);
+        assert_eq!(filter.as_json(), r#"{"kinds":[1],"limit":10}"#);
+
+        let filter = filter.limit(0);
+        assert_eq!(filter.as_json(), r#"{"kinds":[1],"limit":0}"#);
+        assert_eq!(Filter::from_json(filter.as_json()).unwrap().limit, Some(0));
+
+        let filter = filter.no_limit();
+        assert_eq!(filter.limit, None);
+        assert_eq!(filter.as_json(), r#"{"kinds":[1]}"#);
+    }
+
+    #[test]
+    fn test_filter_generic_tags() {
+        let json = r##"{"#t":["nostr"],"#g":["u4pruydqqvj"],"#L":["license"]}"##;
+        let filter = Filter::from_json(json).unwrap();
+        assert_eq!(filter.hashtags, Some(vec![String::from("nostr")]));
+        assert_eq!(
+            filter,
+            Filter::new()
+                .hashtag("nostr")
+                .custom_tag('g', vec!["u4pruydqqvj"])
+                .custom_tag('L', vec!["license"])
+        );
+        assert_eq!(Filter::from_json(filter.as_json()).unwrap(), filter);
+
+        // Multi-letter keys are invalid
+        assert!(Filter::from_json(r##"{"#custom":["value"]}"##).is_err());
+        assert!(Filter::from_json(r##"{"#":["value"]}"##).is_err());
+        // Values must be strings
+        assert!(Filter::from_json(r##"{"#g":"value"}"##).is_err());
+
+        // Matching
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note(
+            "Hi",
+            &[Tag::Generic(
+                TagKind::Custom(String::from("g")),
+                vec![String::from("u4pruydqqvj")],
+            )],
+        )
+        .to_event(&keys)
+        .unwrap();
+        assert!(Filter::new()
+            .custom_tag('g', vec!["u4pruydqqvj"])
+            .matches_event(&event));
+        assert!(!Filter::new()
+            .custom_tag('g', vec!["other"])
+            .matches_event(&event));
+
+        // Letters with a dedicated field are merged into it: a single `#e` key is serialized
+        let id =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")
+                .unwrap();
+        let filter = Filter::new()
+            .event(id)
+            .custom_tag('e', vec![id.to_hex()])
+            .custom_tag('t', vec!["nostr"]);
+        assert_eq!(filter, Filter::new().event(id).hashtag("nostr"));
+        assert!(filter.generic_tags.is_empty());
+        assert_eq!(filter.as_json().matches("\"#e\"").count(), 1);
+        assert_eq!(Filter::from_json(filter.as_json()).unwrap(), filter);
+
+        // Non-letter tags are ignored
+        let filter = Filter::new().custom_tag('1', vec!["value"]);
+        assert_eq!(filter, Filter::new());
+        assert_eq!(Filter::from_json(filter.as_json()).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_filter_matches_event_id() {
+        let event = Event::from_json(r#"{"content":"uRuvYr585B80L6rSJiHocw==?iv=oh6LVqdsYYol3JfFnXTbPA==","created_at":1640839235,"id":"2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45","kind":4,"pubkey":"f86c44a2de95d9149b51c6a29afeabba264c18e2fa7c49de93424a0c56947785","sig":"a5d9290ef9659083c490b303eb7ee41356d8778ff19f2f91776c8dc4443388a64ffcf336e61af4c25c05ac3ae952d1ced889ed655b67790891222aaa15b99fdd","tags":[["p","13adc511de7e1cfcf1c6b7f6365fb5a03442d7bcacf565ea57fa7770912c023d"]]}"#).unwrap();
+
+        // Full id
+        let filter = Filter::new().id(event.id);
+        assert!(filter.matches_event(&event));
+
+        // Prefix
+        let filter = Filter::new().id("2be17aa3");
+        assert!(filter.matches_event(&event));
+
+        let filter = Filter::new().ids(vec!["abcdef", "2be17a"]);
+        assert!(filter.matches_event(&event));
+
+        let filter = Filter::new().id("2be17b");
+        assert!(!filter.matches_event(&event));
+    }
+
+    #[test]
+    fn test_filter_tag_helpers() {
+        let filter = Filter::new().hashtags(vec!["nostr".into()]);
+        assert_eq!(filter.as_json(), r##"{"#t":["nostr"]}"##);
+
+        let filter = Filter::new()
+            .hashtag("nostr")
+            .hashtags(vec!["rust".into(), "nostr".into()])
+            .identifiers(vec!["article".into()])
+            .identifier("note")
+            .reference("https://example.com");
+        assert_eq!(
+            json!(filter),
+            json!({
+                "#t": ["nostr", "rust"],
+                "#r": ["https://example.com"],
+                "#d": ["article", "note"]
+            })
+        );
+        assert_eq!(Filter::from_json(filter.as_json()).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_filter_exclude_authors() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let alice_note = EventBuilder::new_text_note("Alice", &[])
+            .to_event(&alice)
+            .unwrap();
+        let bob_note = EventBuilder::new_text_note("Bob", &[])
+            .to_event(&bob)
+            .unwrap();
+
+        let filter = Filter::new()
+            .kind(Kind::TextNote)
+            .exclude_authors(vec![bob.public_key()]);
+        assert!(filter.matches_event(&alice_note));
+        assert!(!filter.matches_event(&bob_note));
+
+        // Not sent to relays
+        assert_eq!(filter.as_json(), r#"{"kinds":[1]}"#);
+    }
+
+    #[test]
+    fn test_filter_intersection() {
+        let a = Filter::new().kinds(vec![Kind::TextNote, Kind::Reaction]);
+        let b = Filter::new()
+            .kinds(vec![Kind::Reaction, Kind::Repost])
+            .since(Timestamp::from(10));
+        let c = a.intersection(&b).unwrap();
+        assert_eq!(c.kinds, Some(vec![Kind::Reaction]));
+        assert_eq!(c.since, Some(Timestamp::from(10)));
+
+        // Missing field means "any"
+        let d = Filter::new().limit(10);
+        let e = a.intersection(&d).unwrap();
+        assert_eq!(e.kinds, a.kinds);
+        assert_eq!(e.limit, Some(10));
+
+        // Disjoint kinds
+        let f = Filter::new().kind(Kind::Metadata);
+        assert!(a.intersection(&f).is_none());
+
+        // since > until
+        let g = Filter::new().until(Timestamp::from(5));
+        assert!(b.intersection(&g).is_none());
+    }
+
+    #[test]
+    fn test_filter_extend_kinds() {
+        let filter = Filter::new()
+            .kind(Kind::TextNote)
+            .extend_kinds(vec![Kind::TextNote, Kind::Reaction]);
+        assert_eq!(filter.kinds, Some(vec![Kind::TextNote, Kind::Reaction]));
+    }
+
+    #[test]
+    fn test_filter_is_empty() {
+        let filter = Filter::new();
+        assert!(filter.is_empty());
+        assert!(!filter.is_limit_only());
+
+        let filter = Filter::new().limit(10);
+        assert!(!filter.is_empty());
+        assert!(filter.is_limit_only());
+
+        let filter = Filter::new().kind(Kind::TextNote).limit(10);
+        assert!(!filter.is_empty());
+        assert!(!filter.is_limit_only());
+    }
 }
@@ -28,7 +28,7 @@ pub enum Error {
     /// Error while decoding from base64
     #[error("Error while decoding from base64")]
     Base64Decode,
-    /// Error while encoding to UTF-8
+    /// The decrypted content is not valid UTF-8
     #[error("Error while encoding to UTF-8")]
     Utf8Encode,
     /// Wrong encryption block mode
@@ -78,9 +78,10 @@ where
     let iv: Vec<u8> = general_purpose::STANDARD
         .decode(parsed_content[1])
         .map_err(|_| Error::Base64Decode)?;
+    let iv: [u8; 16] = iv.try_into().map_err(|_| Error::InvalidContentFormat)?;
     let key: [u8; 32] = generate_shared_key(sk, pk)?;
 
-    let cipher = Aes256CbcDec::new(&key.into(), iv.as_slice().into());
+    let cipher = Aes256CbcDec::new(&key.into(), &iv.into());
     let result = cipher
         .decrypt_padded_vec_mut::<Pkcs7>(&encrypted_content)
         .map_err(|_| Error::WrongBlockMode)?;
@@ -110,7 +111,7 @@ mod tests {
 
     use secp256k1::KeyPair;
 
-    use crate::{Result, SECP256K1};
+    use crate::{Keys, Result, SECP256K1};
 
     #[test]
     fn test_encryption_decryption() -> Result<()> {
@@ -165,4 +166,42 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_content_length_edge_cases() -> Result<()> {
+        let sender = Keys::generate();
+        let receiver = Keys::generate();
+        let (sender_sk, sender_pk) = (sender.secret_key()?, sender.public_key());
+        let (receiver_sk, receiver_pk) = (receiver.secret_key()?, receiver.public_key());
+
+        // Empty, single char and around the AES block size
+        for len in [0, 1, 15, 16, 17, 32] {
+            let content = "a".repeat(len);
+            let encrypted_content = encrypt(&sender_sk, &receiver_pk, &content)?;
+            assert_eq!(
+                decrypt(&receiver_sk, &sender_pk, encrypted_content)?,
+                content
+            );
+        }
+
+        // Invalid UTF-8
+        let encrypted_content = encrypt(&sender_sk, &receiver_pk, [0xff, 0xfe])?;
+        assert_eq!(
+            decrypt(&receiver_sk, &sender_pk, encrypted_content).unwrap_err(),
+            Error::Utf8Encode
+        );
+
+        // Invalid IV length
+        assert_eq!(
+            decrypt(
+                &receiver_sk,
+                &sender_pk,
+                "dJc+WbBgaFCD2/kfg1XCWJParplBDxnZIdJGZ6FCTOg=?iv=AAAA"
+            )
+            .unwrap_err(),
+            Error::InvalidContentFormat
+        );
+
+        Ok(())
+    }
 }
@@ -5,6 +5,7 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/05.md>
 
+use std::fmt;
 #[cfg(not(target_arch = "wasm32"))]
 use std::net::SocketAddr;
 use std::str::FromStr;
@@ -22,6 +23,12 @@ pub enum Error {
     /// Invalid format
     #[error("invalid format")]
     InvalidFormat,
+    /// Invalid local part
+    #[error("invalid local part: allowed characters are a-z0-9-_.")]
+    InvalidLocalPart,
+    /// Invalid domain
+    #[error("invalid domain")]
+    InvalidDomain,
     /// Impossible to verify
     #[error("impossible to verify")]
     ImpossibleToVerify,
@@ -36,15 +43,75 @@ pub enum Error {
     Secp256k1(#[from] secp256k1::Error),
 }
 
-fn compose_url(nip05: &str) -> Result<(String, &str), Error> {
-    let data: Vec<&str> = nip05.split('@').collect();
-    if data.len() != 2 {
-        return Err(Error::InvalidFormat);
+/// NIP05 address (`<local-part>@<domain>`)
+///
+/// The bare `<domain>` form is interpreted as `_@<domain>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Nip05Address {
+    local_part: String,
+    domain: String,
+}
+
+impl Nip05Address {
+    /// Parse and normalize NIP05 address
+    pub fn parse(address: &str) -> Result<Self, Error> {
+        let address: String = address.trim().to_lowercase();
+        let (local_part, domain) = match address.split_once('@') {
+            Some((local_part, domain)) => (local_part.to_string(), domain.to_string()),
+            None => (String::from("_"), address),
+        };
+
+        if local_part.is_empty()
+            || !local_part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_.".contains(c))
+        {
+            return Err(Error::InvalidLocalPart);
+        }
+
+        if domain.is_empty()
+            || domain.starts_with('.')
+            || !domain
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "-.:".contains(c))
+        {
+            return Err(Error::InvalidDomain);
+        }
+
+        Ok(Self { local_part, domain })
+    }
+
+    /// Get local part
+    pub fn local_part(&self) -> &str {
+        &self.local_part
+    }
+
+    /// Get domain
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Get `.well-known/nostr.json` lookup URL
+    pub fn well_known_url(&self) -> String {
+        format!(
+            "https://{}/.well-known/nostr.json?name={}",
+            self.domain, self.local_part
+        )
+    }
+}
+
+impl FromStr for Nip05Address {
+    type Err = Error;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        Self::parse(address)
+    }
+}
+
+impl fmt::Display for Nip05Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{}", self.local_part, self.domain)
     }
-    let name: &str = data[0];
-    let domain: &str = data[1];
-    let url = format!("https://{domain}/.well-known/nostr.json?name={name}");
-    Ok((url, name))
 }
 
 fn get_key_from_json(json: Value, name: &str) -> Option<XOnlyPublicKey> {
@@ -85,7 +152,8 @@ pub async fn verify(
 ) -> Result<(), Error> {
     use reqwest::Client;
 
-    let (url, name) = compose_url(nip05)?;
+    let address = Nip05Address::parse(nip05)?;
+    let url: String = address.well_known_url();
     let mut builder = Client::builder();
     if let Some(proxy) = proxy {
         let proxy = format!("socks5h://{proxy}");
@@ -94,7 +162,7 @@ pub async fn verify(
     let client: Client = builder.build()?;
     let res = client.get(url).send().await?;
     let json: Value = serde_json::from_str(&res.text().await?)?;
-    verify_json(public_key, json, name)
+    verify_json(public_key, json, address.local_part())
 }
 
 /// Verify NIP05
@@ -107,7 +175,8 @@ pub fn verify_blocking(
 ) -> Result<(), Error> {
     use reqwest::blocking::Client;
 
-    let (url, name) = compose_url(nip05)?;
+    let address = Nip05Address::parse(nip05)?;
+    let url: String = address.well_known_url();
     let mut builder = Client::builder();
     if let Some(proxy) = proxy {
         let proxy = format!("socks5h://{proxy}");
@@ -116,7 +185,7 @@ pub fn verify_blocking(
     let client: Client = builder.build()?;
     let res = client.get(url).send()?;
     let json: Value = serde_json::from_str(&res.text()?)?;
-    verify_json(public_key, json, name)
+    verify_json(public_key, json, address.local_part())
 }
 
 /// Verify NIP05
@@ -124,11 +193,12 @@ pub fn verify_blocking(
 pub async fn verify(public_key: XOnlyPublicKey, nip05: &str) -> Result<(), Error> {
     use reqwest::Client;
 
-    let (url, name) = compose_url(nip05)?;
+    let address = Nip05Address::parse(nip05)?;
+    let url: String = address.well_known_url();
     let client: Client = Client::new();
     let res = client.get(url).send().await?;
     let json: Value = serde_json::from_str(&res.text().await?)?;
-    verify_json(public_key, json, name)
+    verify_json(public_key, json, address.local_part())
 }
 
 /// Get [Profile] from NIP05 (public key and list of advertised relays)
@@ -136,7 +206,8 @@ pub async fn verify(public_key: XOnlyPublicKey, nip05: &str) -> Result<(), Error
 pub async fn get_profile(nip05: &str, proxy: Option<SocketAddr>) -> Result<Profile, Error> {
     use reqwest::Client;
 
-    let (url, name) = compose_url(nip05)?;
+    let address = Nip05Address::parse(nip05)?;
+    let url: String = address.well_known_url();
     let mut builder = Client::builder();
     if let Some(proxy) = proxy {
         let proxy = format!("socks5h://{proxy}");
@@ -146,7 +217,8 @@ pub async fn get_profile(nip05: &str, proxy: Option<SocketAddr>) -> Result<Profi
     let res = client.get(url).send().await?;
     let json: Value = serde_json::from_str(&res.text().await?)?;
 
-    let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
+    let public_key =
+        get_key_from_json(json.clone(), address.local_part()).ok_or(Error::ImpossibleToVerify)?;
     let relays = get_relays_from_json(json, public_key);
 
     Ok(Profile { public_key, relays })
@@ -158,7 +230,8 @@ pub async fn get_profile(nip05: &str, proxy: Option<SocketAddr>) -> Result<Profi
 pub fn get_profile_blocking(nip05: &str, proxy: Option<SocketAddr>) -> Result<Profile, Error> {
     use reqwest::blocking::Client;
 
-    let (url, name) = compose_url(nip05)?;
+    let address = Nip05Address::parse(nip05)?;
+    let url: String = address.well_known_url();
     let mut builder = Client::builder();
     if let Some(proxy) = proxy {
         let proxy = format!("socks5h://{proxy}");
@@ -168,7 +241,8 @@ pub fn get_profile_blocking(nip05: &str, proxy: Option<SocketAddr>) -> Result<Pr
     let res = client.get(url).send()?;
     let json: Value = serde_json::from_str(&res.text()?)?;
 
-    let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
+    let public_key =
+        get_key_from_json(json.clone(), address.local_part()).ok_or(Error::ImpossibleToVerify)?;
     let relays = get_relays_from_json(json, public_key);
 
     Ok(Profile { public_key, relays })
@@ -179,13 +253,63 @@ pub fn get_profile_blocking(nip05: &str, proxy: Option<SocketAddr>) -> Result<Pr
 pub async fn get_profile(nip05: &str) -> Result<Profile, Error> {
     use reqwest::Client;
 
-    let (url, name) = compose_url(nip05)?;
+    let address = Nip05Address::parse(nip05)?;
+    let url: String = address.well_known_url();
     let client: Client = Client::new();
     let res = client.get(url).send().await?;
     let json: Value = serde_json::from_str(&res.text().await?)?;
 
-    let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
+    let public_key =
+        get_key_from_json(json.clone(), address.local_part()).ok_or(Error::ImpossibleToVerify)?;
     let relays = get_relays_from_json(json, public_key);
 
     Ok(Profile { public_key, relays })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nip05_address() {
+        let address = Nip05Address::parse("bob@example.com").unwrap();
+        assert_eq!(address.local_part(), "bob");
+        assert_eq!(address.domain(), "example.com");
+        assert_eq!(
+            address.well_known_url(),
+            "https://example.com/.well-known/nostr.json?name=bob"
+        );
+
+        assert_eq!(Nip05Address::parse("Bob@Example.com").unwrap(), address);
+
+        let address = Nip05Address::parse("example.com").unwrap();
+        assert_eq!(address.to_string(), "_@example.com");
+
+        // Loopback hosts are looked up over https too
+        let address = Nip05Address::parse("bob@127.0.0.1:8080").unwrap();
+        assert_eq!(
+            address.well_known_url(),
+            "https://127.0.0.1:8080/.well-known/nostr.json?name=bob"
+        );
+    }
+
+    #[test]
+    fn test_invalid_nip05_address() {
+        assert!(matches!(
+            Nip05Address::parse("b ob@example.com"),
+            Err(Error::InvalidLocalPart)
+        ));
+        assert!(matches!(
+            Nip05Address::parse("@example.com"),
+            Err(Error::InvalidLocalPart)
+        ));
+        assert!(matches!(
+            Nip05Address::parse("bob@"),
+            Err(Error::InvalidDomain)
+        ));
+        assert!(matches!(
+            Nip05Address::parse("bob@example.com/path"),
+            Err(Error::InvalidDomain)
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP10
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/10.md>
+
+use std::collections::{HashMap, HashSet};
+
+use crate::event::Marker;
+use crate::{Event, EventId, Tag};
+
+/// Node of a thread tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadNode {
+    /// Event (`None` for the synthetic root)
+    pub event: Option<Event>,
+    /// Direct replies, from the oldest to the newest
+    pub replies: Vec<ThreadNode>,
+}
+
+/// Get the id of the event to which `event` replies
+///
+/// Use the `reply` (or `root`) marker if present, otherwise the deprecated positional scheme
+/// (the last `e` tag is the replied event).
+pub fn reply_to(event: &Event) -> Option<EventId> {
+    let mut root: Option<EventId> = None;
+    let mut last: Option<EventId> = None;
+    let mut marked: bool = false;
+
+    for tag in event.tags.iter() {
+        if let Tag::Event(id, _, marker) = tag {
+            match marker {
+                Some(Marker::Reply) => return Some(*id),
+                Some(Marker::Root) => {
+                    marked = true;
+                    root = Some(*id);
+                }
+                Some(_) => marked = true,
+                None => last = Some(*id),
+            }
+        }
+    }
+
+    if marked {
+        root
+    } else {
+        last
+    }
+}
+
+/// Build the reply tree of the events
+///
+/// The events without a known parent are placed under a synthetic root (without event).
+/// Cycles are broken at their oldest event.
+pub fn build_thread(events: Vec<Event>) -> ThreadNode {
+    let mut events: HashMap<EventId, Event> = events.into_iter().map(|e| (e.id, e)).collect();
+
+    let mut parents: HashMap<EventId, EventId> = events
+        .values()
+        .filter_map(|event| {
+            let parent: EventId = reply_to(event)?;
+            (parent != event.id && events.contains_key(&parent)).then_some((event.id, parent))
+        })
+        .collect();
+
+    // Break cycles
+    let ids: Vec<EventId> = events.keys().copied().collect();
+    for id in ids.into_iter() {
+        let mut seen: HashSet<EventId> = HashSet::new();
+        let mut current: EventId = id;
+        while let Some(parent) = parents.get(&current).copied() {
+            if !seen.insert(current) {
+                let mut cycle: Vec<&Event> = vec![&events[&current]];
+                let mut next: EventId = parent;
+                while next != current {
+                    cycle.push(&events[&next]);
+                    next = parents[&next];
+                }
+                if let Some(oldest) = cycle.into_iter().min_by_key(|e| (e.created_at, e.id)) {
+                    parents.remove(&oldest.id);
+                }
+                break;
+            }
+            current = parent;
+        }
+    }
+
+    let mut children: HashMap<Option<EventId>, Vec<EventId>> = HashMap::new();
+    for id in events.keys() {
+        children
+            .entry(parents.get(id).copied())
+            .or_default()
+            .push(*id);
+    }
+    for ids in children.values_mut() {
+        ids.sort_by_key(|id| (events[id].created_at, *id));
+    }
+
+    fn build(
+        id: Option<EventId>,
+        events: &mut HashMap<EventId, Event>,
+        children: &mut HashMap<Option<EventId>, Vec<EventId>>,
+    ) -> ThreadNode {
+        let replies: Vec<ThreadNode> = children
+            .remove(&id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| build(Some(child), events, children))
+            .collect();
+        ThreadNode {
+            event: id.and_then(|id| events.remove(&id)),
+            replies,
+        }
+    }
+
+    build(None, &mut events, &mut children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_build_thread() {
+        let keys = Keys::generate();
+        let root = EventBuilder::new_text_note("Root", &[])
+            .to_event(&keys)
+            .unwrap();
+        let reply =
+            EventBuilder::new_text_note("Reply", &[Tag::Event(root.id, None, Some(Marker::Root))])
+                .to_event(&keys)
+                .unwrap();
+        // Positional scheme
+        let nested = EventBuilder::new_text_note(
+            "Nested",
+            &[
+                Tag::Event(root.id, None, None),
+                Tag::Event(reply.id, None, None),
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let orphan = EventBuilder::new_text_note(
+            "Orphan",
+            &[Tag::Event(
+                EventId::from_slice(&[0; 32]).unwrap(),
+                None,
+                None,
+            )],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let thread = build_thread(vec![
+            nested.clone(),
+            orphan.clone(),
+            reply.clone(),
+            root.clone(),
+        ]);
+        assert!(thread.event.is_none());
+        assert_eq!(thread.replies.len(), 2);
+
+        let root_node = thread
+            .replies
+            .iter()
+            .find(|n| n.event.as_ref() == Some(&root))
+            .unwrap();
+        assert_eq!(root_node.replies.len(), 1);
+        assert_eq!(root_node.replies[0].event, Some(reply));
+        assert_eq!(root_node.replies[0].replies.len(), 1);
+        assert_eq!(root_node.replies[0].replies[0].event, Some(nested));
+        assert!(thread
+            .replies
+            .iter()
+            .any(|n| n.event.as_ref() == Some(&orphan)));
+    }
+
+    #[test]
+    fn test_build_thread_cycle() {
+        let keys = Keys::generate();
+        let mut a = EventBuilder::new_text_note("A", &[])
+            .to_event(&keys)
+            .unwrap();
+        let mut b = EventBuilder::new_text_note("B", &[])
+            .to_event(&keys)
+            .unwrap();
+        a.id = EventId::from_slice(&[1; 32]).unwrap();
+        b.id = EventId::from_slice(&[2; 32]).unwrap();
+        a.tags = vec![Tag::Event(b.id, None, Some(Marker::Reply))];
+        b.tags = vec![Tag::Event(a.id, None, Some(Marker::Reply))];
+
+        let thread = build_thread(vec![a, b]);
+        assert_eq!(thread.replies.len(), 1);
+        assert_eq!(thread.replies[0].replies.len(), 1);
+    }
+}
@@ -13,6 +13,9 @@ use std::net::SocketAddr;
 use reqwest::Proxy;
 use url::Url;
 
+use crate::nips::nip13;
+use crate::{ClientMessage, Event, Timestamp};
+
 /// `NIP11` error
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -30,6 +33,82 @@ pub enum Error {
     InvalidScheme,
 }
 
+/// Violated relay [`Limitation`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LimitViolation {
+    /// Message too long
+    #[error("message too long: {size} > {max} bytes")]
+    MessageTooLong {
+        /// Message size
+        size: usize,
+        /// Max allowed size
+        max: u32,
+    },
+    /// Content too long
+    #[error("content too long: {len} > {max} characters")]
+    ContentTooLong {
+        /// Content length
+        len: usize,
+        /// Max allowed length
+        max: u32,
+    },
+    /// Too many tags
+    #[error("too many tags: {count} > {max}")]
+    TooManyTags {
+        /// Number of tags
+        count: usize,
+        /// Max allowed number of tags
+        max: u32,
+    },
+    /// POW difficulty too low
+    #[error("POW difficulty too low: {difficulty} < {min}")]
+    PowTooLow {
+        /// Event difficulty
+        difficulty: u8,
+        /// Min required difficulty
+        min: u8,
+    },
+    /// `created_at` too far in the past
+    #[error("created_at too far in the past")]
+    CreatedAtTooOld,
+    /// `created_at` too far in the future
+    #[error("created_at too far in the future")]
+    CreatedAtTooNew,
+}
+
+/// Relay limitation
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/11.md#server-limitations>
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limitation {
+    /// Max bytes of the incoming JSON messages
+    pub max_message_length: Option<u32>,
+    /// Max number of subscriptions per connection
+    pub max_subscriptions: Option<u32>,
+    /// Max number of filters per subscription
+    pub max_filters: Option<u32>,
+    /// Max value of the `limit` filter field
+    pub max_limit: Option<u32>,
+    /// Max length of the subscription id
+    pub max_subid_length: Option<u32>,
+    /// Min length of the prefix in `ids` and `authors` filter fields
+    pub min_prefix: Option<u32>,
+    /// Max number of tags of an event
+    pub max_event_tags: Option<u32>,
+    /// Max number of characters of the event content
+    pub max_content_length: Option<u32>,
+    /// Min POW difficulty
+    pub min_pow_difficulty: Option<u8>,
+    /// Authentication (NIP-42) is required
+    pub auth_required: Option<bool>,
+    /// Payment is required
+    pub payment_required: Option<bool>,
+    /// Max age (seconds) of the `created_at` in the past
+    pub created_at_lower_limit: Option<u64>,
+    /// Max distance (seconds) of the `created_at` in the future
+    pub created_at_upper_limit: Option<u64>,
+}
+
 /// Relay information document
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RelayInformationDocument {
@@ -47,6 +126,8 @@ pub struct RelayInformationDocument {
     pub software: Option<String>,
     /// Software version
     pub version: Option<String>,
+    /// Limitation
+    pub limitation: Option<Limitation>,
 }
 
 impl RelayInformationDocument {
@@ -117,6 +198,60 @@ impl RelayInformationDocument {
         }
     }
 
+    /// Check if the [`Event`] satisfies the advertised relay [`Limitation`]
+    ///
+    /// Always `Ok` if the relay doesn't advertise any limitation.
+    pub fn accepts(&self, event: &Event) -> Result<(), LimitViolation> {
+        let limitation: &Limitation = match &self.limitation {
+            Some(limitation) => limitation,
+            None => return Ok(()),
+        };
+
+        if let Some(max) = limitation.max_message_length {
+            let size: usize = ClientMessage::new_event(event.clone()).as_json().len();
+            if size > max as usize {
+                return Err(LimitViolation::MessageTooLong { size, max });
+            }
+        }
+
+        if let Some(max) = limitation.max_content_length {
+            let len: usize = event.content.chars().count();
+            if len > max as usize {
+                return Err(LimitViolation::ContentTooLong { len, max });
+            }
+        }
+
+        if let Some(max) = limitation.max_event_tags {
+            let count: usize = event.tags.len();
+            if count > max as usize {
+                return Err(LimitViolation::TooManyTags { count, max });
+            }
+        }
+
+        if let Some(min) = limitation.min_pow_difficulty {
+            let difficulty: u8 = nip13::get_leading_zero_bits(event.id.inner());
+            if difficulty < min {
+                return Err(LimitViolation::PowTooLow { difficulty, min });
+            }
+        }
+
+        let now = Timestamp::now();
+
+        if let Some(lower) = limitation.created_at_lower_limit {
+            if event.created_at < now - lower {
+                return Err(LimitViolation::CreatedAtTooOld);
+            }
+        }
+
+        if let Some(upper) = limitation.created_at_upper_limit {
+            if event.created_at > now + upper {
+                return Err(LimitViolation::CreatedAtTooNew);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns new URL with scheme substituted to HTTP(S) if WS(S) was provided,
     /// other schemes leaves untouched.
     fn with_http_scheme(url: Url) -> Result<Url, Error> {
@@ -129,3 +264,148 @@ impl RelayInformationDocument {
         Ok(url)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Tag};
+
+    fn document(limitation: Limitation) -> RelayInformationDocument {
+        RelayInformationDocument {
+            limitation: Some(limitation),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_accepts_content_too_long() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("a".repeat(101), &[])
+            .to_event(&keys)
+            .unwrap();
+
+        let doc = document(Limitation {
+            max_content_length: Some(100),
+            ..Default::default()
+        });
+        assert_eq!(
+            doc.accepts(&event),
+            Err(LimitViolation::ContentTooLong { len: 101, max: 100 })
+        );
+
+        let event = EventBuilder::new_text_note("a".repeat(100), &[])
+            .to_event(&keys)
+            .unwrap();
+        assert!(doc.accepts(&event).is_ok());
+    }
+
+    #[test]
+    fn test_accepts_too_many_tags() {
+        let keys = Keys::generate();
+        let tags: Vec<Tag> = (0..3).map(|i| Tag::Hashtag(format!("tag{i}"))).collect();
+        let event = EventBuilder::new_text_note("hello", &tags)
+            .to_event(&keys)
+            .unwrap();
+
+        let doc = document(Limitation {
+            max_event_tags: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(
+            doc.accepts(&event),
+            Err(LimitViolation::TooManyTags { count: 3, max: 2 })
+        );
+
+        // No limitation
+        assert!(RelayInformationDocument::new().accepts(&event).is_ok());
+    }
+
+    #[test]
+    fn test_accepts_message_length() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        let size: usize = ClientMessage::new_event(event.clone()).as_json().len();
+
+        let doc = document(Limitation {
+            max_message_length: Some(size as u32 - 1),
+            ..Default::default()
+        });
+        assert_eq!(
+            doc.accepts(&event),
+            Err(LimitViolation::MessageTooLong {
+                size,
+                max: size as u32 - 1
+            })
+        );
+
+        let doc = document(Limitation {
+            max_message_length: Some(size as u32),
+            ..Default::default()
+        });
+        assert!(doc.accepts(&event).is_ok());
+    }
+
+    #[test]
+    fn test_accepts_pow_difficulty() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new_text_note("hello", &[])
+            .to_event(&keys)
+            .unwrap();
+        let difficulty: u8 = nip13::get_leading_zero_bits(event.id.inner());
+
+        let doc = document(Limitation {
+            min_pow_difficulty: Some(difficulty + 1),
+            ..Default::default()
+        });
+        assert_eq!(
+            doc.accepts(&event),
+            Err(LimitViolation::PowTooLow {
+                difficulty,
+                min: difficulty + 1
+            })
+        );
+
+        let event = EventBuilder::new_text_note("hello", &[])
+            .to_pow_event(&keys, 8)
+            .unwrap();
+        let doc = document(Limitation {
+            min_pow_difficulty: Some(8),
+            ..Default::default()
+        });
+        assert!(doc.accepts(&event).is_ok());
+    }
+
+    #[test]
+    fn test_accepts_created_at_limits() {
+        let keys = Keys::generate();
+        let now = Timestamp::now();
+        let note = |created_at: Timestamp| -> Event {
+            EventBuilder::new_text_note("hello", &[])
+                .custom_created_at(created_at)
+                .to_event(&keys)
+                .unwrap()
+        };
+
+        let doc = document(Limitation {
+            created_at_lower_limit: Some(60),
+            ..Default::default()
+        });
+        assert_eq!(
+            doc.accepts(&note(now - 3600u64)),
+            Err(LimitViolation::CreatedAtTooOld)
+        );
+        assert!(doc.accepts(&note(now)).is_ok());
+
+        let doc = document(Limitation {
+            created_at_upper_limit: Some(60),
+            ..Default::default()
+        });
+        assert_eq!(
+            doc.accepts(&note(now + 3600u64)),
+            Err(LimitViolation::CreatedAtTooNew)
+        );
+        assert!(doc.accepts(&note(now)).is_ok());
+    }
+}
@@ -6,6 +6,8 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/13.md>
 
+use std::time::Duration;
+
 /// Gets the number of leading zero bits. Result is between 0 and 255.
 pub fn get_leading_zero_bits<T>(h: T) -> u8
 where
@@ -52,6 +54,29 @@ pub fn get_prefixes_for_difficulty(leading_zero_bits: u8) -> Vec<String> {
     r
 }
 
+/// Estimate the highest difficulty that can be mined within `target` at `hash_rate` (hashes per second)
+///
+/// On average, `2^difficulty` hashes are needed to reach a difficulty.
+pub fn estimate_difficulty(target: Duration, hash_rate: f64) -> u8 {
+    let hashes: f64 = target.as_secs_f64() * hash_rate;
+    if !hashes.is_finite() || hashes < 1.0 {
+        return 0;
+    }
+    // Tolerate the rounding errors of the float/duration conversions
+    (hashes.log2() + 1e-9).floor().min(u8::MAX as f64) as u8
+}
+
+/// Estimate the average time needed to mine the `difficulty` at `hash_rate` (hashes per second)
+///
+/// Inverse of [`estimate_difficulty`].
+pub fn estimate_duration(difficulty: u8, hash_rate: f64) -> Duration {
+    let secs: f64 = 2_f64.powi(difficulty as i32) / hash_rate;
+    if !secs.is_finite() || secs < 0.0 || secs >= u64::MAX as f64 {
+        return Duration::MAX;
+    }
+    Duration::from_secs_f64(secs)
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::str::FromStr;
@@ -430,4 +455,23 @@ pub mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_estimate_difficulty() {
+        let hash_rate: f64 = 1_000_000.0;
+
+        // 2^20 hashes ~ 1.05 s
+        assert_eq!(estimate_difficulty(Duration::from_secs(1), hash_rate), 19);
+        assert_eq!(estimate_difficulty(Duration::from_secs(2), hash_rate), 20);
+        assert_eq!(estimate_difficulty(Duration::ZERO, hash_rate), 0);
+        assert_eq!(estimate_difficulty(Duration::from_secs(1), 0.0), 0);
+
+        for difficulty in [8, 16, 24] {
+            let duration = estimate_duration(difficulty, hash_rate);
+            assert_eq!(estimate_difficulty(duration, hash_rate), difficulty);
+            assert!(estimate_duration(difficulty + 1, hash_rate) > duration);
+        }
+
+        assert_eq!(estimate_duration(10, 0.0), Duration::MAX);
+    }
 }
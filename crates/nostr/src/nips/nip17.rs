@@ -0,0 +1,67 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP17
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/17.md>
+
+use secp256k1::XOnlyPublicKey;
+
+use super::nip59::{self, UnwrappedGift};
+use crate::{Event, EventId, Keys, Kind, Tag, Timestamp};
+
+/// `NIP17` error
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// NIP59 error
+    #[error(transparent)]
+    NIP59(#[from] nip59::Error),
+    /// The rumor is not a private direct message
+    #[error("not a private direct message")]
+    NotPrivateDirectMessage,
+}
+
+/// Decrypted private direct message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateMessage {
+    /// Sender public key
+    pub sender: XOnlyPublicKey,
+    /// Receivers (`p` tags)
+    pub receivers: Vec<XOnlyPublicKey>,
+    /// Message
+    pub content: String,
+    /// Replied message, if any
+    pub reply_to: Option<EventId>,
+    /// Rumor `created_at` (the real send time)
+    pub created_at: Timestamp,
+}
+
+/// Unwrap a gift-wrapped private direct message (kind `14`)
+pub fn unwrap_private_msg(
+    receiver_keys: &Keys,
+    gift_wrap: &Event,
+) -> Result<PrivateMessage, Error> {
+    let UnwrappedGift { sender, rumor } = nip59::extract_rumor(receiver_keys, gift_wrap)?;
+
+    if rumor.kind != Kind::PrivateDirectMessage {
+        return Err(Error::NotPrivateDirectMessage);
+    }
+
+    let mut receivers: Vec<XOnlyPublicKey> = Vec::new();
+    let mut reply_to: Option<EventId> = None;
+    for tag in rumor.tags.iter() {
+        match tag {
+            Tag::PubKey(pubkey, ..) => receivers.push(*pubkey),
+            Tag::Event(event_id, ..) if reply_to.is_none() => reply_to = Some(*event_id),
+            _ => (),
+        }
+    }
+
+    Ok(PrivateMessage {
+        sender,
+        receivers,
+        content: rumor.content,
+        reply_to,
+        created_at: rumor.created_at,
+    })
+}
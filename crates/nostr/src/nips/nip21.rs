@@ -0,0 +1,48 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP21
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/21.md>
+
+use secp256k1::XOnlyPublicKey;
+
+use super::nip19::{Error, Nip19Event, ParameterizedReplaceableEvent, ToBech32};
+use crate::EventId;
+
+/// URI scheme
+pub const NOSTR_URI_SCHEME: &str = "nostr";
+
+/// Serialize as `nostr:` URI
+///
+/// Secret keys are not allowed by NIP21, so [`ToNostrUri`] is not implemented for them.
+pub trait ToNostrUri: ToBech32<Err = Error> {
+    /// Get `nostr:` URI
+    fn to_nostr_uri(&self) -> Result<String, Error> {
+        Ok(format!("{NOSTR_URI_SCHEME}:{}", self.to_bech32()?))
+    }
+}
+
+impl ToNostrUri for XOnlyPublicKey {}
+impl ToNostrUri for EventId {}
+impl ToNostrUri for Nip19Event {}
+impl ToNostrUri for ParameterizedReplaceableEvent {}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_to_nostr_uri() {
+        let public_key = XOnlyPublicKey::from_str(
+            "aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4",
+        )
+        .unwrap();
+        assert_eq!(
+            public_key.to_nostr_uri().unwrap(),
+            "nostr:npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy"
+        );
+    }
+}
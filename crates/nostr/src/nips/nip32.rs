@@ -0,0 +1,95 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP32
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/32.md>
+
+use crate::event::tag::TagKind;
+use crate::{Event, Tag};
+
+/// Labels of an [`Event`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels {
+    /// Namespaces (`L` tags)
+    pub namespaces: Vec<String>,
+    /// Labels (`l` tags) with their namespace
+    pub labels: Vec<(String, Option<String>)>,
+    /// Labeled targets (`e`, `p`, `a`, `r` and `t` tags)
+    pub targets: Vec<Tag>,
+}
+
+impl Labels {
+    /// Get labels of the [`Event`]
+    pub fn from_event(event: &Event) -> Self {
+        let mut labels = Self::default();
+        for tag in event.tags.iter() {
+            match tag {
+                Tag::LabelNamespace(namespace) => labels.namespaces.push(namespace.clone()),
+                Tag::Label { label, namespace } => {
+                    labels.labels.push((label.clone(), namespace.clone()))
+                }
+                tag => {
+                    if matches!(
+                        tag.kind(),
+                        TagKind::E | TagKind::P | TagKind::A | TagKind::R | TagKind::T
+                    ) {
+                        labels.targets.push(tag.clone());
+                    }
+                }
+            }
+        }
+        labels
+    }
+
+    /// Get labels of a namespace
+    pub fn labels_of<S>(&self, namespace: S) -> Vec<String>
+    where
+        S: AsRef<str>,
+    {
+        self.labels
+            .iter()
+            .filter(|(_, ns)| ns.as_deref() == Some(namespace.as_ref()))
+            .map(|(label, _)| label.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Kind};
+
+    #[test]
+    fn test_label_pubkey() {
+        let keys = Keys::generate();
+        let labeled = Keys::generate().public_key();
+
+        let event = EventBuilder::label(
+            "com.example.ontology",
+            "VI-hum",
+            &[Tag::PubKey(labeled, None)],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        assert_eq!(event.kind, Kind::Label);
+        assert_eq!(event.tags[0].as_vec(), vec!["L", "com.example.ontology"]);
+        assert_eq!(
+            event.tags[1].as_vec(),
+            vec!["l", "VI-hum", "com.example.ontology"]
+        );
+
+        let event = Event::from_json(event.as_json()).unwrap();
+        let labels = Labels::from_event(&event);
+        assert_eq!(
+            labels.namespaces,
+            vec![String::from("com.example.ontology")]
+        );
+        assert_eq!(
+            labels.labels_of("com.example.ontology"),
+            vec![String::from("VI-hum")]
+        );
+        assert_eq!(labels.targets, vec![Tag::PubKey(labeled, None)]);
+    }
+}
@@ -0,0 +1,347 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP44 (v2)
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/44.md>
+
+use base64::engine::{general_purpose, Engine};
+use bitcoin_hashes::hmac::{Hmac, HmacEngine};
+use bitcoin_hashes::{sha256, Hash, HashEngine};
+use chacha20::cipher::{KeyIvInit, StreamCipher};
+use chacha20::ChaCha20;
+use secp256k1::rand::rngs::OsRng;
+use secp256k1::rand::RngCore;
+use secp256k1::{ecdh, PublicKey, SecretKey, XOnlyPublicKey};
+
+const VERSION: u8 = 2;
+const SALT: &[u8] = b"nip44-v2";
+const MIN_PLAINTEXT_SIZE: usize = 1;
+const MAX_PLAINTEXT_SIZE: usize = 65535;
+
+/// `NIP44` error
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum Error {
+    /// Secp256k1 error
+    #[error("secp256k1 error: {0}")]
+    Secp256k1(#[from] secp256k1::Error),
+    /// Error while decoding from base64
+    #[error("Error while decoding from base64")]
+    Base64Decode,
+    /// Error while encoding to UTF-8
+    #[error("Error while encoding to UTF-8")]
+    Utf8Encode,
+    /// Unknown version
+    #[error("unknown version: {0}")]
+    UnknownVersion(u8),
+    /// Invalid payload length
+    #[error("invalid payload length")]
+    InvalidPayloadLength,
+    /// Invalid MAC
+    #[error("invalid MAC")]
+    InvalidMac,
+    /// Invalid padding
+    #[error("invalid padding")]
+    InvalidPadding,
+    /// Message is empty or longer than 65535 bytes
+    #[error("invalid message length: {0}")]
+    InvalidMessageLength(usize),
+}
+
+/// Conversation key (`HKDF-extract` of the ECDH shared point)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversationKey([u8; 32]);
+
+impl ConversationKey {
+    /// Derive [`ConversationKey`]
+    pub fn derive(sk: &SecretKey, pk: &XOnlyPublicKey) -> Result<Self, Error> {
+        let mut pk_bytes: [u8; 33] = [2; 33];
+        pk_bytes[1..].copy_from_slice(&pk.serialize());
+        let pk = PublicKey::from_slice(&pk_bytes)?;
+        let shared_point: [u8; 64] = ecdh::shared_secret_point(&pk, sk);
+        Ok(Self(hmac_sha256(SALT, &[&shared_point[..32]])))
+    }
+
+    /// Get as bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Encrypt
+pub fn encrypt<T>(sk: &SecretKey, pk: &XOnlyPublicKey, content: T) -> Result<String, Error>
+where
+    T: AsRef<[u8]>,
+{
+    let conversation_key = ConversationKey::derive(sk, pk)?;
+    let mut nonce: [u8; 32] = [0u8; 32];
+    OsRng.fill_bytes(&mut nonce);
+    encrypt_with_nonce(&conversation_key, &nonce, content.as_ref())
+}
+
+/// Decrypt
+pub fn decrypt<S>(sk: &SecretKey, pk: &XOnlyPublicKey, payload: S) -> Result<String, Error>
+where
+    S: AsRef<str>,
+{
+    let conversation_key = ConversationKey::derive(sk, pk)?;
+    decrypt_with_conversation_key(&conversation_key, payload.as_ref())
+}
+
+fn encrypt_with_nonce(
+    conversation_key: &ConversationKey,
+    nonce: &[u8; 32],
+    content: &[u8],
+) -> Result<String, Error> {
+    let (chacha_key, chacha_nonce, hmac_key) = message_keys(conversation_key, nonce);
+
+    let mut buffer: Vec<u8> = pad(content)?;
+    let mut cipher = ChaCha20::new(&chacha_key.into(), &chacha_nonce.into());
+    cipher.apply_keystream(&mut buffer);
+
+    let mac: [u8; 32] = hmac_sha256(&hmac_key, &[nonce, &buffer]);
+
+    let mut payload: Vec<u8> = Vec::with_capacity(1 + 32 + buffer.len() + 32);
+    payload.push(VERSION);
+    payload.extend_from_slice(nonce);
+    payload.extend_from_slice(&buffer);
+    payload.extend_from_slice(&mac);
+    Ok(general_purpose::STANDARD.encode(payload))
+}
+
+fn decrypt_with_conversation_key(
+    conversation_key: &ConversationKey,
+    payload: &str,
+) -> Result<String, Error> {
+    if payload.starts_with('#') {
+        return Err(Error::UnknownVersion(b'#'));
+    }
+    if payload.len() < 132 || payload.len() > 87472 {
+        return Err(Error::InvalidPayloadLength);
+    }
+
+    let data: Vec<u8> = general_purpose::STANDARD
+        .decode(payload)
+        .map_err(|_| Error::Base64Decode)?;
+    if data.len() < 99 || data.len() > 65603 {
+        return Err(Error::InvalidPayloadLength);
+    }
+    if data[0] != VERSION {
+        return Err(Error::UnknownVersion(data[0]));
+    }
+
+    let mut nonce: [u8; 32] = [0u8; 32];
+    nonce.copy_from_slice(&data[1..33]);
+    let ciphertext: &[u8] = &data[33..data.len() - 32];
+    let mac: &[u8] = &data[data.len() - 32..];
+
+    let (chacha_key, chacha_nonce, hmac_key) = message_keys(conversation_key, &nonce);
+    let expected_mac: [u8; 32] = hmac_sha256(&hmac_key, &[&nonce, ciphertext]);
+    if !constant_time_eq(&expected_mac, mac) {
+        return Err(Error::InvalidMac);
+    }
+
+    let mut buffer: Vec<u8> = ciphertext.to_vec();
+    let mut cipher = ChaCha20::new(&chacha_key.into(), &chacha_nonce.into());
+    cipher.apply_keystream(&mut buffer);
+
+    String::from_utf8(unpad(&buffer)?.to_vec()).map_err(|_| Error::Utf8Encode)
+}
+
+/// Derive the ChaCha20 key, the ChaCha20 nonce and the HMAC key (`HKDF-expand`)
+fn message_keys(
+    conversation_key: &ConversationKey,
+    nonce: &[u8; 32],
+) -> ([u8; 32], [u8; 12], [u8; 32]) {
+    let mut keys: [u8; 76] = [0u8; 76];
+    let mut block: Vec<u8> = Vec::new();
+    for (i, chunk) in keys.chunks_mut(32).enumerate() {
+        block = hmac_sha256(
+            conversation_key.as_bytes(),
+            &[&block, nonce, &[i as u8 + 1]],
+        )
+        .to_vec();
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+
+    let mut chacha_key: [u8; 32] = [0u8; 32];
+    let mut chacha_nonce: [u8; 12] = [0u8; 12];
+    let mut hmac_key: [u8; 32] = [0u8; 32];
+    chacha_key.copy_from_slice(&keys[..32]);
+    chacha_nonce.copy_from_slice(&keys[32..44]);
+    hmac_key.copy_from_slice(&keys[44..]);
+    (chacha_key, chacha_nonce, hmac_key)
+}
+
+fn hmac_sha256(key: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let mut engine = HmacEngine::<sha256::Hash>::new(key);
+    for d in data.iter() {
+        engine.input(d);
+    }
+    Hmac::from_engine(engine).to_byte_array()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// Calculate padded length
+fn calc_padded_len(len: usize) -> usize {
+    if len <= 32 {
+        return 32;
+    }
+    let next_power: usize = 1 << (usize::BITS - (len - 1).leading_zeros());
+    let chunk: usize = if next_power <= 256 {
+        32
+    } else {
+        next_power / 8
+    };
+    chunk * ((len - 1) / chunk + 1)
+}
+
+/// Prefix the content with its length (`u16` big-endian) and pad it with zeros
+fn pad(content: &[u8]) -> Result<Vec<u8>, Error> {
+    let len: usize = content.len();
+    if !(MIN_PLAINTEXT_SIZE..=MAX_PLAINTEXT_SIZE).contains(&len) {
+        return Err(Error::InvalidMessageLength(len));
+    }
+    let mut padded: Vec<u8> = Vec::with_capacity(2 + calc_padded_len(len));
+    padded.extend_from_slice(&(len as u16).to_be_bytes());
+    padded.extend_from_slice(content);
+    padded.resize(2 + calc_padded_len(len), 0);
+    Ok(padded)
+}
+
+fn unpad(padded: &[u8]) -> Result<&[u8], Error> {
+    if padded.len() < 2 {
+        return Err(Error::InvalidPadding);
+    }
+    let len: usize = u16::from_be_bytes([padded[0], padded[1]]) as usize;
+    if len < MIN_PLAINTEXT_SIZE
+        || padded.len() != 2 + calc_padded_len(len)
+        || padded.len() < 2 + len
+    {
+        return Err(Error::InvalidPadding);
+    }
+    Ok(&padded[2..2 + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::Keys;
+
+    #[test]
+    fn test_nip44_vector() {
+        let sk1 =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        let sk2 =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000002")
+                .unwrap();
+        let pk2: XOnlyPublicKey = Keys::new(sk2).public_key();
+
+        let conversation_key = ConversationKey::derive(&sk1, &pk2).unwrap();
+        assert_eq!(
+            conversation_key.as_bytes().to_vec(),
+            vec![
+                0xc4, 0x1c, 0x77, 0x53, 0x56, 0xfd, 0x92, 0xea, 0xdc, 0x63, 0xff, 0x5a, 0x0d, 0xc1,
+                0xda, 0x21, 0x1b, 0x26, 0x8c, 0xbe, 0xa2, 0x23, 0x16, 0x76, 0x70, 0x95, 0xb2, 0x87,
+                0x1e, 0xa1, 0x41, 0x2d
+            ]
+        );
+
+        let mut nonce: [u8; 32] = [0u8; 32];
+        nonce[31] = 1;
+        let payload = encrypt_with_nonce(&conversation_key, &nonce, b"a").unwrap();
+        assert_eq!(payload, "AgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABee0G5VSK0/9YypIObAtDKfYEAjD35uVkHyB0F4DwrcNaCXlCWZKaArsGrY6M9wnuTMxWfp1RTN9Xga8no+kF5Vsb");
+        assert_eq!(
+            decrypt_with_conversation_key(&conversation_key, &payload).unwrap(),
+            "a"
+        );
+    }
+
+    #[test]
+    fn test_nip44_encrypt_decrypt() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let content = "Saturn, bringer of old age";
+
+        let payload = encrypt(&alice.secret_key().unwrap(), &bob.public_key(), content).unwrap();
+        assert_eq!(
+            decrypt(&bob.secret_key().unwrap(), &alice.public_key(), &payload).unwrap(),
+            content
+        );
+
+        // Tampered ciphertext
+        let mut data = general_purpose::STANDARD.decode(&payload).unwrap();
+        data[40] ^= 1;
+        let tampered = general_purpose::STANDARD.encode(data);
+        assert_eq!(
+            decrypt(&bob.secret_key().unwrap(), &alice.public_key(), tampered),
+            Err(Error::InvalidMac)
+        );
+
+        assert_eq!(
+            encrypt(&alice.secret_key().unwrap(), &bob.public_key(), ""),
+            Err(Error::InvalidMessageLength(0))
+        );
+    }
+
+    #[test]
+    fn test_nip44_message_length_edge_cases() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+        let alice_sk = alice.secret_key().unwrap();
+        let bob_sk = bob.secret_key().unwrap();
+
+        // Single char, padding boundaries and max length
+        for len in [1, 32, 33, 256, 257, MAX_PLAINTEXT_SIZE] {
+            let content = "a".repeat(len);
+            let payload = encrypt(&alice_sk, &bob.public_key(), &content).unwrap();
+            assert_eq!(
+                decrypt(&bob_sk, &alice.public_key(), payload).unwrap(),
+                content
+            );
+        }
+
+        assert_eq!(
+            encrypt(
+                &alice_sk,
+                &bob.public_key(),
+                "a".repeat(MAX_PLAINTEXT_SIZE + 1)
+            ),
+            Err(Error::InvalidMessageLength(MAX_PLAINTEXT_SIZE + 1))
+        );
+    }
+
+    #[test]
+    fn test_calc_padded_len() {
+        let cases = [
+            (1, 32),
+            (32, 32),
+            (33, 64),
+            (65, 96),
+            (100, 128),
+            (200, 224),
+            (250, 256),
+            (320, 320),
+            (383, 384),
+            (400, 448),
+            (500, 512),
+            (515, 640),
+            (700, 768),
+            (900, 1024),
+            (65535, 65536),
+        ];
+        for (len, padded) in cases {
+            assert_eq!(calc_padded_len(len), padded);
+        }
+    }
+}
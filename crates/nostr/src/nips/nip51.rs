@@ -0,0 +1,67 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP51
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/51.md>
+
+use crate::{Event, Kind, Tag};
+
+/// Normalize hashtag (trim, remove the leading `#` and lowercase)
+pub fn normalize_hashtag<S>(hashtag: S) -> String
+where
+    S: AsRef<str>,
+{
+    hashtag
+        .as_ref()
+        .trim()
+        .trim_start_matches('#')
+        .to_lowercase()
+}
+
+/// Extract the hashtags (`t` tags) from an interests list (kind `10015`)
+///
+/// Return an empty list if the [`Event`] isn't an interests list.
+pub fn get_interests(event: &Event) -> Vec<String> {
+    if event.kind != Kind::Interests {
+        return Vec::new();
+    }
+
+    let mut interests: Vec<String> = Vec::new();
+    for tag in event.tags.iter() {
+        if let Tag::Hashtag(hashtag) = tag {
+            let hashtag: String = normalize_hashtag(hashtag);
+            if !hashtag.is_empty() && !interests.contains(&hashtag) {
+                interests.push(hashtag);
+            }
+        }
+    }
+    interests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_interests_list() {
+        let keys = Keys::generate();
+        let event = EventBuilder::interests([" #Nostr", "bitcoin", "nostr"])
+            .to_event(&keys)
+            .unwrap();
+
+        assert_eq!(event.kind, Kind::Interests);
+        assert_eq!(
+            event.tags,
+            vec![
+                Tag::Hashtag(String::from("nostr")),
+                Tag::Hashtag(String::from("bitcoin"))
+            ]
+        );
+        assert_eq!(
+            get_interests(&event),
+            vec![String::from("nostr"), String::from("bitcoin")]
+        );
+    }
+}
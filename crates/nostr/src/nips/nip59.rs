@@ -0,0 +1,214 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP59
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/59.md>
+
+use secp256k1::rand::{self, Rng};
+use secp256k1::{SecretKey, XOnlyPublicKey};
+
+use super::nip44;
+use crate::event::{self, unsigned};
+use crate::{key, Event, EventBuilder, Keys, Kind, Tag, Timestamp, UnsignedEvent};
+
+/// `NIP59` error
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Key error
+    #[error(transparent)]
+    Key(#[from] key::Error),
+    /// Event error
+    #[error(transparent)]
+    Event(#[from] event::Error),
+    /// Unsigned event error
+    #[error(transparent)]
+    Unsigned(#[from] unsigned::Error),
+    /// NIP44 error
+    #[error(transparent)]
+    NIP44(#[from] nip44::Error),
+    /// Not a gift wrap
+    #[error("not a gift wrap")]
+    NotGiftWrap,
+    /// Not a seal
+    #[error("not a seal")]
+    NotSeal,
+    /// The rumor author doesn't match the seal author
+    #[error("rumor author doesn't match the seal author")]
+    SenderMismatch,
+}
+
+/// Unwrapped gift wrap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnwrappedGift {
+    /// Sender public key (author of the seal)
+    pub sender: XOnlyPublicKey,
+    /// Rumor
+    pub rumor: UnsignedEvent,
+}
+
+/// Seal the `rumor` for `receiver_pubkey` (kind `13`)
+pub fn make_seal(
+    sender_keys: &Keys,
+    receiver_pubkey: &XOnlyPublicKey,
+    rumor: &UnsignedEvent,
+) -> Result<Event, Error> {
+    make_seal_with_rng(&mut rand::thread_rng(), sender_keys, receiver_pubkey, rumor)
+}
+
+/// Seal the `rumor` for `receiver_pubkey` (kind `13`), tweaking the `created_at` with a custom [`Rng`]
+pub fn make_seal_with_rng<R>(
+    rng: &mut R,
+    sender_keys: &Keys,
+    receiver_pubkey: &XOnlyPublicKey,
+    rumor: &UnsignedEvent,
+) -> Result<Event, Error>
+where
+    R: Rng,
+{
+    let sender_sk: SecretKey = sender_keys.secret_key()?;
+    let content: String = nip44::encrypt(&sender_sk, receiver_pubkey, rumor.as_json())?;
+    Ok(EventBuilder::new(Kind::Seal, content, &[])
+        .custom_created_at(Timestamp::tweaked_with_rng(rng))
+        .to_unsigned_event(sender_keys.public_key())
+        .sign(sender_keys)?)
+}
+
+/// Seal the `rumor` and wrap it, using an ephemeral key, for `receiver_pubkey` (kind `1059`)
+pub fn make_gift_wrap(
+    sender_keys: &Keys,
+    receiver_pubkey: &XOnlyPublicKey,
+    rumor: &UnsignedEvent,
+) -> Result<Event, Error> {
+    make_gift_wrap_with_rng(&mut rand::thread_rng(), sender_keys, receiver_pubkey, rumor)
+}
+
+/// Seal the `rumor` and wrap it for `receiver_pubkey` (kind `1059`), tweaking the `created_at`
+/// of both the seal and the gift wrap with a custom [`Rng`]
+pub fn make_gift_wrap_with_rng<R>(
+    rng: &mut R,
+    sender_keys: &Keys,
+    receiver_pubkey: &XOnlyPublicKey,
+    rumor: &UnsignedEvent,
+) -> Result<Event, Error>
+where
+    R: Rng,
+{
+    let seal: Event = make_seal_with_rng(rng, sender_keys, receiver_pubkey, rumor)?;
+
+    let keys = Keys::generate();
+    let content: String = nip44::encrypt(&keys.secret_key()?, receiver_pubkey, seal.as_json())?;
+    Ok(EventBuilder::new(
+        Kind::GiftWrap,
+        content,
+        &[Tag::PubKey(*receiver_pubkey, None)],
+    )
+    .custom_created_at(Timestamp::tweaked_with_rng(rng))
+    .to_unsigned_event(keys.public_key())
+    .sign(&keys)?)
+}
+
+/// Decrypt the gift wrap and the seal and extract the rumor
+///
+/// The seal signature is verified and the rumor author must match the seal author.
+pub fn extract_rumor(receiver_keys: &Keys, gift_wrap: &Event) -> Result<UnwrappedGift, Error> {
+    if gift_wrap.kind != Kind::GiftWrap {
+        return Err(Error::NotGiftWrap);
+    }
+
+    let receiver_sk: SecretKey = receiver_keys.secret_key()?;
+
+    let seal: String = nip44::decrypt(&receiver_sk, &gift_wrap.pubkey, &gift_wrap.content)?;
+    let seal: Event = Event::from_json(seal)?;
+    if seal.kind != Kind::Seal {
+        return Err(Error::NotSeal);
+    }
+    seal.verify()?;
+
+    let rumor: String = nip44::decrypt(&receiver_sk, &seal.pubkey, &seal.content)?;
+    let rumor: UnsignedEvent = UnsignedEvent::from_json(rumor)?;
+    if rumor.pubkey != seal.pubkey {
+        return Err(Error::SenderMismatch);
+    }
+
+    Ok(UnwrappedGift {
+        sender: seal.pubkey,
+        rumor,
+    })
+}
+
+/// Unwrap a gift wrap and return the rumor
+///
+/// See [`extract_rumor`] to get also the sender.
+pub fn unwrap_gift_wrap(receiver_keys: &Keys, gift_wrap: &Event) -> Result<UnsignedEvent, Error> {
+    Ok(extract_rumor(receiver_keys, gift_wrap)?.rumor)
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::rand::rngs::mock::StepRng;
+
+    use super::*;
+
+    #[test]
+    fn test_gift_wrap_round_trip() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let rumor = EventBuilder::new_text_note("Are you going to the party tonight?", &[])
+            .to_unsigned_event(alice.public_key());
+        let gift_wrap = make_gift_wrap(&alice, &bob.public_key(), &rumor).unwrap();
+
+        assert_eq!(gift_wrap.kind, Kind::GiftWrap);
+        assert_ne!(gift_wrap.pubkey, alice.public_key());
+        assert!(gift_wrap.verify().is_ok());
+
+        let unwrapped = extract_rumor(&bob, &gift_wrap).unwrap();
+        assert_eq!(unwrapped.sender, alice.public_key());
+        assert_eq!(unwrapped.rumor, rumor);
+
+        // Only the receiver can unwrap
+        assert!(extract_rumor(&alice, &gift_wrap).is_err());
+    }
+
+    #[test]
+    fn test_unwrap_gift_wrap() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let rumor =
+            EventBuilder::new_text_note("Secret", &[]).to_unsigned_event(alice.public_key());
+        let first = EventBuilder::gift_wrap(&alice, bob.public_key(), rumor.clone()).unwrap();
+        let second = EventBuilder::gift_wrap(&alice, bob.public_key(), rumor.clone()).unwrap();
+
+        // Fresh ephemeral key for every gift wrap
+        assert_ne!(first.pubkey, second.pubkey);
+        assert_ne!(first.pubkey, alice.public_key());
+        assert!(first.tags.contains(&Tag::PubKey(bob.public_key(), None)));
+
+        assert_eq!(unwrap_gift_wrap(&bob, &first).unwrap(), rumor);
+        assert_eq!(unwrap_gift_wrap(&bob, &second).unwrap(), rumor);
+    }
+
+    #[test]
+    fn test_gift_wrap_with_rng() {
+        let alice = Keys::generate();
+        let bob = Keys::generate();
+
+        let rumor =
+            EventBuilder::new_text_note("Secret", &[]).to_unsigned_event(alice.public_key());
+
+        // Zero tweak: the seal and the gift wrap are dated now
+        let mut rng = StepRng::new(0, 0);
+        let before = Timestamp::now();
+        let seal = make_seal_with_rng(&mut rng, &alice, &bob.public_key(), &rumor).unwrap();
+        let gift_wrap =
+            make_gift_wrap_with_rng(&mut rng, &alice, &bob.public_key(), &rumor).unwrap();
+        let after = Timestamp::now();
+        for created_at in [seal.created_at, gift_wrap.created_at] {
+            assert!(created_at >= before && created_at <= after);
+        }
+
+        assert_eq!(unwrap_gift_wrap(&bob, &gift_wrap).unwrap(), rumor);
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP68
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/68.md>
+
+use url::Url;
+
+use crate::event::tag::TagKind;
+use crate::{Event, Kind, Tag};
+
+/// NIP68 error
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Picture post without images
+    #[error("picture post must have at least one image")]
+    NoImages,
+    /// Missing image url
+    #[error("missing image url")]
+    MissingUrl,
+    /// Invalid dimensions
+    #[error("invalid dimensions: expected <width>x<height>")]
+    InvalidDimensions,
+    /// Wrong event kind
+    #[error("not a picture post")]
+    WrongKind,
+    /// Url parse error
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+}
+
+/// Image of a [`PicturePost`] (`imeta` tag)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PictureImage {
+    /// Url
+    pub url: Url,
+    /// Mime type
+    pub mime_type: Option<String>,
+    /// Dimensions in pixels (width, height)
+    pub dimensions: Option<(u64, u64)>,
+    /// Blurhash
+    pub blurhash: Option<String>,
+}
+
+impl PictureImage {
+    /// New [`PictureImage`]
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            mime_type: None,
+            dimensions: None,
+            blurhash: None,
+        }
+    }
+
+    /// Set mime type
+    pub fn mime_type<S>(self, mime_type: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            mime_type: Some(mime_type.into()),
+            ..self
+        }
+    }
+
+    /// Set dimensions
+    pub fn dimensions(self, width: u64, height: u64) -> Self {
+        Self {
+            dimensions: Some((width, height)),
+            ..self
+        }
+    }
+
+    /// Set blurhash
+    pub fn blurhash<S>(self, blurhash: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            blurhash: Some(blurhash.into()),
+            ..self
+        }
+    }
+
+    /// Parse [`PictureImage`] from `imeta` tag values
+    pub fn from_values(values: &[String]) -> Result<Self, Error> {
+        let mut url: Option<Url> = None;
+        let mut mime_type: Option<String> = None;
+        let mut dimensions: Option<(u64, u64)> = None;
+        let mut blurhash: Option<String> = None;
+        for value in values.iter() {
+            match value.split_once(' ') {
+                Some(("url", u)) => url = Some(Url::parse(u)?),
+                Some(("m", m)) => mime_type = Some(m.to_string()),
+                Some(("dim", dim)) => {
+                    let (width, height) = dim.split_once('x').ok_or(Error::InvalidDimensions)?;
+                    dimensions = Some((
+                        width.parse().map_err(|_| Error::InvalidDimensions)?,
+                        height.parse().map_err(|_| Error::InvalidDimensions)?,
+                    ));
+                }
+                Some(("blurhash", b)) => blurhash = Some(b.to_string()),
+                _ => (),
+            }
+        }
+        Ok(Self {
+            url: url.ok_or(Error::MissingUrl)?,
+            mime_type,
+            dimensions,
+            blurhash,
+        })
+    }
+
+    /// Get `imeta` [`Tag`]
+    pub fn to_tag(&self) -> Tag {
+        let mut values: Vec<String> = vec![format!("url {}", self.url)];
+        if let Some(mime_type) = &self.mime_type {
+            values.push(format!("m {mime_type}"));
+        }
+        if let Some((width, height)) = self.dimensions {
+            values.push(format!("dim {width}x{height}"));
+        }
+        if let Some(blurhash) = &self.blurhash {
+            values.push(format!("blurhash {blurhash}"));
+        }
+        Tag::Generic(TagKind::Imeta, values)
+    }
+}
+
+/// Picture-first post
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PicturePost {
+    /// Title
+    pub title: Option<String>,
+    /// Description
+    pub description: String,
+    /// Images
+    pub images: Vec<PictureImage>,
+}
+
+impl PicturePost {
+    /// Parse [`PicturePost`] from [`Event`]
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::Picture {
+            return Err(Error::WrongKind);
+        }
+
+        let mut title: Option<String> = None;
+        let mut images: Vec<PictureImage> = Vec::new();
+        for tag in event.tags.iter() {
+            match tag {
+                Tag::Title(t) => title = Some(t.clone()),
+                Tag::Generic(TagKind::Imeta, values) => {
+                    images.push(PictureImage::from_values(values)?)
+                }
+                _ => (),
+            }
+        }
+
+        if images.is_empty() {
+            return Err(Error::NoImages);
+        }
+
+        Ok(Self {
+            title,
+            description: event.content.clone(),
+            images,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_picture_post() {
+        let keys = Keys::generate();
+        let images = vec![
+            PictureImage::new(Url::parse("https://example.com/a.jpg").unwrap())
+                .mime_type("image/jpeg")
+                .dimensions(3024, 4032)
+                .blurhash("eVF$^OI:${M{o#*0-nNFxakD-?xVM}WEWB%iNKxvR-oetmo#R-aen$"),
+            PictureImage::new(Url::parse("https://example.com/b.png").unwrap())
+                .mime_type("image/png"),
+        ];
+
+        let event = EventBuilder::picture_post("Sunset", "Two shots", &images)
+            .unwrap()
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::Picture);
+
+        let event = Event::from_json(event.as_json()).unwrap();
+        let post = PicturePost::from_event(&event).unwrap();
+        assert_eq!(post.title, Some(String::from("Sunset")));
+        assert_eq!(post.description, "Two shots");
+        assert_eq!(post.images, images);
+
+        assert!(matches!(
+            EventBuilder::picture_post("Empty", "", &[]),
+            Err(crate::event::builder::Error::NIP68(Error::NoImages))
+        ));
+    }
+}
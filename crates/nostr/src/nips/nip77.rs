@@ -0,0 +1,589 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP77
+//!
+//! Negentropy set reconciliation (protocol version `0x61`).
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/77.md>
+
+use std::collections::HashSet;
+
+use bitcoin_hashes::sha256::Hash as Sha256Hash;
+use bitcoin_hashes::Hash;
+
+use crate::{EventId, Timestamp};
+
+/// Negentropy protocol version
+pub const PROTOCOL_VERSION: u8 = 0x61;
+
+const ID_SIZE: usize = 32;
+const FINGERPRINT_SIZE: usize = 16;
+const BUCKETS: usize = 16;
+const DOUBLE_BUCKETS: usize = BUCKETS * 2;
+
+const MODE_SKIP: u64 = 0;
+const MODE_FINGERPRINT: u64 = 1;
+const MODE_ID_LIST: u64 = 2;
+
+/// NIP77 error
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Invalid hex message
+    #[error("invalid hex message")]
+    InvalidHex,
+    /// Unsupported protocol version
+    #[error("unsupported protocol version: {0:#x}")]
+    UnsupportedProtocolVersion(u8),
+    /// Unexpected end of message
+    #[error("unexpected end of message")]
+    UnexpectedEnd,
+    /// Invalid mode
+    #[error("invalid mode: {0}")]
+    InvalidMode(u64),
+    /// Invalid id prefix length
+    #[error("invalid id prefix length")]
+    InvalidIdPrefix,
+    /// The method is reserved to the initiator
+    #[error("not the initiator")]
+    NotInitiator,
+    /// The method is reserved to the responder
+    #[error("not the responder")]
+    NotResponder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Item {
+    timestamp: u64,
+    id: [u8; ID_SIZE],
+}
+
+/// Range upper bound: the `id` is the prefix padded with zeros
+#[derive(Debug, Clone, Copy)]
+struct Bound {
+    item: Item,
+    prefix_len: usize,
+}
+
+impl Bound {
+    fn new(timestamp: u64) -> Self {
+        Self {
+            item: Item {
+                timestamp,
+                id: [0; ID_SIZE],
+            },
+            prefix_len: 0,
+        }
+    }
+
+    /// Shortest bound `b` such that `prev < b <= curr`
+    fn minimal(prev: &Item, curr: &Item) -> Self {
+        if curr.timestamp != prev.timestamp {
+            return Self::new(curr.timestamp);
+        }
+
+        let shared: usize = prev
+            .id
+            .iter()
+            .zip(curr.id.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let prefix_len: usize = (shared + 1).min(ID_SIZE);
+        let mut id = [0; ID_SIZE];
+        id[..prefix_len].copy_from_slice(&curr.id[..prefix_len]);
+        Self {
+            item: Item {
+                timestamp: curr.timestamp,
+                id,
+            },
+            prefix_len,
+        }
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    last_timestamp: u64,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            last_timestamp: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.buf.len() < len {
+            return Err(Error::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut res: u64 = 0;
+        loop {
+            let byte: u8 = self.byte()?;
+            res = (res << 7) | (byte & 0x7F) as u64;
+            if byte & 0x80 == 0 {
+                return Ok(res);
+            }
+        }
+    }
+
+    fn timestamp(&mut self) -> Result<u64, Error> {
+        let timestamp: u64 = match self.varint()? {
+            0 => u64::MAX,
+            t => t - 1,
+        };
+        if self.last_timestamp == u64::MAX || timestamp == u64::MAX {
+            self.last_timestamp = u64::MAX;
+            return Ok(u64::MAX);
+        }
+        self.last_timestamp = self.last_timestamp.saturating_add(timestamp);
+        Ok(self.last_timestamp)
+    }
+
+    fn bound(&mut self) -> Result<Bound, Error> {
+        let timestamp: u64 = self.timestamp()?;
+        let prefix_len: usize = self.varint()? as usize;
+        if prefix_len > ID_SIZE {
+            return Err(Error::InvalidIdPrefix);
+        }
+        let mut id = [0; ID_SIZE];
+        id[..prefix_len].copy_from_slice(self.bytes(prefix_len)?);
+        Ok(Bound {
+            item: Item { timestamp, id },
+            prefix_len,
+        })
+    }
+}
+
+struct Writer {
+    buf: Vec<u8>,
+    last_timestamp: u64,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            buf: vec![PROTOCOL_VERSION],
+            last_timestamp: 0,
+        }
+    }
+
+    fn varint(&mut self, mut n: u64) {
+        let mut bytes: Vec<u8> = Vec::new();
+        loop {
+            bytes.push((n & 0x7F) as u8);
+            n >>= 7;
+            if n == 0 {
+                break;
+            }
+        }
+        while let Some(byte) = bytes.pop() {
+            let more: u8 = if bytes.is_empty() { 0 } else { 0x80 };
+            self.buf.push(byte | more);
+        }
+    }
+
+    fn timestamp(&mut self, timestamp: u64) {
+        if timestamp == u64::MAX {
+            self.last_timestamp = u64::MAX;
+            self.varint(0);
+        } else {
+            let delta: u64 = timestamp.saturating_sub(self.last_timestamp);
+            self.last_timestamp = timestamp;
+            self.varint(delta + 1);
+        }
+    }
+
+    fn bound(&mut self, bound: &Bound) {
+        self.timestamp(bound.item.timestamp);
+        self.varint(bound.prefix_len as u64);
+        self.buf
+            .extend_from_slice(&bound.item.id[..bound.prefix_len]);
+    }
+
+    /// Empty message (protocol version only)
+    fn is_empty(&self) -> bool {
+        self.buf.len() == 1
+    }
+}
+
+/// Negentropy reconciliation state
+///
+/// The initiator (client) calls [`Negentropy::initiate`] and then [`Negentropy::reconcile_with_ids`]
+/// for every `NEG-MSG` received, until it returns `None`.
+/// The responder (relay) answers each message with [`Negentropy::reconcile`].
+#[derive(Debug, Clone)]
+pub struct Negentropy {
+    items: Vec<Item>,
+    initiator: bool,
+}
+
+impl Negentropy {
+    /// New [`Negentropy`] from the `(created_at, id)` of the local events
+    pub fn new<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = (Timestamp, EventId)>,
+    {
+        let mut items: Vec<Item> = items
+            .into_iter()
+            .map(|(created_at, event_id)| {
+                let mut id = [0; ID_SIZE];
+                id.copy_from_slice(event_id.as_bytes());
+                Item {
+                    timestamp: created_at.as_u64(),
+                    id,
+                }
+            })
+            .collect();
+        items.sort();
+        items.dedup();
+        Self {
+            items,
+            initiator: false,
+        }
+    }
+
+    /// Build the initial message (hex) of the `NEG-OPEN`
+    pub fn initiate(&mut self) -> String {
+        self.initiator = true;
+        let mut writer = Writer::new();
+        self.split_range(&mut writer, 0, self.items.len(), Bound::new(u64::MAX));
+        to_hex(&writer.buf)
+    }
+
+    /// Answer to a message of the initiator (responder side)
+    pub fn reconcile(&mut self, msg: &str) -> Result<String, Error> {
+        if self.initiator {
+            return Err(Error::NotResponder);
+        }
+        let writer: Writer =
+            self.reconcile_aux(&from_hex(msg)?, &mut Vec::new(), &mut Vec::new())?;
+        Ok(to_hex(&writer.buf))
+    }
+
+    /// Process a message of the responder (initiator side)
+    ///
+    /// Collect the ids that only the initiator has into `have_ids` and the ids that only the responder has into `need_ids`.
+    /// Return the next message to send or `None` if the reconciliation is complete.
+    pub fn reconcile_with_ids(
+        &mut self,
+        msg: &str,
+        have_ids: &mut Vec<EventId>,
+        need_ids: &mut Vec<EventId>,
+    ) -> Result<Option<String>, Error> {
+        if !self.initiator {
+            return Err(Error::NotInitiator);
+        }
+
+        let mut have: Vec<[u8; ID_SIZE]> = Vec::new();
+        let mut need: Vec<[u8; ID_SIZE]> = Vec::new();
+        let writer: Writer = self.reconcile_aux(&from_hex(msg)?, &mut have, &mut need)?;
+        have_ids.extend(have.iter().map(to_event_id));
+        need_ids.extend(need.iter().map(to_event_id));
+
+        if writer.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(to_hex(&writer.buf)))
+        }
+    }
+
+    fn reconcile_aux(
+        &self,
+        msg: &[u8],
+        have: &mut Vec<[u8; ID_SIZE]>,
+        need: &mut Vec<[u8; ID_SIZE]>,
+    ) -> Result<Writer, Error> {
+        let mut reader = Reader::new(msg);
+        let version: u8 = reader.byte()?;
+        if version != PROTOCOL_VERSION {
+            return Err(Error::UnsupportedProtocolVersion(version));
+        }
+
+        let mut writer = Writer::new();
+        let mut prev_bound = Bound::new(0);
+        let mut prev_index: usize = 0;
+        let mut skip: bool = false;
+
+        while !reader.is_empty() {
+            let curr_bound: Bound = reader.bound()?;
+            let mode: u64 = reader.varint()?;
+
+            let lower: usize = prev_index;
+            let upper: usize = self.find_lower_bound(prev_index, &curr_bound);
+
+            match mode {
+                MODE_SKIP => skip = true,
+                MODE_FINGERPRINT => {
+                    let theirs: &[u8] = reader.bytes(FINGERPRINT_SIZE)?;
+                    if theirs != self.fingerprint(lower, upper) {
+                        if skip {
+                            skip = false;
+                            writer.bound(&prev_bound);
+                            writer.varint(MODE_SKIP);
+                        }
+                        self.split_range(&mut writer, lower, upper, curr_bound);
+                    } else {
+                        skip = true;
+                    }
+                }
+                MODE_ID_LIST => {
+                    let len: u64 = reader.varint()?;
+                    let mut theirs: HashSet<[u8; ID_SIZE]> = HashSet::new();
+                    for _ in 0..len {
+                        let mut id = [0; ID_SIZE];
+                        id.copy_from_slice(reader.bytes(ID_SIZE)?);
+                        theirs.insert(id);
+                    }
+
+                    if self.initiator {
+                        for item in self.items[lower..upper].iter() {
+                            if !theirs.remove(&item.id) {
+                                have.push(item.id);
+                            }
+                        }
+                        need.extend(theirs);
+                        skip = true;
+                    } else {
+                        if skip {
+                            skip = false;
+                            writer.bound(&prev_bound);
+                            writer.varint(MODE_SKIP);
+                        }
+                        writer.bound(&curr_bound);
+                        writer.varint(MODE_ID_LIST);
+                        writer.varint((upper - lower) as u64);
+                        for item in self.items[lower..upper].iter() {
+                            writer.buf.extend_from_slice(&item.id);
+                        }
+                    }
+                }
+                mode => return Err(Error::InvalidMode(mode)),
+            }
+
+            prev_index = upper;
+            prev_bound = curr_bound;
+        }
+
+        Ok(writer)
+    }
+
+    /// Index of the first item not lower than `bound`, starting from `begin`
+    fn find_lower_bound(&self, begin: usize, bound: &Bound) -> usize {
+        begin + self.items[begin..].partition_point(|item| *item < bound.item)
+    }
+
+    fn fingerprint(&self, lower: usize, upper: usize) -> [u8; FINGERPRINT_SIZE] {
+        // Sum of the ids as 256-bit little-endian integers (mod 2^256)
+        let mut sum = [0u8; ID_SIZE];
+        for item in self.items[lower..upper].iter() {
+            let mut carry: u16 = 0;
+            for (s, i) in sum.iter_mut().zip(item.id.iter()) {
+                let value: u16 = *s as u16 + *i as u16 + carry;
+                *s = value as u8;
+                carry = value >> 8;
+            }
+        }
+
+        let mut writer = Writer::new();
+        writer.buf.clear();
+        writer.buf.extend_from_slice(&sum);
+        writer.varint((upper - lower) as u64);
+
+        let hash = Sha256Hash::hash(&writer.buf);
+        let hash: &[u8] = hash.as_ref();
+        let mut fingerprint = [0u8; FINGERPRINT_SIZE];
+        fingerprint.copy_from_slice(&hash[..FINGERPRINT_SIZE]);
+        fingerprint
+    }
+
+    fn split_range(&self, writer: &mut Writer, lower: usize, upper: usize, upper_bound: Bound) {
+        let len: usize = upper - lower;
+
+        if len < DOUBLE_BUCKETS {
+            writer.bound(&upper_bound);
+            writer.varint(MODE_ID_LIST);
+            writer.varint(len as u64);
+            for item in self.items[lower..upper].iter() {
+                writer.buf.extend_from_slice(&item.id);
+            }
+            return;
+        }
+
+        let per_bucket: usize = len / BUCKETS;
+        let with_extra: usize = len % BUCKETS;
+        let mut curr: usize = lower;
+        for i in 0..BUCKETS {
+            let size: usize = per_bucket + usize::from(i < with_extra);
+            let fingerprint = self.fingerprint(curr, curr + size);
+            curr += size;
+
+            let bound: Bound = if curr == upper {
+                upper_bound
+            } else {
+                Bound::minimal(&self.items[curr - 1], &self.items[curr])
+            };
+            writer.bound(&bound);
+            writer.varint(MODE_FINGERPRINT);
+            writer.buf.extend_from_slice(&fingerprint);
+        }
+    }
+}
+
+fn to_event_id(id: &[u8; ID_SIZE]) -> EventId {
+    EventId::from_slice(id).expect("Id is 32 bytes long")
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    if hex.len() % 2 != 0 {
+        return Err(Error::InvalidHex);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or(Error::InvalidHex)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u32) -> EventId {
+        EventId::from(Sha256Hash::hash(&n.to_be_bytes()))
+    }
+
+    fn items(range: std::ops::Range<u32>) -> Vec<(Timestamp, EventId)> {
+        range
+            .map(|n| (Timestamp::from(1_000 + (n / 3) as u64), id(n)))
+            .collect()
+    }
+
+    fn reconcile(client: Vec<(Timestamp, EventId)>, server: Vec<(Timestamp, EventId)>) {
+        let mut initiator = Negentropy::new(client.clone());
+        let mut responder = Negentropy::new(server.clone());
+
+        let mut have_ids: Vec<EventId> = Vec::new();
+        let mut need_ids: Vec<EventId> = Vec::new();
+        let mut msg: String = initiator.initiate();
+        let mut rounds: usize = 0;
+        loop {
+            rounds += 1;
+            assert!(rounds < 20, "reconciliation doesn't converge");
+            let response: String = responder.reconcile(&msg).unwrap();
+            match initiator
+                .reconcile_with_ids(&response, &mut have_ids, &mut need_ids)
+                .unwrap()
+            {
+                Some(next) => msg = next,
+                None => break,
+            }
+        }
+
+        let client_ids: HashSet<EventId> = client.iter().map(|(_, id)| *id).collect();
+        let server_ids: HashSet<EventId> = server.iter().map(|(_, id)| *id).collect();
+        let have_ids: HashSet<EventId> = have_ids.into_iter().collect();
+        let need_ids: HashSet<EventId> = need_ids.into_iter().collect();
+        assert_eq!(
+            have_ids,
+            client_ids.difference(&server_ids).copied().collect()
+        );
+        assert_eq!(
+            need_ids,
+            server_ids.difference(&client_ids).copied().collect()
+        );
+    }
+
+    #[test]
+    fn test_varint() {
+        for n in [
+            0,
+            1,
+            127,
+            128,
+            255,
+            16_383,
+            16_384,
+            u32::MAX as u64,
+            u64::MAX,
+        ] {
+            let mut writer = Writer::new();
+            writer.buf.clear();
+            writer.varint(n);
+            let mut reader = Reader::new(&writer.buf);
+            assert_eq!(reader.varint().unwrap(), n);
+            assert!(reader.is_empty());
+        }
+
+        let mut writer = Writer::new();
+        writer.buf.clear();
+        writer.varint(300);
+        assert_eq!(writer.buf, vec![0x82, 0x2C]);
+    }
+
+    #[test]
+    fn test_reconcile_small_sets() {
+        reconcile(Vec::new(), Vec::new());
+        reconcile(items(0..10), Vec::new());
+        reconcile(Vec::new(), items(0..10));
+        reconcile(items(0..10), items(5..15));
+        reconcile(items(0..10), items(0..10));
+    }
+
+    #[test]
+    fn test_reconcile_large_sets() {
+        // Split into fingerprint ranges
+        reconcile(items(0..1_000), items(0..1_000));
+        reconcile(items(0..1_000), items(500..1_500));
+        reconcile(items(0..2_000), items(1..1_999));
+
+        let mut server = items(0..1_000);
+        server.remove(437);
+        server.extend(items(5_000..5_003));
+        reconcile(items(0..1_000), server);
+    }
+
+    #[test]
+    fn test_invalid_messages() {
+        let mut responder = Negentropy::new(items(0..10));
+        assert_eq!(responder.reconcile("6"), Err(Error::InvalidHex));
+        assert_eq!(responder.reconcile("zz"), Err(Error::InvalidHex));
+        assert_eq!(
+            responder.reconcile("62"),
+            Err(Error::UnsupportedProtocolVersion(0x62))
+        );
+        // Bound without mode
+        assert_eq!(responder.reconcile("610000"), Err(Error::UnexpectedEnd));
+        assert_eq!(responder.reconcile("61000005"), Err(Error::InvalidMode(5)));
+
+        let mut initiator = Negentropy::new(items(0..10));
+        assert_eq!(
+            initiator.reconcile_with_ids("61", &mut Vec::new(), &mut Vec::new()),
+            Err(Error::NotInitiator)
+        );
+        initiator.initiate();
+        assert_eq!(initiator.reconcile("61"), Err(Error::NotResponder));
+    }
+}
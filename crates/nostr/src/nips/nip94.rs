@@ -0,0 +1,241 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! NIP94
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/94.md>
+
+use std::str::FromStr;
+
+use bitcoin_hashes::sha256::Hash as Sha256Hash;
+use url::Url;
+
+use crate::event::tag::TagKind;
+use crate::{Event, Kind, Tag};
+
+/// NIP94 error
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Missing url
+    #[error("missing url")]
+    MissingUrl,
+    /// Missing mime type
+    #[error("missing mime type")]
+    MissingMimeType,
+    /// Missing sha256 hash
+    #[error("missing sha256 hash")]
+    MissingHash,
+    /// Invalid sha256 hash
+    #[error("invalid sha256 hash: expected 64 hex chars")]
+    InvalidHash,
+    /// Invalid size
+    #[error("invalid size")]
+    InvalidSize,
+    /// Invalid dimensions
+    #[error("invalid dimensions: expected <width>x<height>")]
+    InvalidDimensions,
+    /// Wrong event kind
+    #[error("not a file metadata event")]
+    WrongKind,
+    /// Url parse error
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+}
+
+/// File metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Url
+    pub url: Url,
+    /// Mime type
+    pub mime_type: String,
+    /// SHA256 of the file
+    pub hash: Sha256Hash,
+    /// Size in bytes
+    pub size: Option<u64>,
+    /// Dimensions in pixels (width, height)
+    pub dimensions: Option<(u64, u64)>,
+    /// Magnet URI
+    pub magnet: Option<String>,
+    /// Blurhash
+    pub blurhash: Option<String>,
+}
+
+impl FileMetadata {
+    /// New [`FileMetadata`]
+    pub fn new<S>(url: Url, mime_type: S, hash: Sha256Hash) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            url,
+            mime_type: mime_type.into(),
+            hash,
+            size: None,
+            dimensions: None,
+            magnet: None,
+            blurhash: None,
+        }
+    }
+
+    /// Set size in bytes
+    pub fn size(self, size: u64) -> Self {
+        Self {
+            size: Some(size),
+            ..self
+        }
+    }
+
+    /// Set dimensions
+    pub fn dimensions(self, width: u64, height: u64) -> Self {
+        Self {
+            dimensions: Some((width, height)),
+            ..self
+        }
+    }
+
+    /// Set magnet URI
+    pub fn magnet<S>(self, magnet: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            magnet: Some(magnet.into()),
+            ..self
+        }
+    }
+
+    /// Set blurhash
+    pub fn blurhash<S>(self, blurhash: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            blurhash: Some(blurhash.into()),
+            ..self
+        }
+    }
+
+    /// Get the [`Tag`]s (`url`, `m`, `x`, `size`, `dim`, `magnet` and `blurhash`)
+    pub fn to_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = vec![
+            Tag::Generic(TagKind::Url, vec![self.url.to_string()]),
+            Tag::Generic(TagKind::MimeType, vec![self.mime_type.clone()]),
+            Tag::Generic(TagKind::Sha256, vec![self.hash.to_string()]),
+        ];
+        if let Some(size) = self.size {
+            tags.push(Tag::Generic(TagKind::Size, vec![size.to_string()]));
+        }
+        if let Some((width, height)) = self.dimensions {
+            tags.push(Tag::Generic(
+                TagKind::Dim,
+                vec![format!("{width}x{height}")],
+            ));
+        }
+        if let Some(magnet) = &self.magnet {
+            tags.push(Tag::Generic(TagKind::Magnet, vec![magnet.clone()]));
+        }
+        if let Some(blurhash) = &self.blurhash {
+            tags.push(Tag::Generic(TagKind::Blurhash, vec![blurhash.clone()]));
+        }
+        tags
+    }
+
+    /// Parse [`FileMetadata`] from [`Event`]
+    ///
+    /// The `x` tag must be a 64 chars hex string.
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        if event.kind != Kind::FileMetadata {
+            return Err(Error::WrongKind);
+        }
+
+        let mut url: Option<Url> = None;
+        let mut mime_type: Option<String> = None;
+        let mut hash: Option<Sha256Hash> = None;
+        let mut size: Option<u64> = None;
+        let mut dimensions: Option<(u64, u64)> = None;
+        let mut magnet: Option<String> = None;
+        let mut blurhash: Option<String> = None;
+        for tag in event.tags.iter() {
+            if let Tag::Generic(kind, values) = tag {
+                let value: &str = match values.first() {
+                    Some(value) => value,
+                    None => continue,
+                };
+                match kind {
+                    TagKind::Url => url = Some(Url::parse(value)?),
+                    TagKind::MimeType => mime_type = Some(value.to_string()),
+                    TagKind::Sha256 => {
+                        hash = Some(Sha256Hash::from_str(value).map_err(|_| Error::InvalidHash)?)
+                    }
+                    TagKind::Size => size = Some(value.parse().map_err(|_| Error::InvalidSize)?),
+                    TagKind::Dim => {
+                        let (width, height) =
+                            value.split_once('x').ok_or(Error::InvalidDimensions)?;
+                        dimensions = Some((
+                            width.parse().map_err(|_| Error::InvalidDimensions)?,
+                            height.parse().map_err(|_| Error::InvalidDimensions)?,
+                        ));
+                    }
+                    TagKind::Magnet => magnet = Some(value.to_string()),
+                    TagKind::Blurhash => blurhash = Some(value.to_string()),
+                    _ => (),
+                }
+            }
+        }
+
+        Ok(Self {
+            url: url.ok_or(Error::MissingUrl)?,
+            mime_type: mime_type.ok_or(Error::MissingMimeType)?,
+            hash: hash.ok_or(Error::MissingHash)?,
+            size,
+            dimensions,
+            magnet,
+            blurhash,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin_hashes::Hash;
+
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_file_metadata() {
+        let keys = Keys::generate();
+        let metadata = FileMetadata::new(
+            Url::parse("https://example.com/sunset.jpg").unwrap(),
+            "image/jpeg",
+            Sha256Hash::hash(b"sunset"),
+        )
+        .size(1_024_000)
+        .dimensions(3024, 4032)
+        .blurhash("eVF$^OI:${M{o#*0-nNFxakD-?xVM}WEWB%iNKxvR-oetmo#R-aen$");
+
+        let event = EventBuilder::file_metadata("Sunset", metadata.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind, Kind::FileMetadata);
+        assert_eq!(event.content, "Sunset");
+
+        let event = Event::from_json(event.as_json()).unwrap();
+        assert_eq!(FileMetadata::from_event(&event).unwrap(), metadata);
+
+        // Short hash
+        let tags = vec![
+            Tag::Generic(TagKind::Url, vec![metadata.url.to_string()]),
+            Tag::Generic(TagKind::MimeType, vec![metadata.mime_type.clone()]),
+            Tag::Generic(TagKind::Sha256, vec![String::from("abcdef")]),
+        ];
+        let event = EventBuilder::new(Kind::FileMetadata, "", &tags)
+            .to_event(&keys)
+            .unwrap();
+        assert!(matches!(
+            FileMetadata::from_event(&event),
+            Err(Error::InvalidHash)
+        ));
+    }
+}
@@ -4,6 +4,7 @@
 //! Prelude
 
 // External crates
+pub use ::url::*;
 #[cfg(feature = "nip19")]
 pub use bech32::*;
 #[cfg(feature = "nip06")]
@@ -13,7 +14,6 @@ pub use bitcoin::*;
 pub use bitcoin_hashes::*;
 pub use secp256k1::*;
 pub use serde_json::*;
-pub use url::*;
 
 // Internal modules
 pub use crate::event::*;
@@ -29,12 +29,22 @@ pub use crate::nips::nip04::*;
 pub use crate::nips::nip05::*;
 #[cfg(feature = "nip06")]
 pub use crate::nips::nip06::*;
+pub use crate::nips::nip10::*;
 #[cfg(feature = "nip11")]
 pub use crate::nips::nip11::*;
 pub use crate::nips::nip13::*;
+#[cfg(feature = "nip59")]
+pub use crate::nips::nip17::*;
 #[cfg(feature = "nip19")]
 pub use crate::nips::nip19::*;
+#[cfg(feature = "nip21")]
+pub use crate::nips::nip21::*;
 pub use crate::nips::nip26::*;
+pub use crate::nips::nip32::*;
 #[cfg(feature = "nip46")]
 pub use crate::nips::nip46::*;
+pub use crate::nips::nip51::*;
+#[cfg(feature = "nip59")]
+pub use crate::nips::nip59::*;
 pub use crate::nips::nip65::*;
+pub use crate::nips::nip68::*;
@@ -3,8 +3,12 @@
 
 //! Contact
 
+use std::collections::BTreeMap;
+
 use secp256k1::XOnlyPublicKey;
 
+use crate::{Event, Tag, UncheckedUrl};
+
 /// Contact
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
 pub struct Contact {
@@ -29,3 +33,111 @@ impl Contact {
         }
     }
 }
+
+/// Read/write permissions of a relay of the [`ContactList`]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct RelayPermissions {
+    /// Read
+    pub read: bool,
+    /// Write
+    pub write: bool,
+}
+
+impl RelayPermissions {
+    /// New [`RelayPermissions`]
+    pub fn new(read: bool, write: bool) -> Self {
+        Self { read, write }
+    }
+}
+
+/// Contact list (kind 3)
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/02.md>
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ContactList {
+    /// Contacts (`p` tags)
+    pub contacts: Vec<Contact>,
+    /// Relays (JSON content)
+    pub relays: BTreeMap<UncheckedUrl, RelayPermissions>,
+}
+
+impl ContactList {
+    /// Parse [`ContactList`] from [`Event`]
+    ///
+    /// A malformed relay map in the content is ignored.
+    pub fn from_event(event: &Event) -> Self {
+        let contacts: Vec<Contact> = event
+            .tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Tag::PubKey(pk, relay_url) => Some(Contact::new(*pk, relay_url.clone(), None)),
+                Tag::ContactList {
+                    pk,
+                    relay_url,
+                    alias,
+                } => Some(Contact::new(*pk, relay_url.clone(), alias.clone())),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            contacts,
+            relays: serde_json::from_str(&event.content).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_contact_list_round_trip() {
+        let keys = Keys::generate();
+        let contacts = vec![
+            Contact::new(
+                Keys::generate().public_key(),
+                Some("wss://relay.damus.io"),
+                Some("alice"),
+            ),
+            Contact::new(
+                Keys::generate().public_key(),
+                Some("wss://nos.lol"),
+                Some("bob"),
+            ),
+        ];
+        let mut relays = BTreeMap::new();
+        relays.insert(
+            UncheckedUrl::from("wss://relay.damus.io"),
+            RelayPermissions::new(true, true),
+        );
+        relays.insert(
+            UncheckedUrl::from("wss://nos.lol"),
+            RelayPermissions::new(true, false),
+        );
+
+        let event = EventBuilder::set_contact_list_with_relays(contacts.clone(), relays.clone())
+            .to_event(&keys)
+            .unwrap();
+        let event = Event::from_json(event.as_json()).unwrap();
+
+        let list = ContactList::from_event(&event);
+        assert_eq!(list.contacts, contacts);
+        assert_eq!(list.relays, relays);
+    }
+
+    #[test]
+    fn test_contact_list_malformed_content() {
+        let keys = Keys::generate();
+        let contact = Contact::new(keys.public_key(), Some("wss://nos.lol"), Some("me"));
+        let mut event = EventBuilder::set_contact_list(vec![contact.clone()])
+            .to_event(&keys)
+            .unwrap();
+        event.content = String::from("{not json");
+
+        let list = ContactList::from_event(&event);
+        assert_eq!(list.contacts, vec![contact]);
+        assert!(list.relays.is_empty());
+    }
+}
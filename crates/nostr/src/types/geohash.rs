@@ -0,0 +1,189 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Geohash
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Tag;
+
+const BASE32: &str = "0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// [`Geohash`] error
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// Empty geohash
+    #[error("empty geohash")]
+    Empty,
+    /// Char not in the geohash base32 alphabet
+    #[error("invalid geohash char: {0}")]
+    InvalidChar(char),
+}
+
+/// Direction
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    /// Neighbor chars for even and odd length geohashes
+    fn neighbors(&self) -> [&'static str; 2] {
+        match self {
+            Self::North => [
+                "p0r21436x8zb9dcf5h7kjnmqesgutwvy",
+                "bc01fg45238967deuvhjyznpkmstqrwx",
+            ],
+            Self::South => [
+                "14365h7k9dcfesgujnmqp0r2twvyx8zb",
+                "238967debc01fg45kmstqrwxuvhjyznp",
+            ],
+            Self::East => [
+                "bc01fg45238967deuvhjyznpkmstqrwx",
+                "p0r21436x8zb9dcf5h7kjnmqesgutwvy",
+            ],
+            Self::West => [
+                "238967debc01fg45kmstqrwxuvhjyznp",
+                "14365h7k9dcfesgujnmqp0r2twvyx8zb",
+            ],
+        }
+    }
+
+    /// Border chars for even and odd length geohashes
+    fn borders(&self) -> [&'static str; 2] {
+        match self {
+            Self::North => ["prxz", "bcfguvyz"],
+            Self::South => ["028b", "0145hjnp"],
+            Self::East => ["bcfguvyz", "prxz"],
+            Self::West => ["0145hjnp", "028b"],
+        }
+    }
+}
+
+/// Geohash (`g` tag)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Geohash(String);
+
+impl Geohash {
+    /// Parse [`Geohash`]
+    pub fn new<S>(geohash: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let geohash: String = geohash.into().to_lowercase();
+        if geohash.is_empty() {
+            return Err(Error::Empty);
+        }
+        if let Some(c) = geohash.chars().find(|c| !BASE32.contains(*c)) {
+            return Err(Error::InvalidChar(c));
+        }
+        Ok(Self(geohash))
+    }
+
+    /// Get precision (number of chars)
+    pub fn precision(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Get the first `len` chars, `None` if `len` is 0 or greater than the precision
+    pub fn prefix(&self, len: usize) -> Option<Self> {
+        if len == 0 || len > self.precision() {
+            return None;
+        }
+        Some(Self(self.0[..len].to_string()))
+    }
+
+    /// Get the 8 neighbors, clockwise from north: `[n, ne, e, se, s, sw, w, nw]`
+    pub fn neighbors(&self) -> [Self; 8] {
+        let n: String = adjacent(&self.0, Direction::North);
+        let s: String = adjacent(&self.0, Direction::South);
+        [
+            Self(n.clone()),
+            Self(adjacent(&n, Direction::East)),
+            Self(adjacent(&self.0, Direction::East)),
+            Self(adjacent(&s, Direction::East)),
+            Self(s.clone()),
+            Self(adjacent(&s, Direction::West)),
+            Self(adjacent(&self.0, Direction::West)),
+            Self(adjacent(&n, Direction::West)),
+        ]
+    }
+
+    /// Get as `&str`
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Adjacent geohash (valid geohash required)
+fn adjacent(geohash: &str, direction: Direction) -> String {
+    let last: char = match geohash.chars().last() {
+        Some(c) => c,
+        None => return String::new(),
+    };
+    let mut parent: String = geohash[..geohash.len() - 1].to_string();
+    let kind: usize = geohash.len() % 2;
+
+    if direction.borders()[kind].contains(last) && !parent.is_empty() {
+        parent = adjacent(&parent, direction);
+    }
+
+    let index: usize = direction.neighbors()[kind].find(last).unwrap_or_default();
+    parent.push(BASE32.as_bytes()[index] as char);
+    parent
+}
+
+impl FromStr for Geohash {
+    type Err = Error;
+
+    fn from_str(geohash: &str) -> Result<Self, Self::Err> {
+        Self::new(geohash)
+    }
+}
+
+impl fmt::Display for Geohash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Geohash> for Tag {
+    fn from(geohash: Geohash) -> Self {
+        Self::Geohash(geohash.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geohash() {
+        let geohash = Geohash::new("GBSUV").unwrap();
+        assert_eq!(geohash.as_str(), "gbsuv");
+        assert_eq!(geohash.precision(), 5);
+        assert_eq!(geohash.prefix(3), Some(Geohash::new("gbs").unwrap()));
+        assert_eq!(geohash.prefix(6), None);
+
+        let neighbors: Vec<String> = geohash.neighbors().iter().map(|g| g.to_string()).collect();
+        assert_eq!(
+            neighbors,
+            vec!["gbsvj", "gbsvn", "gbsuy", "gbsuw", "gbsut", "gbsus", "gbsuu", "gbsvh"]
+        );
+
+        assert_eq!(
+            Tag::from(geohash).as_vec(),
+            vec![String::from("g"), String::from("gbsuv")]
+        );
+    }
+
+    #[test]
+    fn test_invalid_geohash() {
+        assert_eq!(Geohash::new("gbsua"), Err(Error::InvalidChar('a')));
+        assert_eq!(Geohash::new(""), Err(Error::Empty));
+    }
+}
@@ -3,6 +3,9 @@
 
 //! Metadata
 
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
 use url::Url;
 
 /// [`Metadata`] error
@@ -14,7 +17,10 @@ pub enum Error {
 }
 
 /// Metadata
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+///
+/// Invalid `picture` and `banner` URLs are kept as raw strings in the [`Metadata::custom`] fields.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(from = "MetadataRaw")]
 pub struct Metadata {
     /// Name
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,10 +36,10 @@ pub struct Metadata {
     pub website: Option<String>,
     /// Picture url
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub picture: Option<String>,
+    pub picture: Option<Url>,
     /// Banner url
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub banner: Option<String>,
+    pub banner: Option<Url>,
     /// NIP05 (ex. name@example.com)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nip05: Option<String>,
@@ -43,6 +49,84 @@ pub struct Metadata {
     /// Lightning Address
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lud16: Option<String>,
+    /// Custom fields
+    #[serde(flatten)]
+    pub custom: Map<String, Value>,
+}
+
+/// [`Metadata`] with unchecked `picture` and `banner` URLs
+#[derive(Deserialize)]
+struct MetadataRaw {
+    name: Option<String>,
+    display_name: Option<String>,
+    about: Option<String>,
+    website: Option<String>,
+    picture: Option<String>,
+    banner: Option<String>,
+    nip05: Option<String>,
+    lud06: Option<String>,
+    lud16: Option<String>,
+    #[serde(flatten)]
+    custom: Map<String, Value>,
+}
+
+impl From<MetadataRaw> for Metadata {
+    fn from(raw: MetadataRaw) -> Self {
+        let mut custom: Map<String, Value> = raw.custom;
+        let mut parse_url = |key: &str, value: Option<String>| -> Option<Url> {
+            let value: String = value?;
+            match Url::parse(&value) {
+                Ok(url) => Some(url),
+                Err(_) => {
+                    custom.insert(key.to_string(), Value::String(value));
+                    None
+                }
+            }
+        };
+        let picture: Option<Url> = parse_url("picture", raw.picture);
+        let banner: Option<Url> = parse_url("banner", raw.banner);
+
+        Self {
+            name: raw.name,
+            display_name: raw.display_name,
+            about: raw.about,
+            website: raw.website,
+            picture,
+            banner,
+            nip05: raw.nip05,
+            lud06: raw.lud06,
+            lud16: raw.lud16,
+            custom,
+        }
+    }
+}
+
+impl PartialOrd for Metadata {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Metadata {
+    /// Compare field by field: the custom fields are compared by key and serialized JSON value
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.display_name.cmp(&other.display_name))
+            .then_with(|| self.about.cmp(&other.about))
+            .then_with(|| self.website.cmp(&other.website))
+            .then_with(|| self.picture.cmp(&other.picture))
+            .then_with(|| self.banner.cmp(&other.banner))
+            .then_with(|| self.nip05.cmp(&other.nip05))
+            .then_with(|| self.lud06.cmp(&other.lud06))
+            .then_with(|| self.lud16.cmp(&other.lud16))
+            .then_with(|| {
+                let custom = |m: &Map<String, Value>| -> Vec<(String, String)> {
+                    m.iter().map(|(k, v)| (k.clone(), v.to_string())).collect()
+                };
+                custom(&self.custom).cmp(&custom(&other.custom))
+            })
+    }
 }
 
 impl Default for Metadata {
@@ -64,6 +148,7 @@ impl Metadata {
             nip05: None,
             lud06: None,
             lud16: None,
+            custom: Map::new(),
         }
     }
 
@@ -80,6 +165,13 @@ impl Metadata {
         serde_json::json!(self).to_string()
     }
 
+    /// Apply a JSON merge patch (RFC 7386): a `null` value removes the field
+    pub fn apply_merge_patch(self, patch: Value) -> Result<Self, Error> {
+        let mut value: Value = serde_json::to_value(self)?;
+        merge_patch(&mut value, patch);
+        Ok(serde_json::from_value(value)?)
+    }
+
     /// Set name
     pub fn name<S>(self, name: S) -> Self
     where
@@ -122,17 +214,19 @@ impl Metadata {
     }
 
     /// Set picture
-    pub fn picture(self, url: Url) -> Self {
+    pub fn picture(mut self, url: Url) -> Self {
+        self.custom.remove("picture");
         Self {
-            picture: Some(url.into()),
+            picture: Some(url),
             ..self
         }
     }
 
     /// Set banner
-    pub fn banner(self, url: Url) -> Self {
+    pub fn banner(mut self, url: Url) -> Self {
+        self.custom.remove("banner");
         Self {
-            banner: Some(url.into()),
+            banner: Some(url),
             ..self
         }
     }
@@ -169,12 +263,58 @@ impl Metadata {
             ..self
         }
     }
+
+    /// Set custom field
+    pub fn custom_field<S>(mut self, key: S, value: Value) -> Self
+    where
+        S: Into<String>,
+    {
+        self.custom.insert(key.into(), value);
+        self
+    }
+}
+
+fn merge_patch(target: &mut Value, patch: Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            if let Value::Object(target) = target {
+                for (key, value) in patch.into_iter() {
+                    if value.is_null() {
+                        target.remove(&key);
+                    } else {
+                        merge_patch(target.entry(key).or_insert(Value::Null), value);
+                    }
+                }
+            }
+        }
+        patch => *target = patch,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_metadata_ord() {
+        let alice = Metadata::new().name("alice");
+        let bob = Metadata::new().name("bob");
+        assert!(alice < bob);
+        assert_eq!(alice.cmp(&alice.clone()), Ordering::Equal);
+
+        let a = Metadata::from_json(r#"{"name":"alice","custom":1}"#).unwrap();
+        let b = Metadata::from_json(r#"{"name":"alice","custom":2}"#).unwrap();
+        assert!(a < b);
+        assert!(alice < a);
+
+        let mut profiles = vec![b.clone(), bob.clone(), a.clone(), alice.clone()];
+        profiles.sort();
+        assert_eq!(profiles, vec![alice, a, b, bob]);
+    }
+
     #[test]
     fn test_deserialize_metadata() {
         let content = r#"{"name":"myname","about":"Description","display_name":""}"#;
@@ -187,4 +327,94 @@ mod tests {
                 .display_name("")
         );
     }
+
+    #[test]
+    fn test_setters_serialization() {
+        let metadata = Metadata::new()
+            .name("myname")
+            .display_name("My Name")
+            .about("Description")
+            .website(Url::parse("https://example.com/home").unwrap())
+            .picture(Url::parse("https://example.com/avatar.png").unwrap())
+            .banner(Url::parse("https://example.com/banner.png").unwrap())
+            .nip05("myname@example.com")
+            .lud06("lnurl1dp68gurn8ghj7")
+            .lud16("myname@example.com")
+            .custom_field("bot", Value::Bool(true));
+
+        let json: Value = serde_json::from_str(&metadata.as_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "myname",
+                "display_name": "My Name",
+                "about": "Description",
+                "website": "https://example.com/home",
+                "picture": "https://example.com/avatar.png",
+                "banner": "https://example.com/banner.png",
+                "nip05": "myname@example.com",
+                "lud06": "lnurl1dp68gurn8ghj7",
+                "lud16": "myname@example.com",
+                "bot": true,
+            })
+        );
+        assert_eq!(Metadata::from_json(metadata.as_json()).unwrap(), metadata);
+        assert_eq!(Metadata::default(), Metadata::new());
+    }
+
+    #[test]
+    fn test_deserialize_metadata_urls() {
+        let metadata = Metadata::from_json(
+            r#"{"name":"myname","picture":"https://example.com/avatar.png","banner":"not a url"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            metadata.picture,
+            Some(Url::parse("https://example.com/avatar.png").unwrap())
+        );
+        assert_eq!(metadata.banner, None);
+        assert_eq!(
+            metadata.custom.get("banner"),
+            Some(&Value::String(String::from("not a url")))
+        );
+
+        // The raw string is serialized back as is
+        let json: Value = serde_json::from_str(&metadata.as_json()).unwrap();
+        assert_eq!(json["banner"], Value::String(String::from("not a url")));
+        assert_eq!(
+            json["picture"],
+            Value::String(String::from("https://example.com/avatar.png"))
+        );
+
+        // Setting a valid url replaces the raw string
+        let metadata = metadata.banner(Url::parse("https://example.com/banner.png").unwrap());
+        assert!(metadata.custom.get("banner").is_none());
+        assert_eq!(Metadata::from_json(metadata.as_json()).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_apply_merge_patch() {
+        let metadata = Metadata::from_json(
+            r#"{"name":"myname","about":"Old","website":"https://example.com","bot":true}"#,
+        )
+        .unwrap();
+
+        let patch = serde_json::json!({"about": "New", "website": null, "pronouns": "they/them"});
+        let metadata = metadata.apply_merge_patch(patch).unwrap();
+
+        assert_eq!(metadata.name, Some(String::from("myname")));
+        assert_eq!(metadata.about, Some(String::from("New")));
+        assert_eq!(metadata.website, None);
+        assert_eq!(metadata.custom.get("bot"), Some(&Value::Bool(true)));
+        assert_eq!(
+            metadata.custom.get("pronouns"),
+            Some(&Value::String(String::from("they/them")))
+        );
+
+        // Custom fields can be removed too
+        let metadata = metadata
+            .apply_merge_patch(serde_json::json!({"bot": null}))
+            .unwrap();
+        assert!(metadata.custom.get("bot").is_none());
+    }
 }
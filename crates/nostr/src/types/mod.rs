@@ -6,13 +6,17 @@
 pub mod channel_id;
 pub mod contact;
 pub mod entity;
+pub mod geohash;
 pub mod metadata;
 pub mod profile;
 pub mod time;
+pub mod url;
 
 pub use self::channel_id::ChannelId;
-pub use self::contact::Contact;
+pub use self::contact::{Contact, ContactList, RelayPermissions};
 pub use self::entity::Entity;
+pub use self::geohash::Geohash;
 pub use self::metadata::Metadata;
 pub use self::profile::Profile;
 pub use self::time::Timestamp;
+pub use self::url::UncheckedUrl;
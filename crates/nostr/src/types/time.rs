@@ -15,6 +15,7 @@ use std::{
 
 #[cfg(target_arch = "wasm32")]
 use instant::SystemTime;
+use secp256k1::rand::{self, Rng};
 
 #[cfg(target_arch = "wasm32")]
 const UNIX_EPOCH: SystemTime = SystemTime::UNIX_EPOCH;
@@ -23,6 +24,11 @@ const UNIX_EPOCH: SystemTime = SystemTime::UNIX_EPOCH;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Timestamp(i64);
 
+/// Max `created_at` tweak used by [`Timestamp::tweaked`] (2 days)
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+pub const MAX_TIMESTAMP_TWEAK: u64 = 2 * 24 * 60 * 60;
+
 impl Timestamp {
     /// Get UNIX timestamp
     pub fn now() -> Self {
@@ -33,6 +39,24 @@ impl Timestamp {
         Self(ts as i64)
     }
 
+    /// Get UNIX timestamp randomly tweaked in the past (up to 2 days)
+    ///
+    /// Used as `created_at` of seals and gift wraps to prevent timing correlation.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/59.md>
+    pub fn tweaked() -> Self {
+        Self::tweaked_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Get UNIX timestamp randomly tweaked in the past (up to 2 days) using a custom [`Rng`]
+    pub fn tweaked_with_rng<R>(rng: &mut R) -> Self
+    where
+        R: Rng,
+    {
+        let tweak: u64 = rng.gen_range(0..=MAX_TIMESTAMP_TWEAK);
+        Self::now() - tweak
+    }
+
     /// Get timestamp as [`u64`]
     pub fn as_u64(&self) -> u64 {
         if self.0 >= 0 {
@@ -46,6 +70,12 @@ impl Timestamp {
     pub fn as_i64(&self) -> i64 {
         self.0
     }
+
+    /// Timestamp from [`i64`] (negative values are preserved)
+    #[cfg(feature = "borsh")]
+    pub(crate) fn from_i64(timestamp: i64) -> Self {
+        Self(timestamp)
+    }
 }
 
 impl From<u64> for Timestamp {
@@ -108,3 +138,34 @@ impl Sub<i64> for Timestamp {
         Self(self.0.saturating_sub(rhs))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::rand::rngs::mock::StepRng;
+
+    use super::*;
+
+    #[test]
+    fn test_tweaked_timestamp() {
+        for _ in 0..100 {
+            let now = Timestamp::now();
+            let tweaked = Timestamp::tweaked();
+            assert!(tweaked <= Timestamp::now());
+            assert!(tweaked >= now - MAX_TIMESTAMP_TWEAK);
+        }
+
+        // Lowest random value: no tweak
+        let mut rng = StepRng::new(0, 0);
+        let before = Timestamp::now();
+        let tweaked = Timestamp::tweaked_with_rng(&mut rng);
+        let after = Timestamp::now();
+        assert!(tweaked == before || tweaked == after);
+
+        // Smallest random value mapped to the upper bound of `0..=MAX_TIMESTAMP_TWEAK`: max tweak
+        let mut rng = StepRng::new(u64::MAX - u64::MAX / (MAX_TIMESTAMP_TWEAK + 1) + 1, 0);
+        let before = Timestamp::now();
+        let tweaked = Timestamp::tweaked_with_rng(&mut rng);
+        let after = Timestamp::now();
+        assert!(tweaked == before - MAX_TIMESTAMP_TWEAK || tweaked == after - MAX_TIMESTAMP_TWEAK);
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Distributed under the MIT software license
+
+//! Url
+
+use std::fmt;
+use std::str::FromStr;
+
+use url::{ParseError, Url};
+
+/// Unchecked Url
+///
+/// Keep the url exactly as received (no normalization, no validation):
+/// parse it with [`Url::try_from`] when needed.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct UncheckedUrl(String);
+
+impl UncheckedUrl {
+    /// New unchecked url
+    pub fn new<S>(url: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self(url.into())
+    }
+
+    /// Empty unchecked url
+    pub fn empty() -> Self {
+        Self(String::new())
+    }
+}
+
+impl<S> From<S> for UncheckedUrl
+where
+    S: Into<String>,
+{
+    fn from(url: S) -> Self {
+        Self(url.into())
+    }
+}
+
+impl FromStr for UncheckedUrl {
+    type Err = ParseError;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(url))
+    }
+}
+
+impl TryFrom<UncheckedUrl> for Url {
+    type Error = ParseError;
+
+    fn try_from(unchecked_url: UncheckedUrl) -> Result<Url, Self::Error> {
+        Self::parse(&unchecked_url.0)
+    }
+}
+
+impl fmt::Display for UncheckedUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}